@@ -1,3 +1,51 @@
+use std::path::PathBuf;
+
 fn main() {
     divan::main()
 }
+
+// Generates a program of `functions` functions with calls, loops and branches
+fn generate_program(functions: usize) -> String {
+    let mut code = String::new();
+    for i in 0..functions {
+        code.push_str(&format!(
+            "fn function_{i}(a: i64, b: i64) -> i64 {{\n    let sum_{i} = a + b * {i};\n    \
+             while (sum_{i} < 100) {{\n        sum_{i} += 1;\n    }}\n    \
+             if (sum_{i} > 99) {{\n        println(\"large {{}}\", sum_{i});\n    }}\n    \
+             return sum_{i};\n}}\n"
+        ));
+    }
+    code
+}
+
+// Parses the tokens the lexer hands off by reference
+#[divan::bench(args = [100, 1000, 10000])]
+fn parse_large(bencher: divan::Bencher, functions: usize) {
+    let code = generate_program(functions);
+    let mut lexer = lexer::lex_new_new::Lexer::new(PathBuf::from("bench.nk"), &code);
+    let _ = lexer.run();
+    bencher
+        .counter(divan::counter::ItemsCount::new(lexer.token_count()))
+        .bench(|| {
+            let tokens = divan::black_box(lexer.tokens());
+            let mut parser =
+                astgen::parser_new::Parser::new(tokens, PathBuf::from("bench.nk"), &code);
+            parser.run()
+        });
+}
+
+// The same with a copy of the tokens made first, the cost the borrowed hand-off avoids
+#[divan::bench(args = [100, 1000, 10000])]
+fn parse_large_cloned(bencher: divan::Bencher, functions: usize) {
+    let code = generate_program(functions);
+    let mut lexer = lexer::lex_new_new::Lexer::new(PathBuf::from("bench.nk"), &code);
+    let _ = lexer.run();
+    bencher
+        .counter(divan::counter::ItemsCount::new(lexer.token_count()))
+        .bench(|| {
+            let tokens = divan::black_box(lexer.tokens().to_vec());
+            let mut parser =
+                astgen::parser_new::Parser::new(&tokens, PathBuf::from("bench.nk"), &code);
+            parser.run().map(|_| tokens.len())
+        });
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+// The interpreter lives in the binary crate, so it is included directly
+#[allow(dead_code)]
+#[path = "../src/interpreter/interpret_new/mod.rs"]
+mod interpret_new;
+
+fn main() {
+    divan::main()
+}
+
+// Declares `variables` variables and reads every one of them back
+fn generate_program(variables: usize) -> String {
+    let mut code = String::from("fn main() -> i64 {\n    let total = 0;\n");
+    for i in 0..variables {
+        code.push_str(&format!("    let v{i} = {i};\n"));
+    }
+    for i in 0..variables {
+        code.push_str(&format!("    total += v{i};\n"));
+    }
+    code.push_str("    return total;\n}\n");
+    code
+}
+
+#[divan::bench(args = [100, 1000, 10000])]
+fn variable_lookup(bencher: divan::Bencher, variables: usize) {
+    let code = generate_program(variables);
+    let mut lexer = lexer::lex_new_new::Lexer::new(PathBuf::from("bench.nk"), &code);
+    let _ = lexer.run();
+    let mut parser =
+        astgen::parser_new::Parser::new(lexer.tokens(), PathBuf::from("bench.nk"), &code);
+    let _ = parser.run();
+    let asts = parser.get_asts();
+    bencher
+        .counter(divan::counter::ItemsCount::new(variables * 2))
+        .bench(|| interpret_new::Interpreter::new().run(divan::black_box(asts)));
+}
+
+// Matches every value below 256 against 256 arms, spaced by `spacing`. Arms spaced by one are
+// dense enough for a jump table, wider spacing falls back to checking the arms in order.
+fn generate_match(spacing: usize) -> String {
+    let arms: Vec<String> = (0..256).map(|i| format!("{} => {}", i * spacing, i)).collect();
+    format!(
+        "fn main() -> i64 {{\n    let total = 0;\n    let i = 0;\n    \
+         for(i -> 256 :: 1) {{\n        total += match i * {} {{ {}, _ => 0 }};\n    }}\n    \
+         return total;\n}}\n",
+        spacing,
+        arms.join(", ")
+    )
+}
+
+#[divan::bench(args = [1, 3])]
+fn match_arms(bencher: divan::Bencher, spacing: usize) {
+    let code = generate_match(spacing);
+    let mut lexer = lexer::lex_new_new::Lexer::new(PathBuf::from("bench.nk"), &code);
+    let _ = lexer.run();
+    let mut parser =
+        astgen::parser_new::Parser::new(lexer.tokens(), PathBuf::from("bench.nk"), &code);
+    let _ = parser.run();
+    let asts = parser.get_asts();
+    bencher
+        .counter(divan::counter::ItemsCount::new(256usize))
+        .bench(|| interpret_new::Interpreter::new().run(divan::black_box(asts)));
+}
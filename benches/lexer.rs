@@ -53,3 +53,28 @@ fn trie_lexer() {
     }
     compute(divan::black_box(TEST_CODE))
 }
+
+// Generates a program of `functions` functions with a mix of every token kind
+fn generate_program(functions: usize) -> String {
+    let mut code = String::new();
+    for i in 0..functions {
+        code.push_str(&format!(
+            "fn function_{i}(i64:a, i64:b) -> i64 {{\n    let:i64 sum_{i} = a + b * {i};\n    // comment {i}\n    if (sum_{i} >= 100) {{\n        println(\"large {{}}\", sum_{i});\n    }}\n    return sum_{i};\n}}\n"
+        ));
+    }
+    code
+}
+
+#[divan::bench(args = [100, 1000, 10000])]
+fn new_new_lexer_large(bencher: divan::Bencher, functions: usize) {
+    let code = generate_program(functions);
+    bencher
+        .counter(BytesCount::of_str(&code))
+        .bench(|| {
+            let mut lexer = lexer::lex_new_new::Lexer::new(
+                std::path::Path::new("bench.nk").to_path_buf(),
+                divan::black_box(&code),
+            );
+            let _ = lexer.run();
+        });
+}
@@ -11,6 +11,7 @@ pub enum ASTOperator {
     Multiply,
     Divide,
     Remainder,
+    Power,
     BitAnd,
     BitOr,
     BitXor,
@@ -43,6 +44,7 @@ impl fmt::Display for ASTOperator {
             ASTOperator::Multiply => write!(f, "*"),
             ASTOperator::Divide => write!(f, "/"),
             ASTOperator::Remainder => write!(f, "%"),
+            ASTOperator::Power => write!(f, "**"),
             ASTOperator::BitAnd => write!(f, "&"),
             ASTOperator::BitOr => write!(f, "|"),
             ASTOperator::BitXor => write!(f, "^"),
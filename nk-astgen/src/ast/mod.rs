@@ -7,6 +7,7 @@ use std::fmt;
 pub use logic::ASTOperator;
 pub use logic::ASTlogic;
 pub use statement::ASTstatement;
+pub use types::ASTfunctiontype;
 pub use types::ASTtypecomp;
 pub use types::ASTtypename;
 pub use types::ASTtypevalue;
@@ -17,6 +17,10 @@ pub enum ASTstatement {
     // A node representing a statement
     // A node representing a function definition
     Function {
+        // Lines of the `///` doc comments written before the function
+        docs: Vec<String>,
+        // Names of the `@name` attributes written before the function
+        attributes: Vec<String>,
         public: bool,
         name: String,
         args: Vec<ASTtypecomp>,
@@ -29,6 +33,16 @@ pub enum ASTstatement {
         type_name: Option<ASTtypename>,
         value: Option<Box<AST>>,
     },
+    // `name := value;`, a `let` that may not redeclare a name of the same scope
+    Declare {
+        name: String,
+        value: Box<AST>,
+    },
+    // `let (a, b) = value;`, binds each value of a tuple to a name
+    Destructure {
+        names: Vec<String>,
+        value: Box<AST>,
+    },
     Assignment {
         left: Box<AST>,
         op: ASTOperator,
@@ -48,12 +62,28 @@ pub enum ASTstatement {
         statements: Vec<AST>,
     },
 
+    // Loops may be labeled, `'outer: for(...) { ... }`
     For {
+        label: Option<String>,
         start: ASTtypevalue,
         end: ASTtypevalue,
         value: ASTtypevalue,
         statements: Vec<AST>,
     },
+    While {
+        label: Option<String>,
+        condition: Box<AST>,
+        statements: Vec<AST>,
+    },
+    // Without a label they leave or restart the innermost loop
+    // `break value;` gives the value to the `loop` it leaves
+    Break {
+        label: Option<String>,
+        value: Option<Box<AST>>,
+    },
+    Continue {
+        label: Option<String>,
+    },
     Print {
         value: Box<AST>,
         args: Vec<AST>,
@@ -71,6 +101,8 @@ impl fmt::Display for ASTstatement {
         match self {
             ASTstatement::Import { name } => write!(f, "INJECT {}", name),
             ASTstatement::Function {
+                docs,
+                attributes,
                 public,
                 name,
                 args,
@@ -88,6 +120,12 @@ impl fmt::Display for ASTstatement {
                     .collect::<Vec<String>>()
                     .join("\n");
                 let pub_eval = if *public { "public " } else { "" };
+                for doc in docs {
+                    writeln!(f, "///{}", doc)?;
+                }
+                for attribute in attributes {
+                    writeln!(f, "@{}", attribute)?;
+                }
                 write!(
                     f,
                     "{}function {}({})  {}\n{{\n{}\n}}",
@@ -99,13 +137,18 @@ impl fmt::Display for ASTstatement {
                 type_name,
                 value,
             } => {
-                write!(
-                    f,
-                    "let {} : {} = {}",
-                    name,
-                    (*type_name).unwrap(),
-                    value.clone().unwrap()
-                )
+                write!(f, "let {}", name)?;
+                if let Some(type_name) = type_name {
+                    write!(f, " : {}", type_name)?;
+                }
+                match value {
+                    Some(value) => write!(f, " = {}", value),
+                    None => Ok(()),
+                }
+            }
+            ASTstatement::Declare { name, value } => write!(f, "{} := {}", name, value),
+            ASTstatement::Destructure { names, value } => {
+                write!(f, "let ({}) = {}", names.join(", "), value)
             }
             ASTstatement::Assignment { left, op, right } => {
                 write!(f, "{} {} {}", left, op, right)
@@ -164,11 +207,13 @@ impl fmt::Display for ASTstatement {
                 )
             }
             ASTstatement::For {
+                label,
                 start,
                 end,
                 value,
                 statements,
             } => {
+                write_label(f, label)?;
                 write!(
                     f,
                     "for {} {} {} {{\n{}\n}}",
@@ -182,6 +227,26 @@ impl fmt::Display for ASTstatement {
                         .join("\n")
                 )
             }
+            ASTstatement::While {
+                label,
+                condition,
+                statements,
+            } => {
+                write_label(f, label)?;
+                write!(
+                    f,
+                    "while {} {{\n{}\n}}",
+                    condition,
+                    statements
+                        .iter()
+                        .map(|x| x.to_string())
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                )
+            }
+            ASTstatement::Break { label, value } => write_break(f, label, value),
+            ASTstatement::Continue { label: None } => write!(f, "continue"),
+            ASTstatement::Continue { label: Some(label) } => write!(f, "continue '{}", label),
             ASTstatement::Print { value, args } => {
                 write!(f, "print {}", value);
                 for arg in args {
@@ -201,3 +266,23 @@ impl fmt::Display for ASTstatement {
         }
     }
 }
+fn write_break(
+    f: &mut fmt::Formatter<'_>,
+    label: &Option<String>,
+    value: &Option<Box<AST>>,
+) -> fmt::Result {
+    write!(f, "break")?;
+    if let Some(label) = label {
+        write!(f, " '{}", label)?;
+    }
+    match value {
+        Some(value) => write!(f, " {}", value),
+        None => Ok(()),
+    }
+}
+fn write_label(f: &mut fmt::Formatter<'_>, label: &Option<String>) -> fmt::Result {
+    match label {
+        Some(label) => write!(f, "'{}: ", label),
+        None => Ok(()),
+    }
+}
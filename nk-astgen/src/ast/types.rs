@@ -1,4 +1,5 @@
 use crate::AST;
+use lexer::neo_tokens::byte_string_literal;
 use std::fmt;
 /*pub enum ASTtype{
     TypeVoid
@@ -44,9 +45,13 @@ pub enum ASTtypename {
     QuotedString,
     Char,
     Array,
+    Map,
+    Bytes,
+    Tuple,
     Identifier,
     Argument,
     FunctionCall,
+    Function,
 }
 impl fmt::Display for ASTtypename {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -66,9 +71,13 @@ impl fmt::Display for ASTtypename {
             ASTtypename::QuotedString => write!(f, "QuotedString"),
             ASTtypename::Char => write!(f, "Char"),
             ASTtypename::Array => write!(f, "Array"),
+            ASTtypename::Map => write!(f, "Map"),
+            ASTtypename::Bytes => write!(f, "Bytes"),
+            ASTtypename::Tuple => write!(f, "Tuple"),
             ASTtypename::Identifier => write!(f, "Identifier"),
             ASTtypename::Argument => write!(f, "Argument"),
             ASTtypename::FunctionCall => write!(f, "FunctionCall"),
+            ASTtypename::Function => write!(f, "Function"),
         }
     }
 }
@@ -87,12 +96,37 @@ pub enum ASTtypevalue {
     U64(u64),
     //F32(f32),
     //F64(f64),
+    // The `none` literal, an absent optional value
+    None,
     Bool(bool),
     QuotedString(String),
     Char(char),
     Identifier(String),
     Array(Vec<ASTtypevalue>),
+    Map(Vec<(AST, AST)>),
+    Bytes(Vec<u8>),
+    // `(a, b)`, a fixed number of values read with `.0`, `.1`, ...
+    Tuple(Vec<AST>),
+    Index { target: Box<AST>, index: Box<AST> },
     FunctionCall { name: String, args: Vec<AST> },
+    // `target.name`, reads the `name` entry of the target
+    Field { target: Box<AST>, name: String },
+    // `target.name(args)`, calls `name` with the target as its first argument
+    MethodCall { target: Box<AST>, name: String, args: Vec<AST> },
+    // Arms are `pattern => value`, the `_` pattern matches anything
+    Match { value: Box<AST>, arms: Vec<(AST, AST)> },
+    // The `_` pattern of a match arm
+    Discard,
+    // `loop { ... }`, runs until a `break` and evaluates to the value of `break value;`
+    Loop { label: Option<String>, statements: Vec<AST> },
+    // `{ statements; value }`, a block without a final value evaluates to `Void`
+    Block { statements: Vec<AST>, value: Option<Box<AST>> },
+    // `panic(message)`, keeps the line of the call for the runtime error
+    Panic { message: Box<AST>, line: usize },
+    // `dbg(value)`, keeps the source text of the value to print it with the result
+    Debug { value: Box<AST>, source: String },
+    // `let Some(name) = value`, the condition of an `if let`, holds when the value is not `none`
+    SomePattern { name: String, value: Box<AST> },
     // FunctionCall(String),
 }
 impl fmt::Display for ASTtypevalue {
@@ -107,6 +141,9 @@ impl fmt::Display for ASTtypevalue {
             ASTtypevalue::U16(val) => write!(f, "{}", val),
             ASTtypevalue::U32(val) => write!(f, "{}", val),
             ASTtypevalue::U64(val) => write!(f, "{}", val),
+            ASTtypevalue::None => write!(f, "none"),
+            ASTtypevalue::Discard => write!(f, "_"),
+            ASTtypevalue::Bytes(bytes) => write!(f, "{}", byte_string_literal(bytes)),
             ASTtypevalue::Bool(val) => write!(f, "{}", val),
             ASTtypevalue::QuotedString(val) => write!(f, "{}", val),
             ASTtypevalue::Char(val) => write!(f, "{}", val),
@@ -117,6 +154,70 @@ impl fmt::Display for ASTtypevalue {
                 }
                 Ok(())
             }
+            ASTtypevalue::Map(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ASTtypevalue::Tuple(values) => write!(
+                f,
+                "({})",
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ASTtypevalue::Index { target, index } => write!(f, "{}[{}]", target, index),
+            ASTtypevalue::Match { value, arms } => write!(
+                f,
+                "match {} {{{}}}",
+                value,
+                arms.iter()
+                    .map(|(pattern, value)| format!("{} => {}", pattern, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ASTtypevalue::Panic { message, .. } => write!(f, "panic({})", message),
+            ASTtypevalue::Debug { source, .. } => write!(f, "dbg({})", source),
+            ASTtypevalue::SomePattern { name, value } => {
+                write!(f, "let Some({}) = {}", name, value)
+            }
+            ASTtypevalue::Field { target, name } => write!(f, "{}.{}", target, name),
+            ASTtypevalue::MethodCall { target, name, args } => write!(
+                f,
+                "{}.{}({})",
+                target,
+                name,
+                args.iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ASTtypevalue::Loop { label, statements } => {
+                if let Some(label) = label {
+                    write!(f, "'{}: ", label)?;
+                }
+                write!(f, "loop {{")?;
+                for statement in statements {
+                    write!(f, " {};", statement)?;
+                }
+                write!(f, " }}")
+            }
+            ASTtypevalue::Block { statements, value } => {
+                write!(f, "{{")?;
+                for statement in statements {
+                    write!(f, " {};", statement)?;
+                }
+                if let Some(value) = value {
+                    write!(f, " {}", value)?;
+                }
+                write!(f, " }}")
+            }
             ASTtypevalue::FunctionCall { name, args } => write!(
                 f,
                 "CALL {}({})",
@@ -137,6 +238,10 @@ pub enum ASTtypecomp {
     Argument {
         type_name: ASTtypename,
         identifier: ASTtypevalue,
+        // Value used when a trailing argument is omitted at the call site
+        default: Option<Box<AST>>,
+        // Parameter and return types of a `fn(...) -> T` argument
+        signature: Option<ASTfunctiontype>,
     },
 }
 impl fmt::Display for ASTtypecomp {
@@ -153,7 +258,41 @@ impl fmt::Display for ASTtypecomp {
             ASTtypecomp::Argument {
                 type_name,
                 identifier,
-            } => write!(f, "{} {}", type_name, identifier),
+                default,
+                signature,
+            } => {
+                match signature {
+                    Some(signature) => write!(f, "{} {}", signature, identifier)?,
+                    None => write!(f, "{} {}", type_name, identifier)?,
+                }
+                match default {
+                    Some(default) => write!(f, " = {}", default),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct ASTfunctiontype {
+    pub params: Vec<ASTtypename>,
+    pub return_type: ASTtypename,
+}
+impl fmt::Display for ASTfunctiontype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fn({})",
+            self.params
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )?;
+        match self.return_type {
+            ASTtypename::TypeVoid => Ok(()),
+            return_type => write!(f, " -> {}", return_type),
         }
     }
 }
@@ -265,6 +265,8 @@ impl<'a> Parser<'a> {
         let statements = self.parse_statement();
 
         self.asts.push(AST::Statement(ASTstatement::Function {
+            docs: Vec::new(),
+            attributes: Vec::new(),
             public: is_public,
             name: function_name,
             args: arguments,
@@ -826,6 +828,7 @@ impl<'a> Parser<'a> {
         let statements = self.parse_statement();
 
         AST::Statement(ASTstatement::For {
+            label: None,
             start: start_val,
             end: end_val,
             value: val,
@@ -907,6 +910,8 @@ impl<'a> Parser<'a> {
                     args.push(ASTtypecomp::Argument {
                         identifier: ident_name,
                         type_name: cur_type,
+                        default: None,
+                        signature: None,
                     });
                     state = ArgumentParseState::WaitForCommaOrCloseParen;
                     cur_type = ASTtypename::TypeVoid;
@@ -2,21 +2,81 @@ use lexer::neo_tokens::Token;
 
 use std::fmt;
 
+#[derive(Debug)]
 #[allow(missing_docs)]
 #[allow(dead_code)]
 pub struct AstGenError {
-    //pub line: usize,
-    //pub column: usize,
+    pub line: usize,
+    pub column: usize,
     pub message: AstError,
     pub pretty_display: String,
 }
 impl AstGenError {
     pub fn new(message: AstError) -> Self {
         AstGenError {
+            line: 0,
+            column: 0,
             message,
             pretty_display: "".to_string(),
         }
     }
+    // A hint on how to fix the error, shown below it
+    pub fn suggestion(&self) -> String {
+        match &self.message {
+            AstError::ExpectedToken(ref t) => format!(
+                "Add {} on line:{}, col{}",
+                t.token_type, t.metadata.line, t.metadata.column
+            ),
+            AstError::ExpectedExpression() => "Expected an expression. Check syntax.".to_string(),
+            AstError::ExpectedStatement() => "Expected a statement. Check syntax.".to_string(),
+            AstError::UnexpectedToken() => "Unexpected token. Check syntax.".to_string(),
+            AstError::InvalidNumberFormat(num) => format!(
+                "Ensure the number is correctly formatted. Invalid input: '{}'",
+                num
+            ),
+            AstError::UnexpectedEOF() => {
+                "Unexpected end of file. Check for missing tokens.".to_string()
+            }
+            AstError::MismatchedArgumentCount(a, b) => format!(
+                "Mismatched argument count, Check the arguments. Expected: {}, Found: {}",
+                a, b
+            ),
+            AstError::DivisionByZero() => {
+                "The divisor is always zero. Check the expression.".to_string()
+            }
+            AstError::MissingDefaultValue(name) => format!(
+                "Give `{}` a default value or move it before the defaulted parameters",
+                name
+            ),
+            AstError::UnknownLabel(label) => {
+                format!("Label a loop around this statement with '{}:", label)
+            }
+            AstError::OutsideLoop(keyword) => {
+                format!("Move `{}` into the body of a `for`, `while` or `loop`", keyword)
+            }
+            AstError::BreakValueOutsideLoop() => {
+                "Use `loop` for a loop that breaks with a value.".to_string()
+            }
+            AstError::NonConstantArraySize(_) => {
+                "Use literals and arithmetic on them for the size.".to_string()
+            }
+            AstError::NegativeArraySize(_) => "Use a size of zero or more.".to_string(),
+            AstError::AssignmentInCondition() => {
+                "Use `==` to compare, or wrap an intended assignment in a second pair of \
+                 parentheses."
+                    .to_string()
+            }
+            AstError::MissingParens(name) => {
+                format!("Write `{}()`, functions without parameters still need `()`", name)
+            }
+            AstError::NotOptional(_) => {
+                "Use a plain `if` or `let` for a value that is always present.".to_string()
+            }
+            AstError::LiteralOverflow(_, type_name) => {
+                format!("Use a value in the range of {} or a wider type.", type_name)
+            }
+        }
+    }
 }
 impl fmt::Display for AstGenError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -34,6 +94,25 @@ pub enum AstError {
     InvalidNumberFormat(String),
     UnexpectedEOF(),
     MismatchedArgumentCount(usize, usize),
+    DivisionByZero(),
+    MissingDefaultValue(String),
+    // `break 'name` or `continue 'name` without an enclosing loop labeled `'name`
+    UnknownLabel(String),
+    // `break` or `continue` that is not inside a loop
+    OutsideLoop(String),
+    // `break value;` leaving a `for` or `while`, only `loop` has a value
+    BreakValueOutsideLoop(),
+    // The size of a `[type; size]` array that did not fold to an integer
+    NonConstantArraySize(String),
+    NegativeArraySize(i64),
+    // `if (a = b)`, most likely a typo of `==`
+    AssignmentInCondition(),
+    // A function definition or call whose name is not followed by `(`
+    MissingParens(String),
+    // The value of an `if let Some(x) = value` that can never be `none`
+    NotOptional(String),
+    // An integer literal assigned to a typed binding that can not hold it
+    LiteralOverflow(i64, String),
 }
 impl fmt::Display for AstError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -47,6 +126,51 @@ impl fmt::Display for AstError {
             AstError::MismatchedArgumentCount(a, b) => {
                 write!(f, "Mismatched argument count: {} vs {}", a, b)
             }
+            AstError::DivisionByZero() => write!(f, "Division by zero in constant expression"),
+            AstError::MissingDefaultValue(name) => {
+                write!(f, "Parameter `{}` after a defaulted parameter needs a default", name)
+            }
+            AstError::UnknownLabel(label) => write!(f, "Unknown loop label '{}", label),
+            AstError::OutsideLoop(keyword) => write!(f, "`{}` outside of a loop", keyword),
+            AstError::BreakValueOutsideLoop() => {
+                write!(f, "`break` with a value outside of a `loop`")
+            }
+            AstError::NonConstantArraySize(size) => {
+                write!(f, "Array size `{}` is not a constant integer", size)
+            }
+            AstError::NegativeArraySize(size) => write!(f, "Array size {} is negative", size),
+            AstError::AssignmentInCondition() => {
+                write!(f, "Assignment used as a condition, did you mean '=='?")
+            }
+            AstError::MissingParens(_) => write!(f, "expected '(' after function name"),
+            AstError::NotOptional(value) => {
+                write!(f, "`{}` is never none, `if let Some(..)` needs an optional value", value)
+            }
+            AstError::LiteralOverflow(n, type_name) => {
+                write!(f, "Literal {} does not fit in {}", n, type_name)
+            }
+        }
+    }
+}
+// Likely mistakes that do not stop parsing
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AstWarning {
+    // The `return`, `break` or `continue` and the line of the first statement after it
+    // in the same block
+    UnreachableCode(String, usize),
+    // An attribute the compiler does not know, and its line
+    UnknownAttribute(String, usize),
+}
+impl fmt::Display for AstWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AstWarning::UnreachableCode(keyword, line) => {
+                write!(f, "unreachable code after {} at line {}", keyword, line)
+            }
+            AstWarning::UnknownAttribute(name, line) => {
+                write!(f, "unknown attribute `@{}` at line {}", name, line)
+            }
         }
     }
 }
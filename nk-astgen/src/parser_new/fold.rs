@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use super::error::{AstError, AstGenError};
+use crate::ast::*;
+
+// Constant Folding Example
+// 2 * 3 + 1 => 7, "a" + "b" => "ab"
+// Only operations whose operands are all literals are folded
+pub fn fold(ast: AST) -> Result<AST, AstGenError> {
+    match ast {
+        AST::Statement(statement) => Ok(AST::Statement(fold_statement(statement)?)),
+        AST::TypeValue(value) => Ok(AST::TypeValue(fold_value(value)?)),
+        AST::Logic(ASTlogic::BinaryOperation { left, op, right }) => {
+            let left = fold(*left)?;
+            let right = fold(*right)?;
+            if let (AST::TypeValue(l), AST::TypeValue(r)) = (&left, &right) {
+                if let Some(value) = fold_literals(l, &op, r)? {
+                    return Ok(AST::TypeValue(value));
+                }
+            }
+            Ok(AST::Logic(ASTlogic::BinaryOperation {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            }))
+        }
+        other => Ok(other),
+    }
+}
+// Replaces the names in an expression that have a known integer value, so `fold` can fold
+// them like literals
+pub fn substitute(ast: AST, constants: &HashMap<String, i64>) -> AST {
+    match ast {
+        AST::TypeValue(ASTtypevalue::Identifier(name)) => match constants.get(&name) {
+            Some(n) => AST::TypeValue(ASTtypevalue::I64(*n)),
+            None => AST::TypeValue(ASTtypevalue::Identifier(name)),
+        },
+        AST::Logic(ASTlogic::BinaryOperation { left, op, right }) => {
+            AST::Logic(ASTlogic::BinaryOperation {
+                left: Box::new(substitute(*left, constants)),
+                op,
+                right: Box::new(substitute(*right, constants)),
+            })
+        }
+        other => other,
+    }
+}
+pub fn fold_all(asts: Vec<AST>) -> Result<Vec<AST>, AstGenError> {
+    asts.into_iter().map(fold).collect()
+}
+fn fold_boxed(ast: AST) -> Result<Box<AST>, AstGenError> {
+    fold(ast).map(Box::new)
+}
+
+fn fold_statement(statement: ASTstatement) -> Result<ASTstatement, AstGenError> {
+    Ok(match statement {
+        ASTstatement::Function {
+            docs,
+            attributes,
+            public,
+            name,
+            args,
+            statements,
+            return_type,
+        } => ASTstatement::Function {
+            docs,
+            attributes,
+            public,
+            name,
+            args,
+            statements: fold_all(statements)?,
+            return_type,
+        },
+        ASTstatement::Let {
+            name,
+            type_name,
+            value,
+        } => ASTstatement::Let {
+            name,
+            type_name,
+            value: value.map(|ast| fold_boxed(*ast)).transpose()?,
+        },
+        ASTstatement::Declare { name, value } => ASTstatement::Declare {
+            name,
+            value: fold_boxed(*value)?,
+        },
+        ASTstatement::Destructure { names, value } => ASTstatement::Destructure {
+            names,
+            value: fold_boxed(*value)?,
+        },
+        ASTstatement::Assignment { left, op, right } => ASTstatement::Assignment {
+            left: fold_boxed(*left)?,
+            op,
+            right: fold_boxed(*right)?,
+        },
+        ASTstatement::If {
+            condition,
+            statements,
+            elif,
+            else_statements,
+        } => ASTstatement::If {
+            condition: fold_boxed(*condition)?,
+            statements: fold_all(statements)?,
+            elif: elif.map(|ast| fold_boxed(*ast)).transpose()?,
+            else_statements: else_statements.map(fold_all).transpose()?,
+        },
+        ASTstatement::For {
+            label,
+            start,
+            end,
+            value,
+            statements,
+        } => ASTstatement::For {
+            label,
+            start,
+            end,
+            value,
+            statements: fold_all(statements)?,
+        },
+        ASTstatement::While {
+            label,
+            condition,
+            statements,
+        } => ASTstatement::While {
+            label,
+            condition: fold_boxed(*condition)?,
+            statements: fold_all(statements)?,
+        },
+        ASTstatement::Print { value, args } => ASTstatement::Print {
+            value: fold_boxed(*value)?,
+            args: fold_all(args)?,
+        },
+        ASTstatement::Println { value, args } => ASTstatement::Println {
+            value: fold_boxed(*value)?,
+            args: fold_all(args)?,
+        },
+        ASTstatement::Return { value } => ASTstatement::Return {
+            value: fold_boxed(*value)?,
+        },
+        ASTstatement::Break { label, value } => ASTstatement::Break {
+            label,
+            value: value.map(|value| fold_boxed(*value)).transpose()?,
+        },
+        other => other,
+    })
+}
+fn fold_value(value: ASTtypevalue) -> Result<ASTtypevalue, AstGenError> {
+    Ok(match value {
+        ASTtypevalue::Map(entries) => ASTtypevalue::Map(
+            entries
+                .into_iter()
+                .map(|(key, value)| Ok((fold(key)?, fold(value)?)))
+                .collect::<Result<Vec<(AST, AST)>, AstGenError>>()?,
+        ),
+        ASTtypevalue::Tuple(values) => ASTtypevalue::Tuple(fold_all(values)?),
+        ASTtypevalue::Index { target, index } => ASTtypevalue::Index {
+            target: fold_boxed(*target)?,
+            index: fold_boxed(*index)?,
+        },
+        ASTtypevalue::FunctionCall { name, args } => ASTtypevalue::FunctionCall {
+            name,
+            args: fold_all(args)?,
+        },
+        ASTtypevalue::Field { target, name } => ASTtypevalue::Field {
+            target: fold_boxed(*target)?,
+            name,
+        },
+        ASTtypevalue::MethodCall { target, name, args } => ASTtypevalue::MethodCall {
+            target: fold_boxed(*target)?,
+            name,
+            args: fold_all(args)?,
+        },
+        ASTtypevalue::Block { statements, value } => ASTtypevalue::Block {
+            statements: fold_all(statements)?,
+            value: value.map(|value| fold_boxed(*value)).transpose()?,
+        },
+        ASTtypevalue::Loop { label, statements } => ASTtypevalue::Loop {
+            label,
+            statements: fold_all(statements)?,
+        },
+        ASTtypevalue::Panic { message, line } => ASTtypevalue::Panic {
+            message: fold_boxed(*message)?,
+            line,
+        },
+        ASTtypevalue::Debug { value, source } => ASTtypevalue::Debug {
+            value: fold_boxed(*value)?,
+            source,
+        },
+        ASTtypevalue::SomePattern { name, value } => ASTtypevalue::SomePattern {
+            name,
+            value: fold_boxed(*value)?,
+        },
+        ASTtypevalue::Match { value, arms } => ASTtypevalue::Match {
+            value: fold_boxed(*value)?,
+            arms: arms
+                .into_iter()
+                .map(|(pattern, value)| Ok((fold(pattern)?, fold(value)?)))
+                .collect::<Result<Vec<(AST, AST)>, AstGenError>>()?,
+        },
+        other => other,
+    })
+}
+
+fn fold_literals(
+    left: &ASTtypevalue,
+    op: &ASTOperator,
+    right: &ASTtypevalue,
+) -> Result<Option<ASTtypevalue>, AstGenError> {
+    match (left, right) {
+        (ASTtypevalue::I64(a), ASTtypevalue::I64(b)) => fold_integers(*a, op, *b),
+        (ASTtypevalue::QuotedString(a), ASTtypevalue::QuotedString(b))
+            if *op == ASTOperator::Add =>
+        {
+            Ok(Some(ASTtypevalue::QuotedString(format!("{}{}", a, b))))
+        }
+        (ASTtypevalue::Bool(a), ASTtypevalue::Bool(b)) => Ok(fold_bools(*a, op, *b)),
+        _ => Ok(None),
+    }
+}
+// Overflowing operations are left to be reported at runtime
+fn fold_integers(a: i64, op: &ASTOperator, b: i64) -> Result<Option<ASTtypevalue>, AstGenError> {
+    let value = match op {
+        ASTOperator::Add => a.checked_add(b).map(ASTtypevalue::I64),
+        ASTOperator::Subtract => a.checked_sub(b).map(ASTtypevalue::I64),
+        ASTOperator::Multiply => a.checked_mul(b).map(ASTtypevalue::I64),
+        ASTOperator::Divide | ASTOperator::Remainder if b == 0 => {
+            return Err(AstGenError::new(AstError::DivisionByZero()))
+        }
+        ASTOperator::Divide => a.checked_div(b).map(ASTtypevalue::I64),
+        ASTOperator::Remainder => a.checked_rem(b).map(ASTtypevalue::I64),
+        ASTOperator::Power => u32::try_from(b)
+            .ok()
+            .and_then(|b| a.checked_pow(b))
+            .map(ASTtypevalue::I64),
+        ASTOperator::BitAnd => Some(ASTtypevalue::I64(a & b)),
+        ASTOperator::BitOr => Some(ASTtypevalue::I64(a | b)),
+        ASTOperator::BitXor => Some(ASTtypevalue::I64(a ^ b)),
+        ASTOperator::Equals => Some(ASTtypevalue::Bool(a == b)),
+        ASTOperator::NotEquals => Some(ASTtypevalue::Bool(a != b)),
+        ASTOperator::Less => Some(ASTtypevalue::Bool(a < b)),
+        ASTOperator::LessEquals => Some(ASTtypevalue::Bool(a <= b)),
+        ASTOperator::Greater => Some(ASTtypevalue::Bool(a > b)),
+        ASTOperator::GreaterEquals => Some(ASTtypevalue::Bool(a >= b)),
+        _ => None,
+    };
+    Ok(value)
+}
+fn fold_bools(a: bool, op: &ASTOperator, b: bool) -> Option<ASTtypevalue> {
+    match op {
+        ASTOperator::And => Some(ASTtypevalue::Bool(a && b)),
+        ASTOperator::Or => Some(ASTtypevalue::Bool(a || b)),
+        ASTOperator::Equals => Some(ASTtypevalue::Bool(a == b)),
+        ASTOperator::NotEquals => Some(ASTtypevalue::Bool(a != b)),
+        _ => None,
+    }
+}
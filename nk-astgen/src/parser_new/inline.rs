@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+
+// Function Inlining Example
+// fn square(x: i64) -> i64 { return x * x; }
+// square(a + 1) => { let:i64 x = a + 1; let:i64 x = x * x; x }
+// The arguments are still evaluated once and in order, and the result keeps the return type
+// The result is stored in the last parameter, so functions without parameters are kept
+pub fn optimize(asts: Vec<AST>) -> Vec<AST> {
+    let inlinable = inlinable_functions(&asts);
+    let mut asts: Vec<AST> = asts
+        .into_iter()
+        .map(|mut ast| {
+            walk(&mut ast, &mut |value| inline_call(value, &inlinable));
+            ast
+        })
+        .collect();
+    remove_dead_functions(&mut asts);
+    asts
+}
+
+// A function that is only `return expr;`, where `expr` uses nothing but its parameters
+struct Inlinable {
+    params: Vec<(String, ASTtypename)>,
+    return_type: ASTtypename,
+    value: AST,
+}
+
+fn inlinable_functions(asts: &[AST]) -> HashMap<String, Inlinable> {
+    let mut defined = HashSet::new();
+    let mut redefined = HashSet::new();
+    let mut functions = HashMap::new();
+    for ast in asts {
+        let AST::Statement(ASTstatement::Function {
+            name,
+            args,
+            statements,
+            return_type,
+            ..
+        }) = ast
+        else {
+            continue;
+        };
+        if !defined.insert(name.clone()) {
+            redefined.insert(name.clone());
+        }
+        if let Some(function) = as_inlinable(name, args, statements, *return_type) {
+            functions.insert(name.clone(), function);
+        }
+    }
+    functions.retain(|name, _| !redefined.contains(name));
+    functions
+}
+fn as_inlinable(
+    name: &str,
+    args: &[ASTtypecomp],
+    statements: &[AST],
+    return_type: ASTtypename,
+) -> Option<Inlinable> {
+    if name == "main" || return_type == ASTtypename::TypeVoid {
+        return None;
+    }
+    let [AST::Statement(ASTstatement::Return { value })] = statements else {
+        return None;
+    };
+    let params = args
+        .iter()
+        .map(|arg| match arg {
+            ASTtypecomp::Argument {
+                type_name,
+                identifier: ASTtypevalue::Identifier(ident),
+                default: None,
+                signature: None,
+            } => Some((ident.clone(), *type_name)),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (identifiers, has_block) = identifiers(value);
+    let only_params = identifiers
+        .iter()
+        .all(|ident| params.iter().any(|(param, _)| param == ident));
+    if has_block || !only_params || params.is_empty() {
+        return None;
+    }
+    Some(Inlinable {
+        params,
+        return_type,
+        value: (**value).clone(),
+    })
+}
+
+// Calls are only replaced when nothing in the arguments could be shadowed by a parameter
+fn inline_call(value: &mut ASTtypevalue, inlinable: &HashMap<String, Inlinable>) {
+    let ASTtypevalue::FunctionCall { name, args } = value else {
+        return;
+    };
+    let Some(function) = inlinable.get(name.as_str()) else {
+        return;
+    };
+    if args.len() != function.params.len() {
+        return;
+    }
+    let shadowed = args.iter().any(|arg| {
+        let (identifiers, _) = identifiers(arg);
+        identifiers
+            .iter()
+            .any(|ident| function.params.iter().any(|(param, _)| param == ident))
+    });
+    if shadowed {
+        return;
+    }
+    let mut statements: Vec<AST> = function
+        .params
+        .iter()
+        .zip(std::mem::take(args))
+        .map(|((param, type_name), arg)| {
+            AST::Statement(ASTstatement::Let {
+                name: param.clone(),
+                type_name: Some(*type_name),
+                value: Some(Box::new(arg)),
+            })
+        })
+        .collect();
+    let Some((result, _)) = function.params.last() else {
+        return;
+    };
+    statements.push(AST::Statement(ASTstatement::Let {
+        name: result.clone(),
+        type_name: Some(function.return_type),
+        value: Some(Box::new(function.value.clone())),
+    }));
+    *value = ASTtypevalue::Block {
+        statements,
+        value: Some(Box::new(AST::TypeValue(ASTtypevalue::Identifier(
+            result.clone(),
+        )))),
+    };
+}
+
+// Drops the functions that can not be reached from `main`, nothing is removed without a `main`
+fn remove_dead_functions(asts: &mut Vec<AST>) {
+    let mut calls: HashMap<String, HashSet<String>> = HashMap::new();
+    for ast in asts.iter_mut() {
+        if let AST::Statement(ASTstatement::Function { name, .. }) = ast {
+            let name = name.clone();
+            let called = calls.entry(name).or_default();
+            // An identifier may name a function that is used as a value
+            walk(ast, &mut |value| match value {
+                ASTtypevalue::FunctionCall { name, .. }
+                | ASTtypevalue::MethodCall { name, .. }
+                | ASTtypevalue::Identifier(name) => {
+                    called.insert(name.clone());
+                }
+                _ => {}
+            });
+        }
+    }
+    if !calls.contains_key("main") {
+        return;
+    }
+    let mut reachable = HashSet::new();
+    let mut pending = vec!["main".to_string()];
+    while let Some(name) = pending.pop() {
+        if reachable.insert(name.clone()) {
+            pending.extend(calls.get(&name).into_iter().flatten().cloned());
+        }
+    }
+    asts.retain(|ast| match ast {
+        AST::Statement(ASTstatement::Function { name, .. }) => reachable.contains(name),
+        _ => true,
+    });
+}
+
+// Every identifier used in the expression, and whether it contains a block
+fn identifiers(ast: &AST) -> (HashSet<String>, bool) {
+    let mut identifiers = HashSet::new();
+    let mut has_block = false;
+    walk(&mut ast.clone(), &mut |value| match value {
+        ASTtypevalue::Identifier(ident) => {
+            identifiers.insert(ident.clone());
+        }
+        ASTtypevalue::Block { .. } => has_block = true,
+        _ => {}
+    });
+    (identifiers, has_block)
+}
+
+// Visits every value of the tree, the children of a value before the value itself
+fn walk(ast: &mut AST, f: &mut dyn FnMut(&mut ASTtypevalue)) {
+    match ast {
+        AST::Statement(statement) => walk_statement(statement, f),
+        AST::TypeValue(value) => walk_value(value, f),
+        AST::TypeComp(comp) => walk_comp(comp, f),
+        AST::Logic(ASTlogic::BinaryOperation { left, right, .. }) => {
+            walk(left, f);
+            walk(right, f);
+        }
+        AST::TypeName(_) | AST::Operator(_) => {}
+    }
+}
+pub fn walk_all(asts: &mut [AST], f: &mut dyn FnMut(&mut ASTtypevalue)) {
+    for ast in asts {
+        walk(ast, f);
+    }
+}
+fn walk_statement(statement: &mut ASTstatement, f: &mut dyn FnMut(&mut ASTtypevalue)) {
+    match statement {
+        ASTstatement::Function {
+            args, statements, ..
+        } => {
+            for arg in args {
+                walk_comp(arg, f);
+            }
+            walk_all(statements, f);
+        }
+        ASTstatement::Let { value, .. } | ASTstatement::Break { value, .. } => {
+            if let Some(value) = value {
+                walk(value, f);
+            }
+        }
+        ASTstatement::Declare { value, .. }
+        | ASTstatement::Destructure { value, .. }
+        | ASTstatement::Return { value } => walk(value, f),
+        ASTstatement::Assignment { left, right, .. } => {
+            walk(left, f);
+            walk(right, f);
+        }
+        ASTstatement::If {
+            condition,
+            statements,
+            elif,
+            else_statements,
+        } => {
+            walk(condition, f);
+            walk_all(statements, f);
+            if let Some(elif) = elif {
+                walk(elif, f);
+            }
+            if let Some(else_statements) = else_statements {
+                walk_all(else_statements, f);
+            }
+        }
+        ASTstatement::ElseIf {
+            condition,
+            statements,
+        } => {
+            walk_all(condition, f);
+            walk_all(statements, f);
+        }
+        ASTstatement::Else { statements } => walk_all(statements, f),
+        ASTstatement::For {
+            start,
+            end,
+            value,
+            statements,
+            ..
+        } => {
+            walk_value(start, f);
+            walk_value(end, f);
+            walk_value(value, f);
+            walk_all(statements, f);
+        }
+        ASTstatement::While {
+            condition,
+            statements,
+            ..
+        } => {
+            walk(condition, f);
+            walk_all(statements, f);
+        }
+        ASTstatement::Print { value, args } | ASTstatement::Println { value, args } => {
+            walk(value, f);
+            walk_all(args, f);
+        }
+        ASTstatement::Import { .. } | ASTstatement::Continue { .. } => {}
+    }
+}
+fn walk_comp(comp: &mut ASTtypecomp, f: &mut dyn FnMut(&mut ASTtypevalue)) {
+    match comp {
+        ASTtypecomp::Array(values) => {
+            for value in values {
+                walk_value(value, f);
+            }
+        }
+        ASTtypecomp::Argument { default, .. } => {
+            if let Some(default) = default {
+                walk(default, f);
+            }
+        }
+    }
+}
+fn walk_value(value: &mut ASTtypevalue, f: &mut dyn FnMut(&mut ASTtypevalue)) {
+    match value {
+        ASTtypevalue::Array(values) => {
+            for value in values.iter_mut() {
+                walk_value(value, f);
+            }
+        }
+        ASTtypevalue::Map(entries) => {
+            for (key, value) in entries.iter_mut() {
+                walk(key, f);
+                walk(value, f);
+            }
+        }
+        ASTtypevalue::Match { value, arms } => {
+            walk(value, f);
+            for (pattern, value) in arms.iter_mut() {
+                walk(pattern, f);
+                walk(value, f);
+            }
+        }
+        ASTtypevalue::Tuple(values) | ASTtypevalue::Loop { statements: values, .. } => {
+            walk_all(values, f)
+        }
+        ASTtypevalue::Index { target, index } => {
+            walk(target, f);
+            walk(index, f);
+        }
+        ASTtypevalue::FunctionCall { args, .. } => walk_all(args, f),
+        ASTtypevalue::Field { target, .. } => walk(target, f),
+        ASTtypevalue::MethodCall { target, args, .. } => {
+            walk(target, f);
+            walk_all(args, f);
+        }
+        ASTtypevalue::Block { statements, value } => {
+            walk_all(statements, f);
+            if let Some(value) = value {
+                walk(value, f);
+            }
+        }
+        ASTtypevalue::Panic { message, .. } => walk(message, f),
+        ASTtypevalue::Debug { value, .. } | ASTtypevalue::SomePattern { value, .. } => {
+            walk(value, f)
+        }
+        _ => {}
+    }
+    f(value);
+}
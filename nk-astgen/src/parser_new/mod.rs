@@ -2,14 +2,20 @@ use inksac::{Color, Style, Stylish};
 use lexer::neo_tokens::{*};
 
 use std::collections::HashMap;
-use std::iter::{Cloned, Peekable};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 mod error;
-use error::{AstError, AstGenError};
+mod fold;
+mod inline;
+pub use error::{AstError, AstGenError, AstWarning};
+pub use inline::walk_all;
 
 use crate::ast::*;
 
+// Attributes that are accepted without a warning, the interpreter only acts on `memoize`
+const KNOWN_ATTRIBUTES: [&str; 3] = ["inline", "deprecated", "memoize"];
+
 const ERRORTXTSTYLE: Style = Style {
     foreground: Color::Red,
     background: Color::Empty,
@@ -19,6 +25,47 @@ const ERRORTXTSTYLE: Style = Style {
     underline: false,
 };
 
+// `{}` and `{:spec}` of a print format string
+fn count_placeholders(format: &str) -> usize {
+    format.matches("{}").count() + format.matches("{:").count()
+}
+
+// The value the elements of a new fixed size array start with
+fn zero_value(type_name: ASTtypename) -> Option<ASTtypevalue> {
+    match type_name {
+        ASTtypename::I8 => Some(ASTtypevalue::I8(0)),
+        ASTtypename::I16 => Some(ASTtypevalue::I16(0)),
+        ASTtypename::I32 => Some(ASTtypevalue::I32(0)),
+        ASTtypename::I64 => Some(ASTtypevalue::I64(0)),
+        ASTtypename::U8 => Some(ASTtypevalue::U8(0)),
+        ASTtypename::U16 => Some(ASTtypevalue::U16(0)),
+        ASTtypename::U32 => Some(ASTtypevalue::U32(0)),
+        ASTtypename::U64 => Some(ASTtypevalue::U64(0)),
+        ASTtypename::Bool => Some(ASTtypevalue::Bool(false)),
+        ASTtypename::QuotedString => Some(ASTtypevalue::QuotedString(String::new())),
+        _ => None,
+    }
+}
+// Whether an integer literal is in the range of the declared type
+fn literal_fits(type_name: ASTtypename, n: i64) -> bool {
+    match type_name {
+        ASTtypename::I8 => i8::try_from(n).is_ok(),
+        ASTtypename::I16 => i16::try_from(n).is_ok(),
+        ASTtypename::I32 => i32::try_from(n).is_ok(),
+        ASTtypename::U8 => u8::try_from(n).is_ok(),
+        ASTtypename::U16 => u16::try_from(n).is_ok(),
+        ASTtypename::U32 => u32::try_from(n).is_ok(),
+        ASTtypename::U64 => u64::try_from(n).is_ok(),
+        _ => true,
+    }
+}
+
+// Errors are colored when stderr is a terminal, unless `NO_COLOR` is set
+fn color_by_default() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stderr().is_terminal()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum State {
     EmptyState,
@@ -35,13 +82,40 @@ enum ArgumentParseState {
     WaitForColon,
     WaitForIdentifier,
     WaitForCommaOrCloseParen,
+    // `name: type` form
+    WaitForNameColon,
+    WaitForNameType,
 }
 pub struct Parser<'a> {
-    tokens: Peekable<Cloned<std::slice::Iter<'a, Token>>>,
+    tokens: &'a [Token],
+    // Index of the next token in `tokens`
+    position: usize,
     state: State,
     brace_inner: usize,
     asts: Vec<AST>,
+    errors: Vec<AstGenError>,
+    warnings: Vec<AstWarning>,
     buffer: Vec<Token>,
+    // Labels of the loops around the statement being parsed, `None` for unlabeled loops,
+    // and whether the loop is a `loop` that `break value;` may leave
+    loop_labels: Vec<(Option<String>, bool)>,
+    // Attributes read since the last function, they belong to the next one
+    attributes: Vec<String>,
+    // Lines of the `///` doc comments read since the last function, like the attributes
+    docs: Vec<String>,
+    // Byte offset just past the last token taken by `next_token`
+    previous_end: usize,
+    // Style error text with ANSI escapes
+    color: bool,
+    // Errors are written to stderr as they are found, unless the caller reports them itself
+    print_errors: bool,
+    // Errors written before the rest are only counted, unlimited by default
+    max_errors: Option<usize>,
+    // The next parenthesis opens an `if` or `while` condition
+    condition_paren: bool,
+    // Integer values of the names bound by `let` or `:=` to a constant expression and not
+    // assigned since, the sizes of fixed size arrays may use them
+    constants: HashMap<String, i64>,
     file_path: PathBuf,
     source: &'a str,
 }
@@ -49,33 +123,63 @@ pub struct Parser<'a> {
 impl<'a> Parser<'a> {
     #[allow(dead_code)]
     pub fn new(tokens: &'a [Token], file_path: PathBuf, code: &'a str) -> Self {
-        let peeked = tokens.iter().cloned().peekable();
         // println!("{:?}", tokens);
         Parser {
-            tokens: peeked,
+            tokens,
+            position: 0,
             state: State::EmptyState,
             brace_inner: 0,
             asts: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
             buffer: Vec::new(),
+            loop_labels: Vec::new(),
+            attributes: Vec::new(),
+            docs: Vec::new(),
+            previous_end: 0,
+            color: color_by_default(),
+            print_errors: true,
+            max_errors: None,
+            condition_paren: false,
+            constants: HashMap::new(),
             file_path,
             source: code,
         }
     }
+    // Turns the coloring of error text on or off, `--no-color` turns it off
+    pub fn set_color(&mut self, color: bool) {
+        self.color = color;
+    }
+    pub fn set_print_errors(&mut self, print: bool) {
+        self.print_errors = print;
+    }
+    pub fn set_max_errors(&mut self, max: Option<usize>) {
+        self.max_errors = max;
+    }
     #[allow(dead_code)]
     fn next_token(&mut self) -> Token {
-        let token = self.tokens.next();
+        let token = self.peek_token();
         // println!("{} Next Token: {:?}{}", "\x1b[36m", token, "\x1b[0m");
-        match token {
-            Some(t) => t,
-            None => Token::new(TokenType::EOF, TokenMetadata::default()),
+        if self.position < self.tokens.len() {
+            self.position += 1;
+            self.previous_end = token.metadata.end;
         }
+        token
     }
     #[allow(dead_code)]
     fn peek_token(&mut self) -> Token {
-        let peek = self.tokens.peek();
-        // println!("{} Peek Token: {:?}{}", "\x1b[38m", peek, "\x1b[0m");
-        match peek {
-            Some(t) => (*t).clone(),
+        self.peek_nth(0)
+    }
+    // The type of the next token, borrowed from the tokens instead of cloned like `peek_token`
+    fn peek_type(&self) -> &'a TokenType {
+        self.tokens
+            .get(self.position)
+            .map_or(&TokenType::EOF, |token| &token.token_type)
+    }
+    // The token `n` places after the next one, `EOF` past the end
+    fn peek_nth(&self, n: usize) -> Token {
+        match self.tokens.get(self.position + n) {
+            Some(t) => t.clone(),
             None => Token::new(TokenType::EOF, TokenMetadata::default()),
         }
     }
@@ -90,67 +194,143 @@ impl<'a> Parser<'a> {
     }
     fn parse_statement(&mut self) -> Result<Vec<AST>, AstGenError> {
         let mut statements: Vec<AST> = Vec::new();
+        // Set after a `return`, `break` or `continue`, statements after it are reported once
+        // per block
+        let mut terminated = None;
+        let mut reported = false;
         // parse statements
         loop {
             let token = self.next_token();
-            match token.token_type {
-                TokenType::Statement(Statement::Let) => {
-                    statements.push(self.parse_let()?);
-                }
-                TokenType::Statement(Statement::For) => {
-                    statements.push(self.parse_for()?);
-                }
-                TokenType::Statement(Statement::Print) => {
-                    statements.push(self.parse_print()?);
-                }
-                TokenType::Statement(Statement::Println) => {
-                    statements.push(self.parse_println()?);
-                }
-                TokenType::Statement(Statement::If) => {
-                    statements.push(self.parse_if()?);
-                }
-                TokenType::Statement(Statement::Return) => {
-                    statements.push(self.parse_return()?);
-                }
-                TokenType::TypeValue(TypeValue::Identifier(ident)) => {
-                    if let TokenType::Assign(op) = self.peek_token().token_type {
-                        match op {
-                            Assign::Assign
-                            | Assign::AddAssign
-                            | Assign::SubAssign
-                            | Assign::MulAssign
-                            | Assign::DivAssign
-                            | Assign::RemAssign
-                            | Assign::BitAndAssign
-                            | Assign::BitOrAssign
-                            | Assign::BitXorAssign => {
-                                statements.push(self.parse_assignment(ident)?);
-                            }
-                        }
-                    }
-                }
-
+            let line = token.metadata.line;
+            let statement = match token.token_type {
                 TokenType::Symbol(Symbol::OpenBrace) => {
                     continue;
                 }
                 TokenType::Symbol(Symbol::CloseBrace) => {
                     break;
                 }
-                _ => {
-                    continue;
+                TokenType::EOF => {
+                    return Err(
+                        self.report_error(AstGenError::new(AstError::UnexpectedEOF()), &token)
+                    );
+                }
+                _ => match self.parse_single_statement(token) {
+                    Some(statement) => statement,
+                    None => continue,
+                },
+            };
+            match statement {
+                Ok(statement) => {
+                    if let (Some(keyword), false) = (terminated, reported) {
+                        let keyword = String::from(keyword);
+                        self.warnings.push(AstWarning::UnreachableCode(keyword, line));
+                        reported = true;
+                    }
+                    terminated = terminated.or(match statement {
+                        AST::Statement(ASTstatement::Return { .. }) => Some("return"),
+                        AST::Statement(ASTstatement::Break { .. }) => Some("break"),
+                        AST::Statement(ASTstatement::Continue { .. }) => Some("continue"),
+                        _ => None,
+                    });
+                    statements.push(statement);
+                }
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
                 }
             }
         }
 
         Ok(statements)
     }
+    // One statement starting at `token`, `None` when the tokens do not form a statement
+    fn parse_single_statement(&mut self, token: Token) -> Option<Result<AST, AstGenError>> {
+        match token.token_type {
+            TokenType::Statement(Statement::Let) => Some(self.parse_let()),
+            TokenType::Statement(Statement::For) => Some(self.parse_for(None)),
+            TokenType::Statement(Statement::While) => Some(self.parse_while(None)),
+            TokenType::Statement(Statement::Break) | TokenType::Statement(Statement::Continue) => {
+                Some(self.parse_loop_control(&token))
+            }
+            TokenType::Label(ref label) => Some(self.parse_labeled_loop(label.clone())),
+            TokenType::Statement(Statement::Print) => Some(self.parse_print()),
+            TokenType::Statement(Statement::Println) => Some(self.parse_println()),
+            TokenType::Statement(Statement::If) => Some(self.parse_if()),
+            TokenType::Statement(Statement::Return) => Some(self.parse_return()),
+            TokenType::Statement(Statement::Match) => Some(self.parse_match()),
+            TokenType::Statement(Statement::Loop) => Some(self.parse_loop(None)),
+            TokenType::TypeValue(TypeValue::Identifier(ident)) => {
+                self.parse_identifier_statement(ident).transpose()
+            }
+            _ => None,
+        }
+    }
+    // The body of an `if` or `else`, a braced block or a single statement like `if (c) x = 1;`
+    fn parse_branch(&mut self) -> Result<Vec<AST>, AstGenError> {
+        if *self.peek_type() == TokenType::Symbol(Symbol::OpenBrace) {
+            return self.parse_statement();
+        }
+        let token = self.next_token();
+        if token.token_type == TokenType::EOF {
+            return Err(self.report_error(AstGenError::new(AstError::UnexpectedEOF()), &token));
+        }
+        let statement = self.parse_single_statement(token).transpose()?;
+        if *self.peek_type() == TokenType::Symbol(Symbol::Semicolon) {
+            self.next_token();
+        }
+        Ok(statement.into_iter().collect())
+    }
+    // Statements starting with an identifier, `None` when the identifier is not used
+    fn parse_identifier_statement(&mut self, ident: String) -> Result<Option<AST>, AstGenError> {
+        if *self.peek_type() == TokenType::Symbol(Symbol::OpenParen) {
+            // Call used as a statement, the returned value is discarded
+            return self.parse_call(ident).map(Some);
+        }
+        self.expect_call_parens(&ident)?;
+        let left = self.parse_postfix(AST::TypeValue(ASTtypevalue::Identifier(ident)))?;
+        match *self.peek_type() {
+            TokenType::Assign(Assign::Declare) => self.parse_declare(left).map(Some),
+            TokenType::Assign(_) => self.parse_assignment(left).map(Some),
+            _ if matches!(left, AST::TypeValue(ASTtypevalue::MethodCall { .. })) => Ok(Some(left)),
+            _ => Ok(None),
+        }
+    }
+    // A value right after a name, `greet "you"`, is a call without its parentheses
+    fn expect_call_parens(&mut self, name: &str) -> Result<(), AstGenError> {
+        let token = self.peek_token();
+        match token.token_type {
+            TokenType::TypeValue(TypeValue::Discard) => Ok(()),
+            TokenType::TypeValue(_) => {
+                let error = AstGenError::new(AstError::MissingParens(name.to_string()));
+                Err(self.report_error(error, &token))
+            }
+            _ => Ok(()),
+        }
+    }
+    // Skips the rest of a broken statement, up to the next `;` or `}`
+    fn synchronize(&mut self) {
+        loop {
+            match *self.peek_type() {
+                TokenType::Symbol(Symbol::Semicolon) => {
+                    self.next_token();
+                    return;
+                }
+                TokenType::Symbol(Symbol::CloseBrace) | TokenType::EOF => return,
+                _ => {
+                    self.next_token();
+                }
+            }
+        }
+    }
     fn report_error(&self, error: AstGenError, token: &Token) -> AstGenError {
         let context_lines: usize = 3; // Number of lines to show around the error
         let lines: Vec<&str> = self.source.split('\n').collect();
-        let start_line = std::cmp::max(token.metadata.line - context_lines, 0) as usize;
+        let start_line = token.metadata.line.saturating_sub(context_lines);
         let end_line = std::cmp::min(token.metadata.line + context_lines, lines.len());
 
-        let context_snippet: String = lines[start_line..end_line]
+        let context_snippet: String = lines
+            .get(start_line..end_line)
+            .unwrap_or_default()
             .iter()
             .enumerate()
             .map(|(i, line)| {
@@ -168,46 +348,50 @@ impl<'a> Parser<'a> {
             token.metadata.line, token.metadata.column, error
         );
 
+        let error_text = if self.color {
+            error_text.styled(ERRORTXTSTYLE).to_string()
+        } else {
+            error_text
+        };
         let report_text = format!(
             "Context around Line {}:\n{}\n{}\nSuggestion: {}",
             token.metadata.line,
             context_snippet,
-            error_text.styled(ERRORTXTSTYLE),
-            self.suggest_fix(&error)
+            error_text,
+            error.suggestion()
         );
 
         let error_fin = AstGenError {
+            line: token.metadata.line,
+            column: token.metadata.column,
             message: error.message,
             pretty_display: report_text.clone(),
         };
 
-        eprintln!("{}", report_text);
+        if self.print_errors && self.max_errors.is_none_or(|max| self.errors.len() < max) {
+            eprintln!("{}", report_text);
+        }
         error_fin
     }
 
-    fn suggest_fix(&self, error: &AstGenError) -> String {
-        match &error.message {
-            AstError::ExpectedToken(ref t) => format!(
-                "Add {} on line:{}, col{}",
-                t.token_type, t.metadata.line, t.metadata.column
-            ),
-            AstError::ExpectedExpression() => "Expected an expression. Check syntax.".to_string(),
-            AstError::ExpectedStatement() => "Expected a statement. Check syntax.".to_string(),
-            AstError::UnexpectedToken() => "Unexpected token. Check syntax.".to_string(),
-            AstError::InvalidNumberFormat(num) => format!(
-                "Ensure the number is correctly formatted. Invalid input: '{}'",
-                num
-            ),
-            AstError::UnexpectedEOF() => {
-                "Unexpected end of file. Check for missing tokens.".to_string()
+    // Parses the whole program, returning every syntax error that was found
+    pub fn run(&mut self) -> Result<(), Vec<AstGenError>> {
+        if let Err(error) = self.parse_program() {
+            self.errors.push(error);
+        }
+        match self.max_errors {
+            Some(max) if self.print_errors && self.errors.len() > max => {
+                eprintln!("... and {} more errors", self.errors.len() - max);
             }
-            AstError::MismatchedArgumentCount(a, b) => format!(
-                "Mismatched argument count, Check the arguments. Expected: {}, Found: {}",
-                a, b
-            ),
+            _ => {}
+        }
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
         }
     }
-    pub fn run(&mut self) -> Result<(), AstGenError> {
+    fn parse_program(&mut self) -> Result<(), AstGenError> {
         //println!("{:?}", self.tokens.peek());
         loop {
             let token = self.next_token();
@@ -222,11 +406,20 @@ impl<'a> Parser<'a> {
                     TokenType::Statement(Statement::Public) => {
                         self.parse_function(true)?;
                     }
+                    TokenType::Attribute(ref name) => {
+                        self.parse_attribute(name.clone(), &token)?;
+                    }
+                    TokenType::DocComment(ref text) => {
+                        self.docs.push(text.clone());
+                    }
+                    TokenType::InnerDocComment(_) => {}
                     TokenType::Statement(Statement::Let) => {
+                        self.docs.clear();
                         self.state = State::GlobalLet;
                         //println!("Founded Global Let");
                     }
                     TokenType::Statement(Statement::Inject) => {
+                        self.docs.clear();
                         self.state = State::Inject;
                         //println!("Founded Import");
                     }
@@ -249,7 +442,7 @@ impl<'a> Parser<'a> {
                         self.asts.push(AST::Statement(ASTstatement::Import {
                             name: package.to_string(),
                         }));
-                        if self.peek_token().token_type == TokenType::Symbol(Symbol::Semicolon) {
+                        if *self.peek_type() == TokenType::Symbol(Symbol::Semicolon) {
                             self.state = State::EmptyState;
                             self.next_token();
                         }
@@ -277,6 +470,30 @@ impl<'a> Parser<'a> {
         }
         Ok(())
     }
+    // Attribute Example
+    // @inline
+    // fn square(x: i64) -> i64 { return x * x; }
+    fn parse_attribute(&mut self, name: String, token: &Token) -> Result<(), AstGenError> {
+        if !KNOWN_ATTRIBUTES.contains(&name.as_str()) {
+            let warning = AstWarning::UnknownAttribute(name.clone(), token.metadata.line);
+            self.warnings.push(warning);
+        }
+        self.attributes.push(name);
+        let next = self.peek_token();
+        match next.token_type {
+            TokenType::Attribute(_)
+            | TokenType::DocComment(_)
+            | TokenType::Statement(Statement::Function)
+            | TokenType::Statement(Statement::Public) => Ok(()),
+            _ => Err(self.report_error(
+                AstGenError::new(AstError::ExpectedToken(Token::new(
+                    TokenType::Statement(Statement::Function),
+                    next.metadata,
+                ))),
+                &next,
+            )),
+        }
+    }
     fn parse_function(&mut self, is_public: bool) -> Result<(), AstGenError> {
         //println!("Brace: {}", self.brace_inner);
         let type_map: HashMap<TypeName, ASTtypename> = [
@@ -291,6 +508,10 @@ impl<'a> Parser<'a> {
             (TypeName::U64, ASTtypename::U64),
             (TypeName::Bool, ASTtypename::Bool),
             (TypeName::QuotedString, ASTtypename::QuotedString),
+            (TypeName::Array, ASTtypename::Array),
+            (TypeName::Map, ASTtypename::Map),
+            (TypeName::Bytes, ASTtypename::Bytes),
+            (TypeName::Tuple, ASTtypename::Tuple),
         ]
         .iter()
         .cloned()
@@ -310,7 +531,12 @@ impl<'a> Parser<'a> {
 
         //println!("cur: {:?}", cur_token);
         // Parse parameters of the function
-        let arguments = self.parse_arguments();
+        if *self.peek_type() != TokenType::Symbol(Symbol::OpenParen) {
+            let token = self.peek_token();
+            let error = AstGenError::new(AstError::MissingParens(function_name));
+            return Err(self.report_error(error, &token));
+        }
+        let arguments = self.parse_arguments()?;
         // println!("{} Arguments: {:?} {}", "\x1b[34m", arguments,"\x1b[0m");
 
         // Parse function return type
@@ -329,18 +555,30 @@ impl<'a> Parser<'a> {
                     return Err(
                         self.report_error(AstGenError::new(AstError::UnexpectedToken()), &next)
                     );
-                    panic!(
-                        "{} Require type to construct function! {:?}{}",
-                        "\x1b[31m", cur_token, "\x1b[0m"
-                    );
                 }
             }
         }
 
         // parse statements
-        let statements = self.parse_statement()?;
+        // The arguments hide the constants of the same name, and the constants of the body
+        // are gone after it
+        let outer = self.constants.clone();
+        for argument in &arguments {
+            if let ASTtypecomp::Argument {
+                identifier: ASTtypevalue::Identifier(name),
+                ..
+            } = argument
+            {
+                self.constants.remove(name);
+            }
+        }
+        let statements = self.parse_statement();
+        self.constants = outer;
+        let statements = statements?;
 
         self.asts.push(AST::Statement(ASTstatement::Function {
+            docs: std::mem::take(&mut self.docs),
+            attributes: std::mem::take(&mut self.attributes),
             public: is_public,
             name: function_name,
             args: arguments,
@@ -373,10 +611,34 @@ impl<'a> Parser<'a> {
             value: Box::new(return_value),
         }))
     }
-    fn parse_assignment(&mut self, ident: String) -> Result<AST, AstGenError> {
-        let op = match self.next_token().token_type {
+    fn parse_assignment(&mut self, left: AST) -> Result<AST, AstGenError> {
+        let assignment = self.parse_assignment_value(left)?;
+        let peeked = self.peek_token();
+        if peeked.token_type == TokenType::Symbol(Symbol::Semicolon) {
+            self.next_token();
+        } else {
+            return Err(self.report_error(
+                AstGenError::new(AstError::ExpectedToken(Token::new(
+                    TokenType::Symbol(Symbol::Semicolon),
+                    peeked.metadata,
+                ))),
+                &peeked,
+            ));
+        }
+        Ok(assignment)
+    }
+    // Chained Assignment Example
+    // a = b = 5;
+    // Right associative, `b = 5` is the value assigned to `a`
+    fn parse_assignment_value(&mut self, left: AST) -> Result<AST, AstGenError> {
+        let token = self.next_token();
+        let op = match token.token_type {
             TokenType::Assign(op) => match op {
                 Assign::Assign => ASTOperator::Assign,
+                Assign::Declare => {
+                    let error = AstGenError::new(AstError::UnexpectedToken());
+                    return Err(self.report_error(error, &token));
+                }
                 Assign::AddAssign => ASTOperator::AddAssign,
                 Assign::SubAssign => ASTOperator::SubAssign,
                 Assign::MulAssign => ASTOperator::MulAssign,
@@ -386,37 +648,68 @@ impl<'a> Parser<'a> {
                 Assign::BitOrAssign => ASTOperator::BitOrAssign,
                 Assign::BitXorAssign => ASTOperator::BitXorAssign,
             },
-            _ => unreachable!(),
+            _ => {
+                let error = AstGenError::new(AstError::UnexpectedToken());
+                return Err(self.report_error(error, &token));
+            }
         };
         // println!("{} Op: {:?} {}", "\x1b[34m", op, "\x1b[0m");
-        let right_expr = self.parse_expression()?;
+        if let AST::TypeValue(ASTtypevalue::Identifier(name)) = &left {
+            self.constants.remove(name);
+        }
+        let mut right_expr = self.parse_expression()?;
         // println!("{} Right expr: {:?} {}", "\x1b[34m", right_expr, "\x1b[0m");
-        // println!("{} peek: {:?} {}", "\x1b[34m", self.peek_token(), "\x1b[0m");
-        let peeked = self.peek_token();
-        if peeked.token_type == TokenType::Symbol(Symbol::Semicolon) {
-            self.next_token();
-        } else {
-            return Err(self.report_error(
-                AstGenError::new(AstError::ExpectedToken(Token::new(
-                    TokenType::Symbol(Symbol::Semicolon),
-                    peeked.metadata,
-                ))),
-                &peeked,
-            ));
+        match *self.peek_type() {
+            TokenType::Assign(Assign::Declare) => {}
+            TokenType::Assign(_) => right_expr = self.parse_assignment_value(right_expr)?,
+            _ => {}
         }
         Ok(AST::Statement(ASTstatement::Assignment {
-            left: Box::new(AST::TypeValue(ASTtypevalue::Identifier(ident))),
+            left: Box::new(left),
             op,
             right: Box::new(right_expr),
         }))
     }
+    // Declaration Example
+    // a := 5;
+    fn parse_declare(&mut self, left: AST) -> Result<AST, AstGenError> {
+        let token = self.next_token(); // Consume the `:=`
+        let name = match left {
+            AST::TypeValue(ASTtypevalue::Identifier(name)) => name,
+            _ => {
+                return Err(
+                    self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)
+                )
+            }
+        };
+        let value = self.parse_expression()?;
+        self.expect_symbol(Symbol::Semicolon)?;
+        self.bind_constant(&name, Some(&value));
+        Ok(AST::Statement(ASTstatement::Declare {
+            name,
+            value: Box::new(value),
+        }))
+    }
+    // Remembers the value of `name` when it folds to an integer, and forgets an older one
+    fn bind_constant(&mut self, name: &str, value: Option<&AST>) {
+        let value = value.and_then(|value| {
+            match fold::fold(fold::substitute(value.clone(), &self.constants)) {
+                Ok(AST::TypeValue(ASTtypevalue::I64(n))) => Some(n),
+                _ => None,
+            }
+        });
+        match value {
+            Some(n) => self.constants.insert(name.to_string(), n),
+            None => self.constants.remove(name),
+        };
+    }
     fn parse_expression(&mut self) -> Result<AST, AstGenError> {
         self.parse_level1()
     }
 
     fn parse_level1(&mut self) -> Result<AST, AstGenError> {
         let mut node = self.parse_level2()?;
-        while let TokenType::Logical(op) = self.peek_token().token_type {
+        while let TokenType::Logical(op) = *self.peek_type() {
             match op {
                 Logical::Or => {
                     self.next_token();
@@ -435,7 +728,7 @@ impl<'a> Parser<'a> {
 
     fn parse_level2(&mut self) -> Result<AST, AstGenError> {
         let mut node = self.parse_level3()?;
-        while let TokenType::Logical(op) = self.peek_token().token_type {
+        while let TokenType::Logical(op) = *self.peek_type() {
             match op {
                 Logical::And => {
                     self.next_token();
@@ -454,101 +747,96 @@ impl<'a> Parser<'a> {
 
     fn parse_level3(&mut self) -> Result<AST, AstGenError> {
         let mut node = self.parse_level4()?;
-        while let TokenType::Logical(op) = self.peek_token().token_type {
-            match op {
-                Logical::Equals | Logical::NotEquals => {
-                    self.next_token();
-                    let right_node = self.parse_level4()?;
-                    node = AST::Logic(ASTlogic::BinaryOperation {
-                        left: Box::new(node),
-                        op: match op {
-                            Logical::Equals => ASTOperator::Equals,
-                            Logical::NotEquals => ASTOperator::NotEquals,
-                            _ => unreachable!(),
-                        },
-                        right: Box::new(right_node),
-                    });
-                }
+        while let TokenType::Logical(op) = *self.peek_type() {
+            let op = match op {
+                Logical::Equals => ASTOperator::Equals,
+                Logical::NotEquals => ASTOperator::NotEquals,
                 _ => break,
-            }
+            };
+            self.next_token();
+            let right_node = self.parse_level4()?;
+            node = AST::Logic(ASTlogic::BinaryOperation {
+                left: Box::new(node),
+                op,
+                right: Box::new(right_node),
+            });
         }
         Ok(node)
     }
 
     fn parse_level4(&mut self) -> Result<AST, AstGenError> {
         let mut node = self.parse_level5()?;
-        while let TokenType::Logical(op) = self.peek_token().token_type {
-            match op {
-                Logical::LessThan
-                | Logical::LessThanEquals
-                | Logical::GreaterThan
-                | Logical::GreaterThanEquals => {
-                    self.next_token();
-                    let right_node = self.parse_level5()?;
-                    node = AST::Logic(ASTlogic::BinaryOperation {
-                        left: Box::new(node),
-                        op: match op {
-                            Logical::LessThan => ASTOperator::Less,
-                            Logical::LessThanEquals => ASTOperator::LessEquals,
-                            Logical::GreaterThan => ASTOperator::Greater,
-                            Logical::GreaterThanEquals => ASTOperator::GreaterEquals,
-                            _ => unreachable!(),
-                        },
-                        right: Box::new(right_node),
-                    });
-                }
+        while let TokenType::Logical(op) = *self.peek_type() {
+            let op = match op {
+                Logical::LessThan => ASTOperator::Less,
+                Logical::LessThanEquals => ASTOperator::LessEquals,
+                Logical::GreaterThan => ASTOperator::Greater,
+                Logical::GreaterThanEquals => ASTOperator::GreaterEquals,
                 _ => break,
-            }
+            };
+            self.next_token();
+            let right_node = self.parse_level5()?;
+            node = AST::Logic(ASTlogic::BinaryOperation {
+                left: Box::new(node),
+                op,
+                right: Box::new(right_node),
+            });
         }
         Ok(node)
     }
 
     fn parse_level5(&mut self) -> Result<AST, AstGenError> {
         let mut node = self.parse_level6()?;
-        while let TokenType::Operator(op) = self.peek_token().token_type {
-            match op {
-                Operator::Add | Operator::Subtract => {
-                    self.next_token();
-                    let right_node = self.parse_level6()?;
-                    node = AST::Logic(ASTlogic::BinaryOperation {
-                        left: Box::new(node),
-                        op: match op {
-                            Operator::Add => ASTOperator::Add,
-                            Operator::Subtract => ASTOperator::Subtract,
-                            _ => unreachable!(),
-                        },
-                        right: Box::new(right_node),
-                    });
-                }
+        while let TokenType::Operator(op) = *self.peek_type() {
+            let op = match op {
+                Operator::Add => ASTOperator::Add,
+                Operator::Subtract => ASTOperator::Subtract,
                 _ => break,
-            }
+            };
+            self.next_token();
+            let right_node = self.parse_level6()?;
+            node = AST::Logic(ASTlogic::BinaryOperation {
+                left: Box::new(node),
+                op,
+                right: Box::new(right_node),
+            });
         }
         Ok(node)
     }
 
     fn parse_level6(&mut self) -> Result<AST, AstGenError> {
-        let mut node = self.parse_primary()?;
-        while let TokenType::Operator(op) = self.peek_token().token_type {
-            match op {
-                Operator::Multiply | Operator::Divide | Operator::Remainder => {
-                    self.next_token();
-                    let right_node = self.parse_primary()?;
-                    node = AST::Logic(ASTlogic::BinaryOperation {
-                        left: Box::new(node),
-                        op: match op {
-                            Operator::Multiply => ASTOperator::Multiply,
-                            Operator::Divide => ASTOperator::Divide,
-                            Operator::Remainder => ASTOperator::Remainder,
-                            _ => unreachable!(),
-                        },
-                        right: Box::new(right_node),
-                    });
-                }
+        let mut node = self.parse_power()?;
+        while let TokenType::Operator(op) = *self.peek_type() {
+            let op = match op {
+                Operator::Multiply => ASTOperator::Multiply,
+                Operator::Divide => ASTOperator::Divide,
+                Operator::Remainder => ASTOperator::Remainder,
                 _ => break,
-            }
+            };
+            self.next_token();
+            let right_node = self.parse_power()?;
+            node = AST::Logic(ASTlogic::BinaryOperation {
+                left: Box::new(node),
+                op,
+                right: Box::new(right_node),
+            });
         }
         Ok(node)
     }
+    // `**` binds tighter than `*` and is right associative, 2 ** 3 ** 2 == 2 ** 9
+    fn parse_power(&mut self) -> Result<AST, AstGenError> {
+        let node = self.parse_primary()?;
+        if *self.peek_type() != TokenType::Operator(Operator::Power) {
+            return Ok(node);
+        }
+        self.next_token();
+        let right_node = self.parse_power()?;
+        Ok(AST::Logic(ASTlogic::BinaryOperation {
+            left: Box::new(node),
+            op: ASTOperator::Power,
+            right: Box::new(right_node),
+        }))
+    }
 
     fn parse_primary(&mut self) -> Result<AST, AstGenError> {
         // let cur_token = self.next_token();
@@ -558,14 +846,29 @@ impl<'a> Parser<'a> {
             // Consume the opening parenthesis
             // println!("WAI {} {} {} ", "\x1b[31m", cur_token, "\x1b[0m");
             self.next_token();
+            let is_condition = std::mem::take(&mut self.condition_paren);
             let node = self.parse_expression()?;
             let peek_token = self.peek_token();
             let test = match peek_token.token_type {
+                TokenType::Assign(Assign::Assign) if is_condition => {
+                    let error = AstGenError::new(AstError::AssignmentInCondition());
+                    return Err(self.report_error(error, &peek_token));
+                }
+                // `((a = b))`, an assignment in its own parentheses is its value
+                TokenType::Assign(Assign::Assign) => {
+                    let assignment = self.parse_assignment_value(node)?;
+                    self.expect_symbol(Symbol::CloseParen)?;
+                    return Ok(assignment);
+                }
                 // TokenType::Logical(_)| Token::Operator(_) => self.parse_expression(),
                 TokenType::Symbol(Symbol::CloseParen) => {
                     self.next_token();
                     return Ok(node);
                 }
+                TokenType::Symbol(Symbol::Comma) => {
+                    let tuple = self.parse_tuple(node)?;
+                    return self.parse_postfix(tuple);
+                }
                 _ => {
                     self.next_token();
                     self.parse_expression()?
@@ -579,7 +882,7 @@ impl<'a> Parser<'a> {
         // Handle literals and identifiers
         match next_token.token_type {
             // TokenType::Symbol(Symbol::OpenParen) => panic!("Open Parenthesis"),
-            TokenType::TypeValue(TypeValue::Number(num)) => match self.peek_token().token_type {
+            TokenType::TypeValue(TypeValue::Number(num)) => match *self.peek_type() {
                 TokenType::Logical(_) => self.parse_expression(),
                 _ => {
                     self.next_token();
@@ -595,52 +898,38 @@ impl<'a> Parser<'a> {
                     )))
                 }
             },
+            TokenType::TypeValue(TypeValue::NoneVoid) => {
+                self.next_token();
+                Ok(AST::TypeValue(ASTtypevalue::None))
+            }
+            TokenType::TypeValue(TypeValue::Bytes(bytes)) => {
+                self.next_token();
+                self.parse_postfix(AST::TypeValue(ASTtypevalue::Bytes(bytes)))
+            }
+            TokenType::TypeValue(TypeValue::TypedNumber(ref num, type_name)) => {
+                self.next_token();
+                self.parse_typed_number(num, type_name, &next_token)
+            }
             TokenType::TypeValue(TypeValue::Identifier(ident)) => {
-                match self.peek_token().token_type {
+                match *self.peek_type() {
                     TokenType::Logical(_) => self.parse_expression(),
                     _ => {
                         self.next_token();
                         let _status = 1;
-                        if self.peek_token().token_type == TokenType::Symbol(Symbol::OpenParen) {
-                            //FuncCall
-                            self.next_token(); // Consume the opening parenthesis
-                            let mut arguments = Vec::new();
-                            // println!("FuncCall");
-                            loop {
-                                let token = self.peek_token();
-                                // println!("Token FC: {:?}", token);
-
-                                match token.token_type {
-                                    TokenType::Symbol(Symbol::CloseParen) => {
-                                        self.next_token();
-                                        // println!("Close Paren");
-                                        break;
-                                    }
-                                    TokenType::Symbol(Symbol::Comma) => {
-                                        self.next_token();
-                                        // println!("Comma");
-                                    }
-                                    _ => {
-                                        // println!("Argument {:?}",token);
-                                        arguments.push(self.parse_expression()?);
-                                        // self.next_token();
-                                    }
-                                }
-                            }
-                            // println!("Arguments: {:?}", arguments);
-                            return Ok(AST::TypeValue(ASTtypevalue::FunctionCall {
-                                name: ident.to_string(),
-                                args: arguments,
-                            }));
+                        if *self.peek_type() == TokenType::Symbol(Symbol::OpenParen) {
+                            let call = self.parse_call(ident.to_string())?;
+                            return self.parse_postfix(call);
                         }
-                        Ok(AST::TypeValue(ASTtypevalue::Identifier(ident)))
+                        self.expect_call_parens(&ident)?;
+                        self.parse_postfix(AST::TypeValue(ASTtypevalue::Identifier(ident)))
                     }
                 }
             }
             TokenType::TypeValue(TypeValue::QuotedString(s)) => {
-                match self.peek_token().token_type {
+                match *self.peek_type() {
                     TokenType::Logical(_) => {
-                        panic!("Logical operator is not allowed in quoted string!")
+                        let token = self.peek_token();
+                        Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token))
                     }
                     _ => {
                         self.next_token();
@@ -648,6 +937,15 @@ impl<'a> Parser<'a> {
                     }
                 }
             }
+            TokenType::Symbol(Symbol::OpenBrace) => self.parse_brace(),
+            TokenType::Statement(Statement::Match) => {
+                self.next_token();
+                self.parse_match()
+            }
+            TokenType::Statement(Statement::Loop) => {
+                self.next_token();
+                self.parse_loop(None)
+            }
             TokenType::Logical(_) | TokenType::Operator(_) => self.parse_expression(),
             _ => {
                 // println!(
@@ -663,47 +961,349 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_print(&mut self) -> Result<AST, AstGenError> {
-        // Consume the opening parenthesis
-        let next = self.next_token();
-        if next.token_type != TokenType::Symbol(Symbol::OpenParen) {
-            return Err(self.report_error(
-                AstGenError::new(AstError::ExpectedToken(Token::new(
-                    TokenType::Symbol(Symbol::OpenParen),
-                    next.metadata,
-                ))),
-                &next,
-            ));
+    // Tuple Example
+    // (1, "two", 3)
+    // The first value and the opening parenthesis are already consumed
+    fn parse_tuple(&mut self, first: AST) -> Result<AST, AstGenError> {
+        self.next_token(); // Consume the comma
+        let mut values = vec![first];
+        values.extend(self.parse_call_arguments()?);
+        Ok(AST::TypeValue(ASTtypevalue::Tuple(values)))
+    }
+
+    // Function Call Example
+    // add(1, 2)
+    fn parse_call(&mut self, name: String) -> Result<AST, AstGenError> {
+        let open_paren = self.next_token(); // Consume the opening parenthesis
+        if name == "dbg" {
+            return self.parse_dbg();
+        }
+        let arguments = self.parse_call_arguments()?;
+        if name == "panic" {
+            return self.parse_panic(arguments, &open_paren);
+        }
+        Ok(AST::TypeValue(ASTtypevalue::FunctionCall {
+            name,
+            args: arguments,
+        }))
+    }
+    // Arguments of a call up to and including the closing parenthesis
+    fn parse_call_arguments(&mut self) -> Result<Vec<AST>, AstGenError> {
+        let mut arguments = Vec::new();
+        loop {
+            let token = self.peek_token();
+            match token.token_type {
+                TokenType::Symbol(Symbol::CloseParen) => {
+                    self.next_token();
+                    break;
+                }
+                TokenType::Symbol(Symbol::Comma) => {
+                    self.next_token();
+                }
+                _ => {
+                    arguments.push(self.parse_expression()?);
+                }
+            }
+        }
+        Ok(arguments)
+    }
+    // Debug Example
+    // dbg(2 + 3) prints `2 + 3 = 5` and evaluates to 5
+    fn parse_dbg(&mut self) -> Result<AST, AstGenError> {
+        let start = self.peek_token().metadata.start;
+        let value = self.parse_expression()?;
+        let source = self.source.get(start..self.previous_end).unwrap_or_default();
+        let source = source.to_string();
+        self.expect_symbol(Symbol::CloseParen)?;
+        Ok(AST::TypeValue(ASTtypevalue::Debug {
+            value: Box::new(value),
+            source,
+        }))
+    }
+    // The AST has no positions, so `panic` records the line of its call itself
+    fn parse_panic(&self, arguments: Vec<AST>, open_paren: &Token) -> Result<AST, AstGenError> {
+        let count = arguments.len();
+        match <[AST; 1]>::try_from(arguments) {
+            Ok([message]) => Ok(AST::TypeValue(ASTtypevalue::Panic {
+                message: Box::new(message),
+                line: open_paren.metadata.line,
+            })),
+            Err(_) => Err(self.report_error(
+                AstGenError::new(AstError::MismatchedArgumentCount(1, count)),
+                open_paren,
+            )),
         }
+    }
 
-        // Parse the format string
-        let format_str_token = self.peek_token();
-        let format_str = match format_str_token.token_type {
-            TokenType::TypeValue(TypeValue::QuotedString(ref s)) => s,
+    // Postfix Expression Example
+    // m["key"], a[0][1], a.b.c, a.b().c
+    // Indexing and access chain to the left, `a.b.c` is `(a.b).c`
+    fn parse_postfix(&mut self, target: AST) -> Result<AST, AstGenError> {
+        let mut node = target;
+        loop {
+            match *self.peek_type() {
+                TokenType::Symbol(Symbol::OpenSquare) => node = self.parse_index(node)?,
+                TokenType::Symbol(Symbol::Dot) => node = self.parse_access(node)?,
+                _ => return Ok(node),
+            }
+        }
+    }
+    // `.name` or `.name(args)` after an expression
+    fn parse_access(&mut self, target: AST) -> Result<AST, AstGenError> {
+        self.next_token(); // Consume the dot
+        let token = self.next_token();
+        let name = match token.token_type {
+            TokenType::TypeValue(TypeValue::Identifier(name)) => name.to_string(),
+            // `tuple.0`, the position of a tuple value
+            TokenType::TypeValue(TypeValue::Number(position)) => {
+                return Ok(AST::TypeValue(ASTtypevalue::Field {
+                    target: Box::new(target),
+                    name: position.to_string(),
+                }))
+            }
             _ => {
-                return Err(self.report_error(
-                    AstGenError::new(AstError::ExpectedToken(Token::new(
-                        TokenType::TypeName(TypeName::QuotedString),
-                        format_str_token.metadata,
-                    ))),
-                    &format_str_token,
-                ))
+                return Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token))
             }
         };
-        let value = self.parse_expression()?;
-        // Parse arguments
-        let mut args = Vec::new();
-        while self.peek_token().token_type == TokenType::Symbol(Symbol::Comma) {
-            self.next_token(); // Consume the comma
-            args.push(self.parse_expression()?);
+        if *self.peek_type() != TokenType::Symbol(Symbol::OpenParen) {
+            return Ok(AST::TypeValue(ASTtypevalue::Field {
+                target: Box::new(target),
+                name,
+            }));
         }
-
-        // Check if the number of `{}` in the format string matches the number of arguments
-        let placeholders = format_str.matches("{}").count();
-        if placeholders != args.len() {
+        self.next_token(); // Consume the opening parenthesis
+        Ok(AST::TypeValue(ASTtypevalue::MethodCall {
+            target: Box::new(target),
+            name,
+            args: self.parse_call_arguments()?,
+        }))
+    }
+    // Index Expression Example
+    // m["key"], a[0][1]
+    fn parse_index(&mut self, target: AST) -> Result<AST, AstGenError> {
+        self.next_token(); // Consume the opening square bracket
+        let index = self.parse_expression()?;
+        let close = self.next_token();
+        if close.token_type != TokenType::Symbol(Symbol::CloseSquare) {
             return Err(self.report_error(
-                AstGenError::new(AstError::MismatchedArgumentCount(placeholders, args.len())),
-                &format_str_token,
+                AstGenError::new(AstError::ExpectedToken(Token::new(
+                    TokenType::Symbol(Symbol::CloseSquare),
+                    close.metadata,
+                ))),
+                &close,
+            ));
+        }
+        Ok(AST::TypeValue(ASTtypevalue::Index {
+            target: Box::new(target),
+            index: Box::new(index),
+        }))
+    }
+
+    // Map Literal Example
+    // { "key": value, "other": 5 }
+    // A brace can only start a map here, blocks are consumed by `parse_statement`
+    // `{` starts a map when it is empty or its first token is followed by `:`, otherwise a
+    // block, map keys are a single token so two tokens of lookahead tell `{ k: v }` from `{ k }`
+    fn parse_brace(&mut self) -> Result<AST, AstGenError> {
+        let is_map = matches!(
+            (self.peek_nth(1).token_type, self.peek_nth(2).token_type),
+            (TokenType::Symbol(Symbol::CloseBrace), _) | (_, TokenType::Symbol(Symbol::Colon))
+        );
+        self.next_token(); // Consume the opening brace
+        if is_map {
+            self.parse_map()
+        } else {
+            self.parse_block()
+        }
+    }
+    // The rest of a block after `{`
+    fn parse_block(&mut self) -> Result<AST, AstGenError> {
+        let mut statements = Vec::new();
+        let mut expression = None;
+        loop {
+            if let Some(left) = expression.take() {
+                match *self.peek_type() {
+                    TokenType::Assign(Assign::Declare) => {
+                        statements.push(self.parse_declare(left)?)
+                    }
+                    TokenType::Assign(_) => statements.push(self.parse_assignment(left)?),
+                    // No `;` after the last expression, it is the value of the block
+                    TokenType::Symbol(Symbol::CloseBrace) => {
+                        self.next_token();
+                        return Ok(AST::TypeValue(ASTtypevalue::Block {
+                            statements,
+                            value: Some(Box::new(left)),
+                        }));
+                    }
+                    _ => statements.push(left),
+                }
+                continue;
+            }
+            let token = self.peek_token();
+            match token.token_type {
+                TokenType::Symbol(Symbol::CloseBrace) => {
+                    self.next_token();
+                    return Ok(AST::TypeValue(ASTtypevalue::Block {
+                        statements,
+                        value: None,
+                    }));
+                }
+                TokenType::Symbol(Symbol::Semicolon) => {
+                    self.next_token();
+                }
+                TokenType::EOF => {
+                    return Err(
+                        self.report_error(AstGenError::new(AstError::UnexpectedEOF()), &token)
+                    );
+                }
+                TokenType::Statement(_) => {
+                    let token = self.next_token();
+                    match self.parse_single_statement(token.clone()) {
+                        Some(statement) => statements.push(statement?),
+                        None => {
+                            return Err(self.report_error(
+                                AstGenError::new(AstError::ExpectedStatement()),
+                                &token,
+                            ))
+                        }
+                    }
+                }
+                _ => expression = Some(self.parse_expression()?),
+            }
+        }
+    }
+    // The rest of a map after `{`
+    fn parse_map(&mut self) -> Result<AST, AstGenError> {
+        let mut entries = Vec::new();
+        loop {
+            let token = self.peek_token();
+            match token.token_type {
+                TokenType::Symbol(Symbol::CloseBrace) => {
+                    self.next_token();
+                    break;
+                }
+                TokenType::Symbol(Symbol::Comma) => {
+                    self.next_token();
+                }
+                TokenType::EOF => {
+                    return Err(
+                        self.report_error(AstGenError::new(AstError::UnexpectedEOF()), &token)
+                    );
+                }
+                _ => {
+                    let key = self.parse_expression()?;
+                    let colon = self.next_token();
+                    if colon.token_type != TokenType::Symbol(Symbol::Colon) {
+                        return Err(self.report_error(
+                            AstGenError::new(AstError::ExpectedToken(Token::new(
+                                TokenType::Symbol(Symbol::Colon),
+                                colon.metadata,
+                            ))),
+                            &colon,
+                        ));
+                    }
+                    let value = self.parse_expression()?;
+                    entries.push((key, value));
+                }
+            }
+        }
+        Ok(AST::TypeValue(ASTtypevalue::Map(entries)))
+    }
+
+    // Match Expression Example
+    // match x { 1 => "one", "foo" => "bar", _ => "other" }
+    fn parse_match(&mut self) -> Result<AST, AstGenError> {
+        let value = self.parse_expression()?;
+        self.expect_symbol(Symbol::OpenBrace)?;
+        let mut arms = Vec::new();
+        loop {
+            let token = self.peek_token();
+            match token.token_type {
+                TokenType::Symbol(Symbol::CloseBrace) => {
+                    self.next_token();
+                    break;
+                }
+                TokenType::Symbol(Symbol::Comma) => {
+                    self.next_token();
+                }
+                TokenType::EOF => {
+                    return Err(
+                        self.report_error(AstGenError::new(AstError::UnexpectedEOF()), &token)
+                    );
+                }
+                TokenType::TypeValue(TypeValue::Discard) => {
+                    self.next_token();
+                    self.expect_symbol(Symbol::FatArrow)?;
+                    let arm = self.parse_expression()?;
+                    arms.push((AST::TypeValue(ASTtypevalue::Discard), arm));
+                }
+                _ => {
+                    let pattern = self.parse_expression()?;
+                    self.expect_symbol(Symbol::FatArrow)?;
+                    arms.push((pattern, self.parse_expression()?));
+                }
+            }
+        }
+        Ok(AST::TypeValue(ASTtypevalue::Match {
+            value: Box::new(value),
+            arms,
+        }))
+    }
+    fn expect_symbol(&mut self, symbol: Symbol) -> Result<(), AstGenError> {
+        let token = self.next_token();
+        if token.token_type == TokenType::Symbol(symbol) {
+            return Ok(());
+        }
+        Err(self.report_error(
+            AstGenError::new(AstError::ExpectedToken(Token::new(
+                TokenType::Symbol(symbol),
+                token.metadata,
+            ))),
+            &token,
+        ))
+    }
+
+    fn parse_print(&mut self) -> Result<AST, AstGenError> {
+        // Consume the opening parenthesis
+        let next = self.next_token();
+        if next.token_type != TokenType::Symbol(Symbol::OpenParen) {
+            return Err(self.report_error(
+                AstGenError::new(AstError::ExpectedToken(Token::new(
+                    TokenType::Symbol(Symbol::OpenParen),
+                    next.metadata,
+                ))),
+                &next,
+            ));
+        }
+
+        // Parse the format string
+        let format_str_token = self.peek_token();
+        let format_str = match format_str_token.token_type {
+            TokenType::TypeValue(TypeValue::QuotedString(ref s)) => s,
+            _ => {
+                return Err(self.report_error(
+                    AstGenError::new(AstError::ExpectedToken(Token::new(
+                        TokenType::TypeName(TypeName::QuotedString),
+                        format_str_token.metadata,
+                    ))),
+                    &format_str_token,
+                ))
+            }
+        };
+        let value = self.parse_expression()?;
+        // Parse arguments
+        let mut args = Vec::new();
+        while *self.peek_type() == TokenType::Symbol(Symbol::Comma) {
+            self.next_token(); // Consume the comma
+            args.push(self.parse_expression()?);
+        }
+
+        // Check if the number of `{}` in the format string matches the number of arguments
+        let placeholders = count_placeholders(format_str);
+        if placeholders != args.len() {
+            return Err(self.report_error(
+                AstGenError::new(AstError::MismatchedArgumentCount(placeholders, args.len())),
+                &format_str_token,
             ));
         }
 
@@ -767,13 +1367,13 @@ impl<'a> Parser<'a> {
         let value = self.parse_expression()?;
         // Parse arguments
         let mut args = Vec::new();
-        while self.peek_token().token_type == TokenType::Symbol(Symbol::Comma) {
+        while *self.peek_type() == TokenType::Symbol(Symbol::Comma) {
             self.next_token(); // Consume the comma
             args.push(self.parse_expression()?);
         }
 
         // Check if the number of `{}` in the format string matches the number of arguments
-        let placeholders = format_str.matches("{}").count();
+        let placeholders = count_placeholders(format_str);
         if placeholders != args.len() {
             return Err(self.report_error(
                 AstGenError::new(AstError::MismatchedArgumentCount(placeholders, args.len())),
@@ -812,18 +1412,22 @@ impl<'a> Parser<'a> {
         }))
     }
     fn parse_if(&mut self) -> Result<AST, AstGenError> {
-        self.next_token();
-        // Parse the condition
-        let condition = self.parse_expression()?;
+        // Parse the condition, the parentheses are part of the expression
+        let condition = if *self.peek_type() == TokenType::Statement(Statement::Let) {
+            self.next_token();
+            self.parse_some_pattern()?
+        } else {
+            self.parse_condition()?
+        };
         // Parse the statements
-        let statements = self.parse_statement()?;
+        let statements = self.parse_branch()?;
         let mut elif = Option::None;
         let mut else_statements = Option::None;
         // Create the If AST node
         // Check for else or else if
-        if let TokenType::Statement(Statement::Else) = self.peek_token().token_type {
+        if let TokenType::Statement(Statement::Else) = *self.peek_type() {
             self.next_token(); // consume the else token
-            match self.peek_token().token_type {
+            match *self.peek_type() {
                 TokenType::Statement(Statement::If) => {
                     self.next_token(); // consume the if token
                     let else_if_node = self.parse_if()?;
@@ -831,7 +1435,8 @@ impl<'a> Parser<'a> {
                     elif = Option::Some(Box::new(else_if_node));
                 }
                 _ => {
-                    else_statements = Option::Some(self.parse_statement()?);
+                    // A brace-less `else` binds to the nearest `if`
+                    else_statements = Option::Some(self.parse_branch()?);
                 }
             }
         }
@@ -842,6 +1447,50 @@ impl<'a> Parser<'a> {
             else_statements,
         }))
     }
+    fn parse_some_pattern(&mut self) -> Result<AST, AstGenError> {
+        // If Let Example
+        // if let Some(x) = get(m, "key") { ... }
+        let token = self.next_token();
+        if token.token_type != TokenType::TypeValue(TypeValue::Identifier("Some".to_string())) {
+            return Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token));
+        }
+        self.expect_symbol(Symbol::OpenParen)?;
+        let token = self.next_token();
+        let TokenType::TypeValue(TypeValue::Identifier(name)) = token.token_type else {
+            return Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token));
+        };
+        self.expect_symbol(Symbol::CloseParen)?;
+        let token = self.next_token();
+        if token.token_type != TokenType::Assign(Assign::Assign) {
+            return Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token));
+        }
+        let start = self.peek_token();
+        let value = self.parse_expression()?;
+        // Literals and operations never give `none`, variables, calls and indexing may
+        let optional = matches!(
+            &value,
+            AST::TypeValue(
+                ASTtypevalue::None
+                    | ASTtypevalue::Identifier(_)
+                    | ASTtypevalue::Index { .. }
+                    | ASTtypevalue::Field { .. }
+                    | ASTtypevalue::FunctionCall { .. }
+                    | ASTtypevalue::MethodCall { .. }
+                    | ASTtypevalue::Match { .. }
+                    | ASTtypevalue::Block { .. }
+                    | ASTtypevalue::Loop { .. }
+                    | ASTtypevalue::Debug { .. }
+            )
+        );
+        if !optional {
+            let error = AstGenError::new(AstError::NotOptional(value.to_string()));
+            return Err(self.report_error(error, &start));
+        }
+        Ok(AST::TypeValue(ASTtypevalue::SomePattern {
+            name,
+            value: Box::new(value),
+        }))
+    }
     fn parse_let(&mut self) -> Result<AST, AstGenError> {
         // Let Statement Example
         // let:i32 a = 5;
@@ -850,7 +1499,11 @@ impl<'a> Parser<'a> {
         let mut name: String = String::new();
 
         let mut type_name: Option<ASTtypename> = None;
+        // The type as it was written, for errors
+        let mut type_label = String::new();
         let mut value: Option<Box<AST>> = None;
+        // Elements of a fixed size array, filled with zeros when there is no value
+        let mut elements: Option<Vec<ASTtypevalue>> = None;
         let type_map: HashMap<TypeName, ASTtypename> = [
             (TypeName::I8, ASTtypename::I8),
             (TypeName::I16, ASTtypename::I16),
@@ -862,6 +1515,10 @@ impl<'a> Parser<'a> {
             (TypeName::U64, ASTtypename::U64),
             (TypeName::Bool, ASTtypename::Bool),
             (TypeName::QuotedString, ASTtypename::QuotedString),
+            (TypeName::Array, ASTtypename::Array),
+            (TypeName::Map, ASTtypename::Map),
+            (TypeName::Bytes, ASTtypename::Bytes),
+            (TypeName::Tuple, ASTtypename::Tuple),
         ]
         .iter()
         .cloned()
@@ -875,6 +1532,12 @@ impl<'a> Parser<'a> {
                 name = ident.to_string();
                 status = 4;
             }
+            // `let _ = value;` runs the value and drops it
+            TokenType::TypeValue(TypeValue::Discard) => {
+                name = "_".to_string();
+                status = 4;
+            }
+            TokenType::Symbol(Symbol::OpenParen) => return self.parse_destructure(),
             _ => {
                 return Err(
                     self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)
@@ -896,6 +1559,7 @@ impl<'a> Parser<'a> {
                 (TokenType::TypeName(typename), 2) => {
                     if let Some(ast_type) = type_map.get(typename) {
                         type_name = Some(*ast_type);
+                        type_label = typename.to_string();
                         self.next_token();
                         status = 3;
                         continue;
@@ -905,20 +1569,36 @@ impl<'a> Parser<'a> {
                     );
                     // println!("Missing Type Announcement for `let` statement After `:`");
                 }
+                (TokenType::Symbol(Symbol::OpenSquare), 2) => {
+                    self.next_token();
+                    elements = Some(self.parse_array_type(&type_map)?);
+                    type_name = Some(ASTtypename::Array);
+                    status = 3;
+                    continue;
+                }
                 (TokenType::TypeValue(TypeValue::Identifier(ident)), 3) => {
                     name = ident.to_string();
                     self.next_token();
                     status = 4;
                     continue;
                 }
+                (TokenType::TypeValue(TypeValue::Discard), 3) => {
+                    name = "_".to_string();
+                    self.next_token();
+                    status = 4;
+                    continue;
+                }
                 (TokenType::Assign(Assign::Assign), 4) => {
                     self.next_token();
                     status = 5;
                     continue;
                 }
+                // `let:i32 a;` declares `a` without a value, it has to be assigned before use
+                (TokenType::Symbol(Symbol::Semicolon), 4) => break,
                 (_, 5) => {
                     value = Some(Box::new(self.parse_expression()?));
                     // println!("Value: {:?}", value);
+                    self.check_literal_range(type_name, &type_label, value.as_deref(), &token)?;
                     status = 6;
                     break;
                 }
@@ -936,14 +1616,194 @@ impl<'a> Parser<'a> {
             }
         }
 
+        if let Some(elements) = elements {
+            if value.is_some() {
+                let token = self.peek_token();
+                return Err(
+                    self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)
+                );
+            }
+            value = Some(Box::new(AST::TypeValue(ASTtypevalue::Array(elements))));
+        }
+        self.bind_constant(&name, value.as_deref());
+
         Ok(AST::Statement(ASTstatement::Let {
             name,
             type_name,
             value,
         }))
     }
+    // Literal Range Example
+    // let:i8 a = 999;
+    // A literal that does not fit the declared type is an error before the program runs
+    fn check_literal_range(
+        &self,
+        type_name: Option<ASTtypename>,
+        type_label: &str,
+        value: Option<&AST>,
+        token: &Token,
+    ) -> Result<(), AstGenError> {
+        if let (Some(type_name), Some(AST::TypeValue(ASTtypevalue::I64(n)))) = (type_name, value) {
+            if !literal_fits(type_name, *n) {
+                let error = AstError::LiteralOverflow(*n, type_label.to_string());
+                return Err(self.report_error(AstGenError::new(error), token));
+            }
+        }
+        Ok(())
+    }
+    // Destructuring Example
+    // let (quotient, _) = divide(7, 2);
+    fn parse_destructure(&mut self) -> Result<AST, AstGenError> {
+        let mut names = Vec::new();
+        loop {
+            let token = self.next_token();
+            match token.token_type {
+                TokenType::TypeValue(TypeValue::Identifier(name)) => names.push(name.to_string()),
+                TokenType::TypeValue(TypeValue::Discard) => names.push("_".to_string()),
+                TokenType::Symbol(Symbol::Comma) if !names.is_empty() => {}
+                TokenType::Symbol(Symbol::CloseParen) if !names.is_empty() => break,
+                _ => {
+                    return Err(
+                        self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)
+                    )
+                }
+            }
+        }
+        let token = self.next_token();
+        if token.token_type != TokenType::Assign(Assign::Assign) {
+            return Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token));
+        }
+        let value = self.parse_expression()?;
+        self.expect_symbol(Symbol::Semicolon)?;
+        Ok(AST::Statement(ASTstatement::Destructure {
+            names,
+            value: Box::new(value),
+        }))
+    }
+    // Fixed Size Array Example
+    // let:[i32; 2 * 4] a;
+    // n := 4; let:[i32; n * 2] b;
+    // The size is folded like the constant expressions of `fold_constants`, after the names
+    // in `constants` are replaced by their values. The array starts out as `size` zeros of
+    // the element type
+    fn parse_array_type(
+        &mut self,
+        type_map: &HashMap<TypeName, ASTtypename>,
+    ) -> Result<Vec<ASTtypevalue>, AstGenError> {
+        let token = self.next_token();
+        let zero = match &token.token_type {
+            TokenType::TypeName(type_name) => type_map.get(type_name).and_then(|t| zero_value(*t)),
+            _ => None,
+        };
+        let Some(zero) = zero else {
+            return Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token));
+        };
+        self.expect_symbol(Symbol::Semicolon)?;
+        let size_token = self.peek_token();
+        let size = fold::fold(fold::substitute(self.parse_expression()?, &self.constants))?;
+        self.expect_symbol(Symbol::CloseSquare)?;
+        let size = match size {
+            AST::TypeValue(ASTtypevalue::I64(size)) => usize::try_from(size)
+                .map_err(|_| AstGenError::new(AstError::NegativeArraySize(size))),
+            other => Err(AstGenError::new(AstError::NonConstantArraySize(
+                other.to_string(),
+            ))),
+        };
+        match size {
+            Ok(size) => Ok(vec![zero; size]),
+            Err(error) => Err(self.report_error(error, &size_token)),
+        }
+    }
 
-    fn parse_for(&mut self) -> Result<AST, AstGenError> {
+    // Labeled Loop Example
+    // 'outer: while (a < 10) { ... }
+    fn parse_labeled_loop(&mut self, label: String) -> Result<AST, AstGenError> {
+        self.expect_symbol(Symbol::Colon)?;
+        let token = self.next_token();
+        match token.token_type {
+            TokenType::Statement(Statement::While) => self.parse_while(Some(label)),
+            TokenType::Statement(Statement::For) => self.parse_for(Some(label)),
+            TokenType::Statement(Statement::Loop) => self.parse_loop(Some(label)),
+            _ => Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)),
+        }
+    }
+    // While Loop Example
+    // while (a < 10) { a += 1; }
+    fn parse_while(&mut self, label: Option<String>) -> Result<AST, AstGenError> {
+        let condition = self.parse_condition()?;
+        let statements = self.parse_loop_body(label.clone(), false)?;
+        Ok(AST::Statement(ASTstatement::While {
+            label,
+            condition: Box::new(condition),
+            statements,
+        }))
+    }
+    // Loop Example
+    // let a = loop { i += 1; if (i == 10) { break i * 2; } };
+    fn parse_loop(&mut self, label: Option<String>) -> Result<AST, AstGenError> {
+        let statements = self.parse_loop_body(label.clone(), true)?;
+        Ok(AST::TypeValue(ASTtypevalue::Loop { label, statements }))
+    }
+    // Condition Example
+    // if (a == 5), while (i < 10)
+    // `if (a = 5)` is an error, `if ((a = 5))` assigns and tests the value
+    fn parse_condition(&mut self) -> Result<AST, AstGenError> {
+        self.condition_paren = *self.peek_type() == TokenType::Symbol(Symbol::OpenParen);
+        let condition = self.parse_expression();
+        self.condition_paren = false;
+        condition
+    }
+    // The labels of the enclosing loops are known while their body is parsed
+    fn parse_loop_body(
+        &mut self,
+        label: Option<String>,
+        has_value: bool,
+    ) -> Result<Vec<AST>, AstGenError> {
+        self.loop_labels.push((label, has_value));
+        let statements = self.parse_statement();
+        self.loop_labels.pop();
+        statements
+    }
+    // `break;`, `continue;`, `break 'label;` or `continue 'label;`
+    // A `break` leaving a `loop` may have a value, `break 'label value;`
+    fn parse_loop_control(&mut self, keyword: &Token) -> Result<AST, AstGenError> {
+        let mut label = None;
+        let token = self.peek_token();
+        if let TokenType::Label(name) = &token.token_type {
+            self.next_token();
+            label = Some(name.clone());
+            if !self.loop_labels.iter().any(|(loop_label, _)| *loop_label == label) {
+                let error = AstGenError::new(AstError::UnknownLabel(name.clone()));
+                return Err(self.report_error(error, &token));
+            }
+        }
+        // The loop that is left, the innermost one without a label
+        let Some(&(_, has_value)) = self
+            .loop_labels
+            .iter()
+            .rev()
+            .find(|(loop_label, _)| label.is_none() || *loop_label == label)
+        else {
+            let error = AstGenError::new(AstError::OutsideLoop(keyword.to_string()));
+            return Err(self.report_error(error, keyword));
+        };
+        if keyword.token_type != TokenType::Statement(Statement::Break) {
+            self.expect_symbol(Symbol::Semicolon)?;
+            return Ok(AST::Statement(ASTstatement::Continue { label }));
+        }
+        let mut value = None;
+        let token = self.peek_token();
+        if token.token_type != TokenType::Symbol(Symbol::Semicolon) {
+            if !has_value {
+                let error = AstGenError::new(AstError::BreakValueOutsideLoop());
+                return Err(self.report_error(error, &token));
+            }
+            value = Some(Box::new(self.parse_expression()?));
+        }
+        self.expect_symbol(Symbol::Semicolon)?;
+        Ok(AST::Statement(ASTstatement::Break { label, value }))
+    }
+    fn parse_for(&mut self, label: Option<String>) -> Result<AST, AstGenError> {
         //let mut statements: Vec<ASTstatement> = Vec::new();
         // parse arguments and for header
         //println!("{} Start of For: {:?} {}", "\x1b[34m", self.next_token(), "\x1b[0m");
@@ -972,7 +1832,7 @@ impl<'a> Parser<'a> {
                     continue;
                 }
                 (TokenType::TypeValue(TypeValue::Number(num)), 4) => {
-                    end_val = ASTtypevalue::I64(num.parse::<i64>().unwrap());
+                    end_val = ASTtypevalue::I64(self.parse_number(num, &token)?);
                     status = 5;
                     continue;
                 }
@@ -981,7 +1841,7 @@ impl<'a> Parser<'a> {
                     continue;
                 }
                 (TokenType::TypeValue(TypeValue::Number(num)), 6) => {
-                    val = ASTtypevalue::I64(num.parse::<i64>().unwrap());
+                    val = ASTtypevalue::I64(self.parse_number(num, &token)?);
                     status = 7;
                     continue;
                 }
@@ -998,14 +1858,14 @@ impl<'a> Parser<'a> {
                     return Err(
                         self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)
                     );
-                    panic!("{} Invalid for statement! {}", "\x1b[31m", "\x1b[0m");
                 }
             }
         }
         // parse statements
-        let statements = self.parse_statement()?;
+        let statements = self.parse_loop_body(label.clone(), false)?;
 
         Ok(AST::Statement(ASTstatement::For {
+            label,
             start: start_val,
             end: end_val,
             value: val,
@@ -1034,10 +1894,44 @@ impl<'a> Parser<'a> {
     // parse statements
     self.parse_statement();
     }*/
-    fn parse_arguments(&mut self) -> Vec<ASTtypecomp> {
+    fn parse_number(&self, num: &str, token: &Token) -> Result<i64, AstGenError> {
+        num.parse::<i64>().map_err(|_| {
+            self.report_error(
+                AstGenError::new(AstError::InvalidNumberFormat(num.to_string())),
+                token,
+            )
+        })
+    }
+    // The lexer already checked that the literal fits its suffix type
+    fn parse_typed_number(
+        &self,
+        num: &str,
+        type_name: TypeName,
+        token: &Token,
+    ) -> Result<AST, AstGenError> {
+        let value = match type_name {
+            TypeName::I8 => num.parse().map(ASTtypevalue::I8).ok(),
+            TypeName::I16 => num.parse().map(ASTtypevalue::I16).ok(),
+            TypeName::I32 => num.parse().map(ASTtypevalue::I32).ok(),
+            TypeName::I64 => num.parse().map(ASTtypevalue::I64).ok(),
+            TypeName::U8 => num.parse().map(ASTtypevalue::U8).ok(),
+            TypeName::U16 => num.parse().map(ASTtypevalue::U16).ok(),
+            TypeName::U32 => num.parse().map(ASTtypevalue::U32).ok(),
+            TypeName::U64 => num.parse().map(ASTtypevalue::U64).ok(),
+            _ => None,
+        };
+        value.map(AST::TypeValue).ok_or_else(|| {
+            self.report_error(
+                AstGenError::new(AstError::InvalidNumberFormat(num.to_string())),
+                token,
+            )
+        })
+    }
+    fn parse_arguments(&mut self) -> Result<Vec<ASTtypecomp>, AstGenError> {
         let mut args: Vec<ASTtypecomp> = Vec::new();
         let mut state: ArgumentParseState = ArgumentParseState::WaitForType;
         let mut cur_type = ASTtypename::TypeVoid;
+        let mut cur_name = String::new();
         let type_map: HashMap<TypeName, ASTtypename> = [
             (TypeName::I8, ASTtypename::I8),
             (TypeName::I16, ASTtypename::I16),
@@ -1049,6 +1943,10 @@ impl<'a> Parser<'a> {
             (TypeName::U64, ASTtypename::U64),
             (TypeName::Bool, ASTtypename::Bool),
             (TypeName::QuotedString, ASTtypename::QuotedString),
+            (TypeName::Array, ASTtypename::Array),
+            (TypeName::Map, ASTtypename::Map),
+            (TypeName::Bytes, ASTtypename::Bytes),
+            (TypeName::Tuple, ASTtypename::Tuple),
         ]
         .iter()
         .cloned()
@@ -1084,11 +1982,7 @@ impl<'a> Parser<'a> {
                     TokenType::TypeValue(TypeValue::Identifier(ident)),
                     ArgumentParseState::WaitForIdentifier,
                 ) => {
-                    let ident_name = ASTtypevalue::Identifier(ident.to_string());
-                    args.push(ASTtypecomp::Argument {
-                        identifier: ident_name,
-                        type_name: cur_type,
-                    });
+                    self.push_argument(&mut args, ident.to_string(), cur_type, None)?;
                     state = ArgumentParseState::WaitForCommaOrCloseParen;
                     cur_type = ASTtypename::TypeVoid;
                 }
@@ -1098,35 +1992,746 @@ impl<'a> Parser<'a> {
                 ) => {
                     state = ArgumentParseState::WaitForType;
                 }
+                (
+                    TokenType::TypeValue(TypeValue::Identifier(ident)),
+                    ArgumentParseState::WaitForType,
+                ) => {
+                    cur_name = ident.to_string();
+                    state = ArgumentParseState::WaitForNameColon;
+                }
+                (TokenType::Symbol(Symbol::Colon), ArgumentParseState::WaitForNameColon) => {
+                    state = ArgumentParseState::WaitForNameType;
+                }
+                (TokenType::TypeName(type_name), ArgumentParseState::WaitForNameType)
+                    if type_map.contains_key(&type_name) =>
+                {
+                    let name = std::mem::take(&mut cur_name);
+                    self.push_argument(&mut args, name, type_map[&type_name], None)?;
+                    state = ArgumentParseState::WaitForCommaOrCloseParen;
+                }
+                (
+                    TokenType::Statement(Statement::Function),
+                    ArgumentParseState::WaitForNameType,
+                ) => {
+                    let name = std::mem::take(&mut cur_name);
+                    let signature = self.parse_function_type(&type_map)?;
+                    let type_name = ASTtypename::Function;
+                    self.push_argument(&mut args, name, type_name, Some(signature))?;
+                    state = ArgumentParseState::WaitForCommaOrCloseParen;
+                }
 
                 _ => {
-                    let error_msg = match state {
-                        ArgumentParseState::WaitForType => {
-                            "Require a type to construct an argument!"
-                        }
-                        ArgumentParseState::WaitForColon => {
-                            "Require a colon to construct an argument!"
-                        }
-                        ArgumentParseState::WaitForIdentifier => {
-                            "Require an identifier to construct an argument!"
-                        }
-                        ArgumentParseState::WaitForCommaOrCloseParen => {
-                            "Require a comma or close paren to construct an argument!"
-                        }
-                    };
-                    //println!("{} {} {}", "\x1b[33m", token, "\x1b[0m");
-                    // return Err(self.report_error(AstGenError {
-                    // message: AstError::UnexpectedToken(),
-                    // }, &token));
-                    panic!("{} {} {}", "\x1b[31m", error_msg, "\x1b[0m");
+                    return Err(
+                        self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token)
+                    );
                 }
             }
         }
 
-        args
+        Ok(args)
+    }
+    // Function Type Example
+    // fn apply(f: fn(i32) -> i32, x: i32)
+    // The `fn` keyword has already been consumed, a missing arrow means a void return
+    fn parse_function_type(
+        &mut self,
+        type_map: &HashMap<TypeName, ASTtypename>,
+    ) -> Result<ASTfunctiontype, AstGenError> {
+        self.expect_symbol(Symbol::OpenParen)?;
+        let mut params = Vec::new();
+        if *self.peek_type() == TokenType::Symbol(Symbol::CloseParen) {
+            self.next_token();
+        } else {
+            loop {
+                params.push(self.parse_type_name(type_map)?);
+                let token = self.next_token();
+                match token.token_type {
+                    TokenType::Symbol(Symbol::Comma) => continue,
+                    TokenType::Symbol(Symbol::CloseParen) => break,
+                    _ => {
+                        let error = AstGenError::new(AstError::UnexpectedToken());
+                        return Err(self.report_error(error, &token));
+                    }
+                }
+            }
+        }
+        let mut return_type = ASTtypename::TypeVoid;
+        if *self.peek_type() == TokenType::Symbol(Symbol::Arrow) {
+            self.next_token();
+            return_type = self.parse_type_name(type_map)?;
+        }
+        Ok(ASTfunctiontype {
+            params,
+            return_type,
+        })
+    }
+    fn parse_type_name(
+        &mut self,
+        type_map: &HashMap<TypeName, ASTtypename>,
+    ) -> Result<ASTtypename, AstGenError> {
+        let token = self.next_token();
+        if let TokenType::TypeName(type_name) = &token.token_type {
+            if let Some(ast_type) = type_map.get(type_name) {
+                return Ok(*ast_type);
+            }
+        }
+        Err(self.report_error(AstGenError::new(AstError::UnexpectedToken()), &token))
+    }
+    // Default Parameter Example
+    // fn greet(name: String = "world")
+    // Only trailing parameters may have a default value
+    fn push_argument(
+        &mut self,
+        args: &mut Vec<ASTtypecomp>,
+        name: String,
+        type_name: ASTtypename,
+        signature: Option<ASTfunctiontype>,
+    ) -> Result<(), AstGenError> {
+        let mut default = None;
+        if *self.peek_type() == TokenType::Assign(Assign::Assign) {
+            self.next_token(); // Consume the `=`
+            default = Some(Box::new(self.parse_expression()?));
+        }
+        let follows_default = args
+            .iter()
+            .any(|arg| matches!(arg, ASTtypecomp::Argument { default: Some(_), .. }));
+        if default.is_none() && follows_default {
+            let token = self.peek_token();
+            return Err(self.report_error(
+                AstGenError::new(AstError::MissingDefaultValue(name)),
+                &token,
+            ));
+        }
+        args.push(ASTtypecomp::Argument {
+            type_name,
+            identifier: ASTtypevalue::Identifier(name),
+            default,
+            signature,
+        });
+        Ok(())
+    }
+    // Folds operations on literal operands of the parsed program into single literals.
+    // A literal division by zero is an error here, also in code that would never run.
+    pub fn fold_constants(&mut self) -> Result<(), AstGenError> {
+        let asts = std::mem::take(&mut self.asts);
+        self.asts = fold::fold_all(asts)?;
+        Ok(())
+    }
+    // Inlines calls to trivial functions and drops the functions `main` never reaches
+    pub fn optimize(&mut self) {
+        let asts = std::mem::take(&mut self.asts);
+        self.asts = inline::optimize(asts);
     }
     #[allow(dead_code)]
+    pub fn get_warnings(&self) -> &Vec<AstWarning> {
+        &self.warnings
+    }
     pub fn get_asts(&self) -> &Vec<AST> {
         &self.asts
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lexer::lex_new_new::Lexer;
+
+    fn parse(code: &str) -> Result<(), Vec<AstGenError>> {
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run()
+    }
+
+    #[test]
+    fn test_error_color() {
+        let code = "fn main() {\n    let = 1;\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        for color in [false, true] {
+            let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+            parser.set_color(color);
+            let errors = parser.run().unwrap_err();
+            assert!(errors[0].pretty_display.contains("--> Error at Line: 2"));
+            assert_eq!(errors[0].pretty_display.contains('\x1b'), color);
+        }
+    }
+    #[test]
+    fn test_untyped_argument() {
+        let error = &parse("fn main(x) {\n}").unwrap_err()[0];
+        assert_eq!(error.message, AstError::UnexpectedToken());
+        assert_eq!(error.line, 1);
+    }
+    #[test]
+    fn test_error_near_first_line() {
+        let error = &parse("fn main() {\n    let = 5;\n}").unwrap_err()[0];
+        assert_eq!(error.message, AstError::UnexpectedToken());
+        assert_eq!(error.line, 2);
+    }
+    #[test]
+    fn test_unclosed_function_body() {
+        let error = &parse("fn main() {\n    let a = 1;").unwrap_err()[0];
+        assert_eq!(error.message, AstError::UnexpectedEOF());
+    }
+    #[test]
+    fn test_fold_literal_arithmetic() {
+        let code = "fn main() -> i64 {\n    return 2 * 3 + 1;\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run().unwrap();
+        parser.fold_constants().unwrap();
+        let expected = AST::Statement(ASTstatement::Return {
+            value: Box::new(AST::TypeValue(ASTtypevalue::I64(7))),
+        });
+        match &parser.get_asts()[0] {
+            AST::Statement(ASTstatement::Function { statements, .. }) => {
+                assert_eq!(statements, &vec![expected]);
+            }
+            other => panic!("expected a function, found {}", other),
+        }
+    }
+    #[test]
+    fn test_fold_literal_division_by_zero() {
+        let code = "fn main() -> i64 {\n    return 1 / 0;\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run().unwrap();
+        let error = parser.fold_constants().unwrap_err();
+        assert_eq!(error.message, AstError::DivisionByZero());
+    }
+    #[test]
+    fn test_chained_access() {
+        let field = |target: AST, name: &str| {
+            AST::TypeValue(ASTtypevalue::Field {
+                target: Box::new(target),
+                name: name.to_string(),
+            })
+        };
+        let method = |target: AST, name: &str| {
+            AST::TypeValue(ASTtypevalue::MethodCall {
+                target: Box::new(target),
+                name: name.to_string(),
+                args: Vec::new(),
+            })
+        };
+        let a = AST::TypeValue(ASTtypevalue::Identifier("a".to_string()));
+        let cases = [
+            ("a.b.c", field(field(a.clone(), "b"), "c")),
+            ("a.b().c", field(method(a.clone(), "b"), "c")),
+        ];
+        for (expression, expected) in cases {
+            let code = format!("fn main() {{\n    let x = {};\n}}", expression);
+            let mut lexer = Lexer::new(PathBuf::from("test.nk"), &code);
+            assert!(lexer.run().is_ok());
+            let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), &code);
+            parser.run().unwrap();
+            let expected = AST::Statement(ASTstatement::Let {
+                name: "x".to_string(),
+                type_name: None,
+                value: Some(Box::new(expected)),
+            });
+            match &parser.get_asts()[0] {
+                AST::Statement(ASTstatement::Function { statements, .. }) => {
+                    assert_eq!(statements, &vec![expected]);
+                }
+                other => panic!("expected a function, found {}", other),
+            }
+        }
+    }
+    #[test]
+    fn test_optimize_inlines_and_drops_functions() {
+        let code = "fn double(n: i64) -> i64 {\n    return n * 2;\n}\n\
+                    fn unused() -> i64 {\n    return 1;\n}\n\
+                    fn main() -> i64 {\n    return double(4);\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run().unwrap();
+        parser.optimize();
+        let names: Vec<String> = parser
+            .get_asts()
+            .iter()
+            .filter_map(|ast| match ast {
+                AST::Statement(ASTstatement::Function { name, .. }) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["main".to_string()]);
+        let expected = AST::Statement(ASTstatement::Return {
+            value: Box::new(AST::TypeValue(ASTtypevalue::Block {
+                statements: vec![
+                    AST::Statement(ASTstatement::Let {
+                        name: "n".to_string(),
+                        type_name: Some(ASTtypename::I64),
+                        value: Some(Box::new(AST::TypeValue(ASTtypevalue::I64(4)))),
+                    }),
+                    AST::Statement(ASTstatement::Let {
+                        name: "n".to_string(),
+                        type_name: Some(ASTtypename::I64),
+                        value: Some(Box::new(AST::Logic(ASTlogic::BinaryOperation {
+                            left: Box::new(AST::TypeValue(ASTtypevalue::Identifier(
+                                "n".to_string(),
+                            ))),
+                            op: ASTOperator::Multiply,
+                            right: Box::new(AST::TypeValue(ASTtypevalue::I64(2))),
+                        }))),
+                    }),
+                ],
+                value: Some(Box::new(AST::TypeValue(ASTtypevalue::Identifier(
+                    "n".to_string(),
+                )))),
+            })),
+        });
+        match &parser.get_asts()[0] {
+            AST::Statement(ASTstatement::Function { statements, .. }) => {
+                assert_eq!(statements, &vec![expected]);
+            }
+            other => panic!("expected a function, found {}", other),
+        }
+    }
+    #[test]
+    fn test_multiple_errors() {
+        let code = "fn main() {\n    let = 5;\n    let b 6;\n    return;\n}";
+        let errors = parse(code).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(errors[1].line, 3);
+    }
+    #[test]
+    fn test_named_typed_parameters() {
+        let code = "fn add(a: i32, b: i32) -> i32 {\n    return a + b;\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run().unwrap();
+        let expected = vec![
+            ASTtypecomp::Argument {
+                type_name: ASTtypename::I32,
+                identifier: ASTtypevalue::Identifier("a".to_string()),
+                default: None,
+                signature: None,
+            },
+            ASTtypecomp::Argument {
+                type_name: ASTtypename::I32,
+                identifier: ASTtypevalue::Identifier("b".to_string()),
+                default: None,
+                signature: None,
+            },
+        ];
+        match &parser.get_asts()[0] {
+            AST::Statement(ASTstatement::Function {
+                args, return_type, ..
+            }) => {
+                assert_eq!(args, &expected);
+                assert_eq!(*return_type, ASTtypename::I32);
+            }
+            other => panic!("expected a function, found {}", other),
+        }
+    }
+    #[test]
+    fn test_function_type_parameters() {
+        let code = "fn apply(f: fn(i32) -> i32, x: i32) -> i32 {\n    return f(x);\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run().unwrap();
+        let signature = ASTfunctiontype {
+            params: vec![ASTtypename::I32],
+            return_type: ASTtypename::I32,
+        };
+        assert_eq!(signature.to_string(), "fn(I32) -> I32");
+        let expected = vec![
+            ASTtypecomp::Argument {
+                type_name: ASTtypename::Function,
+                identifier: ASTtypevalue::Identifier("f".to_string()),
+                default: None,
+                signature: Some(signature),
+            },
+            ASTtypecomp::Argument {
+                type_name: ASTtypename::I32,
+                identifier: ASTtypevalue::Identifier("x".to_string()),
+                default: None,
+                signature: None,
+            },
+        ];
+        match &parser.get_asts()[0] {
+            AST::Statement(ASTstatement::Function { args, .. }) => assert_eq!(args, &expected),
+            other => panic!("expected a function, found {}", other),
+        }
+        let cases = [
+            "fn run(f: fn()) {\n    f();\n}",
+            "fn run(f: fn(i32, String) -> Bool) {\n    f(1, \"a\");\n}",
+        ];
+        for code in cases {
+            assert!(parse(code).is_ok(), "{}", code);
+        }
+        for code in ["fn run(f: fn(i32) ->) {\n}", "fn run(f: fn i32) {\n}"] {
+            assert!(parse(code).is_err(), "{}", code);
+        }
+    }
+    #[test]
+    fn test_match_arms() {
+        let code = "fn main() {\n    match x {\n        1 => 10,\n        _ => 0,\n    }\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        parser.run().unwrap();
+        let expected = AST::TypeValue(ASTtypevalue::Match {
+            value: Box::new(AST::TypeValue(ASTtypevalue::Identifier("x".to_string()))),
+            arms: vec![
+                (
+                    AST::TypeValue(ASTtypevalue::I64(1)),
+                    AST::TypeValue(ASTtypevalue::I64(10)),
+                ),
+                (
+                    AST::TypeValue(ASTtypevalue::Discard),
+                    AST::TypeValue(ASTtypevalue::I64(0)),
+                ),
+            ],
+        });
+        match &parser.get_asts()[0] {
+            AST::Statement(ASTstatement::Function { statements, .. }) => {
+                assert_eq!(statements, &vec![expected]);
+            }
+            other => panic!("expected a function, found {}", other),
+        }
+    }
+    // Statements of the first function in `code`
+    fn function_body(code: &str) -> Vec<AST> {
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        match &parser.get_asts()[0] {
+            AST::Statement(ASTstatement::Function { statements, .. }) => statements.clone(),
+            other => panic!("expected a function, found {}", other),
+        }
+    }
+    fn if_node(condition: &str, statements: Vec<AST>, else_statements: Option<Vec<AST>>) -> AST {
+        AST::Statement(ASTstatement::If {
+            condition: Box::new(AST::TypeValue(ASTtypevalue::Identifier(
+                condition.to_string(),
+            ))),
+            statements,
+            elif: None,
+            else_statements,
+        })
+    }
+    fn return_node(n: i64) -> AST {
+        AST::Statement(ASTstatement::Return {
+            value: Box::new(AST::TypeValue(ASTtypevalue::I64(n))),
+        })
+    }
+    #[test]
+    fn test_if_without_braces() {
+        let braced = "fn main() {\n    if (a) {\n        return 1;\n    } else {\n        return 2;\n    }\n\
+                      return 3;\n}";
+        let braceless = "fn main() {\n    if (a) return 1; else return 2;\n    return 3;\n}";
+        let expected = vec![
+            if_node("a", vec![return_node(1)], Some(vec![return_node(2)])),
+            return_node(3),
+        ];
+        assert_eq!(function_body(braced), expected);
+        assert_eq!(function_body(braceless), expected);
+    }
+    #[test]
+    fn test_dangling_else_binds_to_inner_if() {
+        let code = "fn main() {\n    if (a) if (b) return 1; else return 2;\n    return 3;\n}";
+        let inner = if_node("b", vec![return_node(1)], Some(vec![return_node(2)]));
+        assert_eq!(
+            function_body(code),
+            vec![if_node("a", vec![inner], None), return_node(3)]
+        );
+    }
+    #[test]
+    fn test_match_arm_without_arrow() {
+        let error = &parse("fn main() {\n    match x {\n        1 10\n    }\n}").unwrap_err()[0];
+        assert_eq!(error.line, 3);
+    }
+    #[test]
+    fn test_unreachable_after_return() {
+        let code = "fn main() -> i64 {\n    if (a) {\n        return 1;\n        let b = 2;\n\
+                    let c = 3;\n    }\n    return 2;\n    println(\"done\");\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        assert_eq!(
+            parser.get_warnings(),
+            &vec![
+                AstWarning::UnreachableCode("return".to_string(), 4),
+                AstWarning::UnreachableCode("return".to_string(), 8)
+            ]
+        );
+        assert_eq!(
+            parser.get_warnings()[0].to_string(),
+            "unreachable code after return at line 4"
+        );
+    }
+    #[test]
+    fn test_unreachable_after_loop_control() {
+        let code = "fn main() {\n    while (a) {\n        break;\n        let b = 2;\n    }\n\
+                    for(i -> 3 :: 1) {\n        continue;\n        println(\"{}\", i);\n    }\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        let warnings: Vec<String> = parser.get_warnings().iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "unreachable code after break at line 4",
+                "unreachable code after continue at line 8"
+            ]
+        );
+    }
+    #[test]
+    fn test_panic_argument_count() {
+        let error = &parse("fn main() {\n    panic();\n}").unwrap_err()[0];
+        assert_eq!(error.message, AstError::MismatchedArgumentCount(1, 0));
+        assert_eq!(error.line, 2);
+    }
+    #[test]
+    fn test_parameter_without_default_after_default() {
+        let error = &parse("fn greet(a: i64 = 1, b: i64) {\n}").unwrap_err()[0];
+        assert_eq!(
+            error.message,
+            AstError::MissingDefaultValue("b".to_string())
+        );
+    }
+    #[test]
+    fn test_brace_lookahead() {
+        let value = |code: &str| match &function_body(code)[0] {
+            AST::Statement(ASTstatement::Let { value: Some(value), .. }) => (**value).clone(),
+            other => panic!("expected a let, found {:?}", other),
+        };
+        let k = || AST::TypeValue(ASTtypevalue::Identifier("k".to_string()));
+        let one = || AST::TypeValue(ASTtypevalue::I64(1));
+        assert_eq!(
+            value("fn main() {\n    let a = { k: 1 };\n}"),
+            AST::TypeValue(ASTtypevalue::Map(vec![(k(), one())]))
+        );
+        assert_eq!(
+            value("fn main() {\n    let a = { k };\n}"),
+            AST::TypeValue(ASTtypevalue::Block {
+                statements: Vec::new(),
+                value: Some(Box::new(k())),
+            })
+        );
+        assert_eq!(
+            value("fn main() {\n    let a = {};\n}"),
+            AST::TypeValue(ASTtypevalue::Map(Vec::new()))
+        );
+    }
+    #[test]
+    fn test_assignment_in_condition() {
+        for condition in ["if (a = 5) {\n    }", "while (a = b) {\n    }"] {
+            let code = format!("fn main() {{\n    {}\n}}", condition);
+            let error = &parse(&code).unwrap_err()[0];
+            assert_eq!((&error.message, error.line), (&AstError::AssignmentInCondition(), 2));
+            assert_eq!(
+                error.message.to_string(),
+                "Assignment used as a condition, did you mean '=='?"
+            );
+        }
+        let statements = function_body("fn main() {\n    if ((a = 5)) {\n    }\n}");
+        let AST::Statement(ASTstatement::If { condition, .. }) = &statements[0] else {
+            panic!("expected an if, found {:?}", statements);
+        };
+        assert!(matches!(**condition, AST::Statement(ASTstatement::Assignment { .. })));
+        assert!(parse("fn main() {\n    if (a == 5) {\n    }\n}").is_ok());
+    }
+    #[test]
+    fn test_if_let() {
+        let statements = function_body("fn main() {\n    if let Some(x) = v {\n    }\n}");
+        let AST::Statement(ASTstatement::If { condition, .. }) = &statements[0] else {
+            panic!("expected an if, found {:?}", statements);
+        };
+        assert_eq!(condition.to_string(), "let Some(x) = v");
+        for value in ["5", "\"text\"", "1 + 2"] {
+            let code = format!("fn main() {{\n    if let Some(x) = {} {{\n    }}\n}}", value);
+            let error = &parse(&code).unwrap_err()[0];
+            assert!(matches!(error.message, AstError::NotOptional(_)), "{}", value);
+        }
+        assert!(parse("fn main() {\n    if let x = y {\n    }\n}").is_err());
+    }
+    #[test]
+    fn test_statements_on_one_line() {
+        let statements = function_body("fn main() {\n    let:i32 a=1; let:i32 b=2;\n}");
+        let expected = function_body("fn main() {\n    let:i32 a=1;\n    let:i32 b=2;\n}");
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements, expected);
+        let code = "fn main() -> i32 {let:i32 a=1;let:i32 b=2;a+=b;if(a==3){a=0;}return a;}";
+        assert!(parse(code).is_ok());
+    }
+    #[test]
+    fn test_literal_overflow() {
+        for value in ["127", "0", "a"] {
+            let code = format!("fn main() {{\n    let:i8 a = {};\n}}", value);
+            assert!(parse(&code).is_ok(), "{}", value);
+        }
+        let cases = [
+            ("let:i8 a = 999;", AstError::LiteralOverflow(999, "i8".to_string())),
+            ("let:u8 a = 256;", AstError::LiteralOverflow(256, "u8".to_string())),
+        ];
+        for (statement, expected) in cases {
+            let code = format!("fn main() {{\n    {}\n}}", statement);
+            let error = &parse(&code).unwrap_err()[0];
+            assert_eq!((&error.message, error.line), (&expected, 2), "{}", statement);
+        }
+        let error = AstError::LiteralOverflow(999, "i8".to_string());
+        assert_eq!(error.to_string(), "Literal 999 does not fit in i8");
+    }
+    #[test]
+    fn test_missing_parens() {
+        let cases = [
+            ("fn main {\n}", "main", 1),
+            ("fn helper -> i64 {\n    return 1;\n}", "helper", 1),
+            ("fn main() {\n    helper 5;\n}", "helper", 2),
+            ("fn main() {\n    let a = helper \"x\";\n}", "helper", 2),
+        ];
+        for (code, name, line) in cases {
+            let error = &parse(code).unwrap_err()[0];
+            let expected = AstError::MissingParens(name.to_string());
+            assert_eq!((&error.message, error.line), (&expected, line), "{}", code);
+            assert_eq!(error.message.to_string(), "expected '(' after function name");
+        }
+        assert!(parse("fn main() {\n    helper();\n    let a = helper;\n}").is_ok());
+    }
+    #[test]
+    fn test_loop_labels() {
+        let statements = function_body(
+            "fn main() {\n    'outer: while (a) {\n        while (b) {\n            \
+             break 'outer;\n        }\n        continue;\n    }\n}",
+        );
+        let identifier =
+            |name: &str| Box::new(AST::TypeValue(ASTtypevalue::Identifier(name.to_string())));
+        let inner = AST::Statement(ASTstatement::While {
+            label: None,
+            condition: identifier("b"),
+            statements: vec![AST::Statement(ASTstatement::Break {
+                label: Some("outer".to_string()),
+                value: None,
+            })],
+        });
+        let outer = AST::Statement(ASTstatement::While {
+            label: Some("outer".to_string()),
+            condition: identifier("a"),
+            statements: vec![inner, AST::Statement(ASTstatement::Continue { label: None })],
+        });
+        assert_eq!(statements, vec![outer]);
+        let cases = [
+            (
+                "fn main() {\n    'a: while (x) {\n    }\n    while (y) {\n        break 'a;\n    \
+                 }\n}",
+                AstError::UnknownLabel("a".to_string()),
+                5,
+            ),
+            (
+                "fn main() {\n    continue;\n}",
+                AstError::OutsideLoop("continue".to_string()),
+                2,
+            ),
+            (
+                "fn main() {\n    loop {\n        while (y) {\n            break 5;\n        \
+                 }\n    }\n}",
+                AstError::BreakValueOutsideLoop(),
+                4,
+            ),
+        ];
+        for (code, message, line) in cases {
+            let error = &parse(code).unwrap_err()[0];
+            assert_eq!((&error.message, error.line), (&message, line), "{}", code);
+        }
+        let code = "fn main() {\n    'a: loop {\n        while (y) {\n            \
+                    break 'a 5;\n        }\n    }\n}";
+        assert!(parse(code).is_ok());
+    }
+    #[test]
+    fn test_function_attributes() {
+        let code = "@inline\n@memo\nfn square(x: i64) -> i64 {\n    return x * x;\n}\n\
+                    fn main() {\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        let attributes: Vec<Vec<String>> = parser
+            .get_asts()
+            .iter()
+            .map(|ast| match ast {
+                AST::Statement(ASTstatement::Function { attributes, .. }) => attributes.clone(),
+                other => panic!("expected a function, found {}", other),
+            })
+            .collect();
+        let expected = vec!["inline".to_string(), "memo".to_string()];
+        assert_eq!(attributes, vec![expected, Vec::new()]);
+        assert_eq!(
+            parser.get_warnings(),
+            &vec![AstWarning::UnknownAttribute("memo".to_string(), 2)]
+        );
+        let error = &parse("@inline\nlet a = 1;").unwrap_err()[0];
+        assert_eq!(error.line, 2);
+    }
+    #[test]
+    fn test_doc_comments() {
+        let code = "//! Math helpers\n/// Adds one\n/// to x\n@inline\nfn inc(x: i64) -> i64 {\n\
+                    /// ignored\n    return x + 1;\n}\n/// dropped\nlet a = 1;\nfn main() {\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        let docs: Vec<Vec<String>> = parser
+            .get_asts()
+            .iter()
+            .map(|ast| match ast {
+                AST::Statement(ASTstatement::Function { docs, .. }) => docs.clone(),
+                other => panic!("expected a function, found {}", other),
+            })
+            .collect();
+        let expected = vec![" Adds one".to_string(), " to x".to_string()];
+        assert_eq!(docs, vec![expected, Vec::new()]);
+    }
+    #[test]
+    fn test_tuples() {
+        let body = function_body("fn main() {\n    let (a, _) = (1, x.0);\n}");
+        let field = AST::TypeValue(ASTtypevalue::Field {
+            target: Box::new(AST::TypeValue(ASTtypevalue::Identifier("x".to_string()))),
+            name: "0".to_string(),
+        });
+        let tuple = ASTtypevalue::Tuple(vec![AST::TypeValue(ASTtypevalue::I64(1)), field]);
+        let expected = ASTstatement::Destructure {
+            names: vec!["a".to_string(), "_".to_string()],
+            value: Box::new(AST::TypeValue(tuple)),
+        };
+        assert_eq!(body, vec![AST::Statement(expected)]);
+        assert!(parse("fn main() {\n    let () = x;\n}").is_err());
+    }
+    #[test]
+    fn test_fixed_size_arrays() {
+        let array = |size: usize| {
+            vec![AST::Statement(ASTstatement::Let {
+                name: "a".to_string(),
+                type_name: Some(ASTtypename::Array),
+                value: Some(Box::new(AST::TypeValue(ASTtypevalue::Array(vec![
+                    ASTtypevalue::I32(0);
+                    size
+                ])))),
+            })]
+        };
+        assert_eq!(function_body("fn main() {\n    let:[i32; 3] a;\n}"), array(3));
+        assert_eq!(function_body("fn main() {\n    let:[i32; 2 * 4 - 8] a;\n}"), array(0));
+        let code = "fn main() {\n    n := 2;\n    let m = n * 3;\n    let:[i32; m - n] a;\n}";
+        assert_eq!(function_body(code)[2..], array(4));
+        let cases = [
+            ("let:[i32; n] a;", AstError::NonConstantArraySize("n".to_string())),
+            ("n := 2; n = 3; let:[i32; n] a;", AstError::NonConstantArraySize("n".to_string())),
+            ("let:[i32; 1 - 2] a;", AstError::NegativeArraySize(-1)),
+        ];
+        for (statement, message) in cases {
+            let error = &parse(&format!("fn main() {{\n    {}\n}}", statement)).unwrap_err()[0];
+            assert_eq!((&error.message, error.line), (&message, 2), "{}", statement);
+        }
+    }
+}
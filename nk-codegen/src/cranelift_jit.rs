@@ -102,6 +102,13 @@ impl JIT {
         file_location: &str,
         is_lib: bool,
     ) -> Result<*const u8, String> {
+        // An empty program has nothing to run, report it before generating any code
+        let has_main = input.iter().any(|ast| {
+            matches!(ast, AST::Statement(ASTstatement::Function { name, .. }) if name == "main")
+        });
+        if !is_lib && !has_main {
+            return Err("no `main` function found".to_string());
+        }
         let mut funcid = HashMap::new();
         self.define_print_function();
         self.define_println_function();
@@ -113,15 +120,13 @@ impl JIT {
                     args,
                     statements: _,
                     return_type,
+                    ..
                 } = statement {
                 let int = self.module.target_config().pointer_type();
 
                 for p in args.clone() {
                     match p {
-                        ASTtypecomp::Argument {
-                            type_name,
-                            identifier: _,
-                        } => {
+                        ASTtypecomp::Argument { type_name, .. } => {
                             self.ctx
                                 .func
                                 .signature
@@ -159,7 +164,7 @@ impl JIT {
                         })?;
                         let mut new_lexer = lexer::lex_new::Lexer::new(&contents);
                         new_lexer.run();
-                        let new_tokens = new_lexer.get_tokens();
+                        let new_tokens = new_lexer.tokens();
 
                         let mut new_new_lexer = lexer::lex_new_new::Lexer::new(
                             Path::new(&name).to_path_buf(),
@@ -169,16 +174,17 @@ impl JIT {
                         if lex_result.is_err() {
                             println!("Error: {}", lex_result.err().unwrap());
                         }
-                        let new_new_tokens = new_new_lexer.get_tokens();
+                        let new_new_tokens = new_new_lexer.tokens();
 
                         let mut mid_ir = astgen::parser_new::Parser::new(
                             new_new_tokens,
                             Path::new(&name).to_path_buf(),
                             &contents,
                         );
-                        let ast_result = mid_ir.run();
-                        if ast_result.is_err() {
-                            println!("Error: {}", ast_result.err().unwrap());
+                        if let Err(errors) = mid_ir.run() {
+                            for error in errors {
+                                println!("Error: {}", error);
+                            }
                         }
                         let ast_new = mid_ir.get_asts();
                         let _ =
@@ -194,6 +200,7 @@ impl JIT {
                         args,
                         statements,
                         return_type,
+                        ..
                     } => {
                         self.ctx.func.signature =
                             self.functions.get(name.as_str()).unwrap().clone();
@@ -233,7 +240,7 @@ impl JIT {
 
             let code = self
                 .module
-                .get_finalized_function(*funcid.get("main").expect("main function not found"));
+                .get_finalized_function(*funcid.get("main").ok_or("no `main` function found")?);
 
             println!("code: {:?}", code);
             Ok(code)
@@ -382,6 +389,8 @@ impl FunctionTranslator<'_> {
                 self.builder.ins().iconst(types::I32, i64::from(imm))
             }
             ASTtypevalue::I64(i) => self.builder.ins().iconst(types::I64, i),
+            // Like the result of `icmp`, so folded comparisons branch the same way
+            ASTtypevalue::Bool(b) => self.builder.ins().iconst(types::I8, i64::from(b)),
 
             ASTtypevalue::Identifier(id) => {
                 if let Some(variable) = self.variables.get(&id) {
@@ -529,6 +538,7 @@ impl FunctionTranslator<'_> {
                         end,
                         value,
                         statements,
+                        ..
                     } => self.translate_for(start, end, value, statements),
                     ASTstatement::If {
                         condition,
@@ -934,10 +944,7 @@ fn declare_signature(
     let mut params = Vec::new();
     for arg in args {
         match arg {
-            ASTtypecomp::Argument {
-                type_name,
-                identifier: _,
-            } => {
+            ASTtypecomp::Argument { type_name, .. } => {
                 let _type_val = translate_type(int, *type_name);
                 params.push(AbiParam::new(translate_type(int, *type_name)));
             }
@@ -968,6 +975,7 @@ fn declare_variables(
         if let ASTtypecomp::Argument {
             identifier,
             type_name,
+            ..
         } = param
         {
             // Assuming ASTtypevalue has a method to_string() to convert it to a String
@@ -268,9 +268,13 @@ impl<'a> Lexer<'a> {
         );
     }
 
+    // Clones every token, `tokens` borrows them instead
     pub fn get_tokens(&self) -> Vec<Token> {
         self.tokens.clone()
     }
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
 }
 
 #[cfg(test)]
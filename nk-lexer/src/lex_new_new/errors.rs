@@ -1,11 +1,70 @@
 use std::fmt;
 
+use crate::neo_tokens::TypeName;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LexcialError {
     pub line: usize,
     pub column: usize,
     pub message: LexError,
 }
+impl LexcialError {
+    // A hint on how to fix the error, shown below it
+    // Control characters are escaped, `'\u{7}'` instead of a bell in the terminal
+    pub fn suggestion(&self) -> String {
+        match self.message {
+            LexError::InvalidCharacter(ref ch) => {
+                format!(
+                    "Unexpected character '{}'. Try removing or replacing it.",
+                    ch.escape_debug()
+                )
+            }
+            LexError::InvalidTypeName(ref ch) => {
+                format!("Unexpected type'{}'.", ch)
+            }
+            LexError::InvalidNumber(ref n) => {
+                format!("Invalid number '{}'.", n)
+            }
+            LexError::InvalidIdentifier(ref i) => {
+                format!("Invalid identifier '{}'.", i)
+            }
+            LexError::InvalidOperator(ref o) => {
+                format!("Invalid operator '{}'.", o)
+            }
+            LexError::InvalidSymbol(ref s) => {
+                format!("Invalid symbol '{}'.", s.escape_debug())
+            }
+            LexError::InvalidStatement(ref s) => {
+                format!("Invalid statement '{}'.", s)
+            }
+            LexError::InvalidDoubleSymbol(ref s) => {
+                format!("Invalid double symbol '{}'.", s)
+            }
+            LexError::ExpectedQuote() => {
+                "Check the syntax around the error line, and add a double quote.".to_string()
+            }
+            LexError::UnterminatedComment() => {
+                "Close the comment with `*/`, every nested `/*` needs its own `*/`.".to_string()
+            }
+            LexError::IdentifierTooLong(_, _) => "Use a shorter name.".to_string(),
+            LexError::LineTooLong(_) => {
+                "Split the line, or check for accidentally pasted input.".to_string()
+            }
+            LexError::InvalidEscape(_) => {
+                "Use one of \\n \\t \\r \\0 \\\\ \\\" or \\u{...}.".to_string()
+            }
+            LexError::InvalidByteEscape(_) => {
+                "Use one of \\n \\t \\r \\0 \\\\ \\\" or \\xNN with two hex digits.".to_string()
+            }
+            LexError::InvalidCodePoint(_) => {
+                "Use a unicode scalar value, at most 10FFFF and not a surrogate.".to_string()
+            }
+            LexError::NumberOutOfRange(_, ref t) => {
+                format!("Use a value in the range of {} or a wider suffix.", t)
+            }
+        }
+    }
+}
 impl fmt::Display for LexcialError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Lexical Error: {}", self.message)
@@ -24,6 +83,31 @@ pub enum LexError {
     InvalidTypeName(String),
     InvalidDoubleSymbol(String),
     ExpectedQuote(),
+    // `/*` without a matching `*/`, at the position of the outermost `/*`
+    UnterminatedComment(),
+    NumberOutOfRange(String, TypeName),
+    IdentifierTooLong(String, usize),
+    LineTooLong(usize),
+    InvalidEscape(String),
+    // Escape in a `b"..."` string, where `\xNN` replaces `\u{...}`
+    InvalidByteEscape(String),
+    InvalidCodePoint(String),
+}
+// Questionable input that still lexes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum LexWarning {
+    // Line whose indentation has both tabs and spaces
+    MixedIndentation(usize),
+}
+impl fmt::Display for LexWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexWarning::MixedIndentation(line) => {
+                write!(f, "line {} mixes tabs and spaces in its indentation", line)
+            }
+        }
+    }
 }
 impl fmt::Display for LexError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -37,6 +121,15 @@ impl fmt::Display for LexError {
             LexError::InvalidTypeName(t) => write!(f, "Invalid type name: {}", t),
             LexError::InvalidDoubleSymbol(s) => write!(f, "Invalid double symbol: {}", s),
             LexError::ExpectedQuote() => write!(f, "Expected quote"),
+            LexError::UnterminatedComment() => write!(f, "Unterminated block comment"),
+            LexError::NumberOutOfRange(n, t) => write!(f, "Number {} does not fit in {}", n, t),
+            LexError::IdentifierTooLong(i, max) => {
+                write!(f, "Identifier {} is longer than {} characters", i, max)
+            }
+            LexError::LineTooLong(max) => write!(f, "Line is longer than {} columns", max),
+            LexError::InvalidEscape(e) => write!(f, "Invalid escape: {}", e),
+            LexError::InvalidByteEscape(e) => write!(f, "Invalid byte string escape: {}", e),
+            LexError::InvalidCodePoint(c) => write!(f, "Invalid unicode code point: {}", c),
         }
     }
 }
@@ -1,7 +1,7 @@
 use crate::lex_new_new::errors::LexError;
 use crate::lex_new_new::errors::LexcialError;
 // use crate::tokens_new::{Statement, TokenType, TypeName};
-use crate::neo_tokens::{Statement, TokenType, TypeName};
+use crate::neo_tokens::{Statement, TokenType, TypeName, TypeValue};
 
 #[allow(dead_code)]
 pub fn statement_to_token(
@@ -18,10 +18,14 @@ pub fn statement_to_token(
         "if" => Ok(TokenType::Statement(Statement::If)),
         "else" => Ok(TokenType::Statement(Statement::Else)),
         "while" => Ok(TokenType::Statement(Statement::While)),
+        "loop" => Ok(TokenType::Statement(Statement::Loop)),
         "print" => Ok(TokenType::Statement(Statement::Print)),
         "println" => Ok(TokenType::Statement(Statement::Println)),
         "scanln" => Ok(TokenType::Statement(Statement::Scanln)),
         "for" => Ok(TokenType::Statement(Statement::For)),
+        "match" => Ok(TokenType::Statement(Statement::Match)),
+        "break" => Ok(TokenType::Statement(Statement::Break)),
+        "continue" => Ok(TokenType::Statement(Statement::Continue)),
         /*"void" => Ok(TokenType::TypeName(TypeName::Void)),
         "bool" => Ok(TokenType::TypeName(TypeName::Bool)),
         "string" => Ok(TokenType::TypeName(TypeName::QuotedString)),
@@ -40,6 +44,52 @@ pub fn statement_to_token(
         }),
     }
 }
+// Keywords that are values rather than statements
+pub fn value_keyword_to_token(value: &str) -> Option<TokenType> {
+    match value {
+        "none" => Some(TokenType::TypeValue(TypeValue::NoneVoid)),
+        "_" => Some(TokenType::TypeValue(TypeValue::Discard)),
+        _ => None,
+    }
+}
+// Names after a sigil, `'name` labels a loop and `@name` is an attribute
+pub fn is_sigil(c: char) -> bool {
+    matches!(c, '\'' | '@')
+}
+// The name after the sigil has to start like an identifier
+pub fn sigil_to_token(
+    sigil: char,
+    name: &str,
+    line: usize,
+    column: usize,
+) -> Result<TokenType, LexcialError> {
+    match name.chars().next() {
+        Some(c) if is_first_identifierable(c) && sigil == '@' => {
+            Ok(TokenType::Attribute(name.to_string()))
+        }
+        Some(c) if is_first_identifierable(c) => Ok(TokenType::Label(name.to_string())),
+        _ => Err(LexcialError {
+            line,
+            column,
+            message: LexError::InvalidSymbol(format!("{}{}", sigil, name)),
+        }),
+    }
+}
+// The void type is only spelled `Void`, `void` is rejected instead of read as an identifier
+pub fn check_type_name_spelling(
+    name: &str,
+    line: usize,
+    column: usize,
+) -> Result<(), LexcialError> {
+    match name {
+        "void" => Err(LexcialError {
+            line,
+            column,
+            message: LexError::InvalidTypeName(name.to_string()),
+        }),
+        _ => Ok(()),
+    }
+}
 #[allow(dead_code)]
 pub fn type_name_to_token(
     typename: &str,
@@ -59,6 +109,10 @@ pub fn type_name_to_token(
         "u32" => Ok(TokenType::TypeName(TypeName::U32)),
         "u64" => Ok(TokenType::TypeName(TypeName::U64)),
         "Char" => Ok(TokenType::TypeName(TypeName::Char)),
+        "Array" => Ok(TokenType::TypeName(TypeName::Array)),
+        "Map" => Ok(TokenType::TypeName(TypeName::Map)),
+        "Bytes" => Ok(TokenType::TypeName(TypeName::Bytes)),
+        "Tuple" => Ok(TokenType::TypeName(TypeName::Tuple)),
         _ => Err(LexcialError {
             line,
             column,
@@ -4,9 +4,8 @@ mod identifier;
 mod symbol;
 mod value;
 
-use errors::{LexError, LexcialError};
+pub use errors::{LexError, LexWarning, LexcialError};
 
-use std::borrow::Cow;
 use std::iter::Peekable;
 use std::path::PathBuf;
 use std::str::Chars;
@@ -38,6 +37,8 @@ enum State {
     QuotedString,
     DoubleState,
     Comment,
+    // Inside `/* */`, which may nest
+    BlockComment,
 }
 
 pub struct Lexer<'a> {
@@ -46,10 +47,31 @@ pub struct Lexer<'a> {
     state: State,
     buffer_st: usize,
     buffer_ed: usize,
+    // First char of the buffer, updated in `next_char` so the buffer is not re-sliced
+    first_char: char,
     line: usize,
     column: usize,
     file_path: PathBuf,
     source: &'a str,
+    // The string being read has a `b` prefix
+    byte_string: bool,
+    // Emit comments as tokens instead of skipping them
+    preserve_comments: bool,
+    // Optional limits for catching pasted or generated input, unlimited by default
+    max_identifier_length: Option<usize>,
+    max_line_length: Option<usize>,
+    // Warn about lines indented with both tabs and spaces, off by default
+    lint_mixed_indentation: bool,
+    // Whitespace seen so far at the start of the current line, as (tabs, spaces)
+    indentation: Option<(bool, bool)>,
+    warnings: Vec<LexWarning>,
+    // Errors are written to stderr as they are found, unless the caller reports them itself
+    print_errors: bool,
+    // Errors `run_all` writes before it only counts the rest, unlimited by default
+    max_errors: Option<usize>,
+    // Number of `/*` not closed yet, and the position of the outermost one
+    comment_depth: usize,
+    comment_start: (usize, usize),
 }
 
 impl<'a> Lexer<'a> {
@@ -61,16 +83,58 @@ impl<'a> Lexer<'a> {
             state: State::EmptyState,
             buffer_st: 0,
             buffer_ed: 0,
+            first_char: '\0',
             line: 1,
             column: 0,
             file_path,
             source: code,
+            byte_string: false,
+            preserve_comments: false,
+            max_identifier_length: None,
+            max_line_length: None,
+            lint_mixed_indentation: false,
+            indentation: Some((false, false)),
+            warnings: Vec::new(),
+            print_errors: true,
+            max_errors: None,
+            comment_depth: 0,
+            comment_start: (0, 0),
         }
     }
+    pub fn set_max_identifier_length(&mut self, max: Option<usize>) {
+        self.max_identifier_length = max;
+    }
+    pub fn set_max_line_length(&mut self, max: Option<usize>) {
+        self.max_line_length = max;
+    }
+    pub fn set_lint_mixed_indentation(&mut self, lint: bool) {
+        self.lint_mixed_indentation = lint;
+    }
+    pub fn set_print_errors(&mut self, print: bool) {
+        self.print_errors = print;
+    }
+    pub fn set_max_errors(&mut self, max: Option<usize>) {
+        self.max_errors = max;
+    }
+    // Line number of the first line of the source, for lexing a part of a file
+    pub(crate) fn set_first_line(&mut self, line: usize) {
+        self.line = line;
+    }
+    // Keeps `//` comments as `TokenType::Comment` tokens, for formatters and doc tools
+    pub fn set_preserve_comments(&mut self, preserve: bool) {
+        self.preserve_comments = preserve;
+    }
     #[allow(dead_code)]
     pub fn run(&mut self) -> Result<(), LexcialError> {
+        // A `#!` first line makes scripts executable, it is skipped like a comment
+        if self.buffer_ed == 0 && self.line == 1 && self.source.starts_with("#!") {
+            self.state = State::Comment;
+        }
         while let Some(c) = self.next_char() {
             let peeked_char = self.peek_char().unwrap_or('\0');
+            if let Err(error) = self.check_line_length() {
+                return self.report_error(error);
+            }
 
             // println!("---------------------------------");
             // println!("Current Char: {}", c);
@@ -78,90 +142,25 @@ impl<'a> Lexer<'a> {
             // println!("Current Buffer: {}", self.source[self.buffer_st..self.buffer_ed].to_string());
             // println!("Current Buffer start: {}", self.buffer_st);
             // println!("Current Buffer end: {}", self.buffer_ed);
-            // self.handle_double_state();
-            if self.state == State::DoubleState {
-                self.buffer_st = self.buffer_ed;
-                self.state = State::EmptyState;
-                continue;
-            }
-
-            // Handling Comment State
-            if self.state == State::Comment {
-                if c == '\n' {
-                    self.state = State::EmptyState;
-                    self.buffer_st = self.buffer_ed;
-                }
-                continue;
-            }
-
-            // Handling Whitespace
-            if c.is_whitespace() && self.state != State::QuotedString {
-                self.buffer_st = self.buffer_ed;
-                self.state = State::EmptyState;
+            if self.skip_char(c, peeked_char) {
                 continue;
             }
 
             // Check if the buffer is empty and the current character when is empty
-            if self.buffer_ed == self.buffer_st + c.len_utf8() {
-                // check if is a double symbol
-                if peeked_char != '\0' {
-                    let peeked_index = self.buffer_ed + peeked_char.len_utf8();
-                    let double_symbol_str = &self.source[self.buffer_st..peeked_index];
-                    let double_symbol =
-                        symbol::double_symbol_to_token(double_symbol_str, self.line, self.column);
-                    if let Ok(double_symbol) = double_symbol {
-                        if double_symbol == TokenType::Symbol(Symbol::Comment) {
-                            self.state = State::Comment;
-                            continue;
-                        }
-                        self.insert_token(double_symbol);
-                        self.state = State::DoubleState;
-                        continue;
-                    }
-                }
-
-                // Check for single symbols
-                let symbol = symbol::symbol_to_token(c, self.line, self.column);
-                if let Ok(symbol) = symbol {
-                    self.insert_token(symbol);
-                    self.buffer_st = self.buffer_ed;
-                    continue;
-                }
-
-                // Handling operators
-                let operator = symbol::operator_to_token(c, self.line, self.column);
-                if let Ok(operator) = operator {
-                    self.insert_token(operator);
-                    self.buffer_st = self.buffer_ed;
-                    continue;
-                }
-
-                self.state = State::DefaultState;
+            if self.buffer_ed == self.buffer_st + c.len_utf8() && self.read_symbol(c, peeked_char) {
+                continue;
             }
 
             // Handling numbers
-            let first_char: char = self.source[self.buffer_st..self.buffer_ed]
-                .chars()
-                .next()
-                .unwrap();
+            let first_char = self.first_char;
             if self.state == State::DefaultState && (first_char == '-' || first_char.is_numeric()) {
                 self.state = State::Number;
             }
-            if self.state == State::Number && !peeked_char.is_numeric() {
-                let number = value::number_to_token(
-                    &self.source[self.buffer_st..self.buffer_ed],
-                    self.line,
-                    self.column,
-                );
-                match number {
-                    Ok(number) => {
-                        self.insert_token(number);
-                        self.buffer_st = self.buffer_ed;
-                    }
-                    Err(error) => return self.report_error(error),
+            // Letters right after the digits are a type suffix like `255u8`
+            if self.state == State::Number && !peeked_char.is_alphanumeric() {
+                if let Err(error) = self.read_number() {
+                    return self.report_error(error);
                 }
-
-                self.state = State::EmptyState;
                 continue;
             }
 
@@ -169,58 +168,257 @@ impl<'a> Lexer<'a> {
             if self.state == State::DefaultState && identifier::is_quote(first_char) {
                 self.state = State::QuotedString;
                 continue;
-            } else if self.state == State::QuotedString && !identifier::is_quote(c) {
-                continue;
-            } else if self.state == State::QuotedString && identifier::is_quote(c) {
-                let mut string = &self.source[self.buffer_st..self.buffer_ed];
-                string = string.trim_matches('"');
-                // self.insert_token(TokenType::TypeValue(TypeValue::QuotedString(
-                // string.to_string(),
-                // )));
-                self.insert_token(TokenType::TypeValue(TypeValue::QuotedString(Cow::Owned(
-                    string.to_owned(),
-                ))));
-                self.buffer_st = self.buffer_ed;
-                self.state = State::EmptyState;
+            }
+            if self.state == State::QuotedString {
+                if let Err(error) = self.read_string_char(c) {
+                    return self.report_error(error);
+                }
                 continue;
             }
 
-            // check if is a identifier, statement, or symbol
-            if self.state == State::DefaultState && identifier::is_first_identifierable(first_char)
-            {
-                self.state = State::Identifier;
+            if let Err(error) = self.read_word(peeked_char) {
+                return self.report_error(error);
             }
-            if self.state == State::Identifier && !identifier::is_identifierable(peeked_char) {
-                let string = &self.source[self.buffer_st..self.buffer_ed];
-                let statement = identifier::statement_to_token(string, self.line, self.column);
-                if let Ok(statement) = statement {
-                    self.insert_token(statement);
-                    self.buffer_st = self.buffer_ed;
-                    self.state = State::EmptyState;
-                    continue;
-                }
-                let type_name = identifier::type_name_to_token(string, self.line, self.column);
-                if let Ok(type_name) = type_name {
-                    self.insert_token(type_name);
-                    self.reset_state();
-                    continue;
-                }
-                let identifier = TokenType::TypeValue(TypeValue::Identifier(string.to_string()));
-                self.insert_token(identifier);
-                self.reset_state();
-                continue;
+        }
+        match self.unterminated() {
+            Some(error) => self.report_error(error),
+            None => Ok(()),
+        }
+    }
+    // Comments, whitespace and the second character of a double symbol make no token
+    fn skip_char(&mut self, c: char, peeked_char: char) -> bool {
+        match self.state {
+            State::DoubleState => self.reset_state(),
+            State::Comment if c == '\n' => self.reset_state(),
+            State::Comment => {}
+            State::BlockComment => self.read_block_comment(c, peeked_char),
+            State::QuotedString => return false,
+            _ if c.is_whitespace() => self.reset_state(),
+            _ => return false,
+        }
+        true
+    }
+    // Reads a comment or a symbol starting at `c`, or marks the start of a longer token
+    fn read_symbol(&mut self, c: char, peeked_char: char) -> bool {
+        if c == '/' && peeked_char == '*' {
+            self.state = State::BlockComment;
+            self.comment_start = (self.line, self.column);
+            self.read_block_comment(c, peeked_char);
+            return true;
+        }
+        // check if is a double symbol
+        if peeked_char != '\0' {
+            let peeked_index = self.buffer_ed + peeked_char.len_utf8();
+            let double_symbol_str = &self.source[self.buffer_st..peeked_index];
+            let double_symbol =
+                symbol::double_symbol_to_token(double_symbol_str, self.line, self.column);
+            if let Ok(double_symbol) = double_symbol {
+                self.read_double_symbol(double_symbol, peeked_index);
+                return true;
             }
         }
-        if self.state == State::QuotedString {
-            return self.report_error(LexcialError {
+        // Check for single symbols, then for operators
+        let symbol = symbol::symbol_to_token(c, self.line, self.column)
+            .or_else(|_| symbol::operator_to_token(c, self.line, self.column));
+        if let Ok(symbol) = symbol {
+            self.insert_token(symbol);
+            self.buffer_st = self.buffer_ed;
+            return true;
+        }
+        self.state = State::DefaultState;
+        false
+    }
+    // `//` starts a comment, a doc comment is kept as a token like a preserved comment
+    fn read_double_symbol(&mut self, double_symbol: TokenType, end: usize) {
+        if double_symbol != TokenType::Symbol(Symbol::Comment) {
+            self.push_token(double_symbol, end);
+            self.state = State::DoubleState;
+            return;
+        }
+        let doc = doc_comment_kind(&self.source[end..]);
+        if doc.is_some() || self.preserve_comments {
+            self.read_comment(doc);
+        } else {
+            self.state = State::Comment;
+        }
+    }
+    fn read_number(&mut self) -> Result<(), LexcialError> {
+        let number = &self.source[self.buffer_st..self.buffer_ed];
+        let number = value::number_to_token(number, self.line, self.column)?;
+        self.insert_token(number);
+        self.reset_state();
+        Ok(())
+    }
+    fn read_string_char(&mut self, c: char) -> Result<(), LexcialError> {
+        if c == '\\' {
+            // The escaped character can not end the string, `value::string_to_token` decodes it
+            self.next_char();
+            Ok(())
+        } else if identifier::is_quote(c) {
+            self.read_string()
+        } else {
+            Ok(())
+        }
+    }
+    // The closing quote was read, the token is the text between the quotes
+    fn read_string(&mut self) -> Result<(), LexcialError> {
+        // Without the prefix and the opening and closing quote
+        let prefix = if self.byte_string { 2 } else { 1 };
+        let raw = &self.source[self.buffer_st + prefix..self.buffer_ed - 1];
+        let string = if self.byte_string {
+            value::byte_string_to_token(raw, self.line, self.column)
+        } else {
+            value::string_to_token(raw, self.line, self.column)
+        };
+        self.byte_string = false;
+        self.insert_token(string?);
+        self.reset_state();
+        Ok(())
+    }
+    // check if is a identifier, statement, or symbol
+    fn read_word(&mut self, peeked_char: char) -> Result<(), LexcialError> {
+        let first_char = self.first_char;
+        // Labels and attributes are read like an identifier after their sigil
+        if self.state == State::DefaultState
+            && (identifier::is_first_identifierable(first_char) || identifier::is_sigil(first_char))
+        {
+            self.state = State::Identifier;
+        }
+        if self.state == State::Identifier && !identifier::is_identifierable(peeked_char) {
+            return self.read_identifier(peeked_char);
+        }
+        // Nothing starts with this character
+        if self.state == State::DefaultState {
+            return Err(LexcialError {
                 line: self.line,
                 column: self.column,
-                message: LexError::ExpectedQuote(),
+                message: LexError::InvalidSymbol(first_char.to_string()),
             });
         }
         Ok(())
     }
+    // The buffer holds a whole word, a keyword, type name, value or identifier
+    fn read_identifier(&mut self, peeked_char: char) -> Result<(), LexcialError> {
+        let source = self.source;
+        let string = &source[self.buffer_st..self.buffer_ed];
+        self.check_identifier_length(string)?;
+        if self.read_prefix(string, peeked_char) {
+            return Ok(());
+        }
+        if let Some(name) = string.strip_prefix(identifier::is_sigil) {
+            let token = identifier::sigil_to_token(self.first_char, name, self.line, self.column)?;
+            self.insert_token(token);
+            self.reset_state();
+            return Ok(());
+        }
+        let token = identifier::statement_to_token(string, self.line, self.column)
+            .or_else(|_| identifier::type_name_to_token(string, self.line, self.column))
+            .ok()
+            .or_else(|| identifier::value_keyword_to_token(string));
+        let token = match token {
+            Some(token) => token,
+            None => {
+                identifier::check_type_name_spelling(string, self.line, self.column)?;
+                TokenType::TypeValue(TypeValue::Identifier(string.to_string()))
+            }
+        };
+        self.insert_token(token);
+        self.reset_state();
+        Ok(())
+    }
+    // `b"` starts a byte string and `r#` makes the name after it an identifier, also when it
+    // is a keyword. The character after the prefix is read here so it does not end the word.
+    fn read_prefix(&mut self, string: &str, peeked_char: char) -> bool {
+        if string == "b" && identifier::is_quote(peeked_char) {
+            self.next_char();
+            self.state = State::QuotedString;
+            self.byte_string = true;
+            return true;
+        }
+        if string == "r"
+            && peeked_char == '#'
+            && self.source[self.buffer_ed + 1..].starts_with(identifier::is_first_identifierable)
+        {
+            self.next_char();
+            return true;
+        }
+        let Some(name) = string.strip_prefix("r#") else {
+            return false;
+        };
+        let identifier = TokenType::TypeValue(TypeValue::Identifier(name.to_string()));
+        self.insert_token(identifier);
+        self.reset_state();
+        true
+    }
+    // An unclosed string or block comment at the end of the source
+    fn unterminated(&mut self) -> Option<LexcialError> {
+        let (line, column, message) = match self.state {
+            State::QuotedString => (self.line, self.column, LexError::ExpectedQuote()),
+            State::BlockComment => {
+                self.comment_depth = 0;
+                let (line, column) = self.comment_start;
+                (line, column, LexError::UnterminatedComment())
+            }
+            _ => return None,
+        };
+        Some(LexcialError {
+            line,
+            column,
+            message,
+        })
+    }
 
+    fn check_line_length(&self) -> Result<(), LexcialError> {
+        match self.max_line_length {
+            Some(max) if self.column > max => Err(LexcialError {
+                line: self.line,
+                column: self.column,
+                message: LexError::LineTooLong(max),
+            }),
+            _ => Ok(()),
+        }
+    }
+    fn check_identifier_length(&self, identifier: &str) -> Result<(), LexcialError> {
+        match self.max_identifier_length {
+            Some(max) if identifier.chars().count() > max => Err(LexcialError {
+                line: self.line,
+                column: self.column,
+                message: LexError::IdentifierTooLong(identifier.to_string(), max),
+            }),
+            _ => Ok(()),
+        }
+    }
+    // Reads the rest of the line after `//` into a comment token, leaving the newline
+    // Doc comments also skip their third character, the `/` of `///` or the `!` of `//!`
+    fn read_comment(&mut self, doc: Option<fn(String) -> TokenType>) {
+        let metadata = TokenMetadata::new(self.line, self.column);
+        self.next_char();
+        if doc.is_some() {
+            self.next_char();
+        }
+        let start = self.buffer_ed;
+        while self.peek_char().is_ok_and(|c| c != '\n') {
+            self.next_char();
+        }
+        let text = self.source[start..self.buffer_ed].trim_end_matches('\r');
+        let metadata = metadata.with_span(self.buffer_st, start + text.len());
+        let token_type = doc.unwrap_or(TokenType::Comment)(text.to_string());
+        self.tokens.push(Token::new(token_type, metadata));
+        self.buffer_st = self.buffer_ed;
+        self.state = State::EmptyState;
+    }
+    // `/*` opens one more level and `*/` closes one, the comment ends at depth zero
+    fn read_block_comment(&mut self, c: char, peeked_char: char) {
+        match (c, peeked_char) {
+            ('/', '*') => self.comment_depth += 1,
+            ('*', '/') => self.comment_depth -= 1,
+            _ => return,
+        }
+        self.next_char();
+        if self.comment_depth == 0 {
+            self.reset_state();
+        }
+    }
     #[inline]
     fn handle_double_state(&mut self) {
         self.buffer_st = self.buffer_ed;
@@ -233,36 +431,65 @@ impl<'a> Lexer<'a> {
     }
     #[inline]
     fn update_position(&mut self, ch: char) {
-        self.column += match ch {
+        match ch {
             '\n' => {
                 self.line += 1;
-                0
-            } // Reset column to 0 for new line
-            '\t' => 4, // Assume tab is 4 spaces
-            _ => 1,
-        };
+                self.column = 0;
+            }
+            '\r' => {} // Part of a `\r\n` line end, the `\n` counts the line
+            '\t' => self.column += 4, // Assume tab is 4 spaces
+            _ => self.column += 1,
+        }
+        if self.lint_mixed_indentation {
+            self.check_indentation(ch);
+        }
+        if self.buffer_st == self.buffer_ed {
+            self.first_char = ch;
+        }
         self.buffer_ed += ch.len_utf8();
     }
+    // Blank lines are not checked, their whitespace is never seen by the parser
+    fn check_indentation(&mut self, ch: char) {
+        self.indentation = match (ch, self.indentation) {
+            ('\n', _) => Some((false, false)),
+            ('\t', Some((_, spaces))) => Some((true, spaces)),
+            (' ', Some((tabs, _))) => Some((tabs, true)),
+            ('\r', indentation) | (_, indentation @ None) => indentation,
+            (_, Some((tabs, spaces))) => {
+                if tabs && spaces {
+                    self.warnings.push(LexWarning::MixedIndentation(self.line));
+                }
+                None
+            }
+        };
+    }
     fn peek_char(&mut self) -> Result<char, ()> {
         self.code.peek().copied().ok_or(())
     }
     fn insert_token(&mut self, token: TokenType) {
+        self.push_token(token, self.buffer_ed);
+    }
+    // The token starts at the buffer, `end` is past the buffer for lookahead symbols like `==`
+    fn push_token(&mut self, token: TokenType, end: usize) {
         self.tokens.push(Token::new(
             token,
-            TokenMetadata::new(self.line, self.column),
+            TokenMetadata::new(self.line, self.column).with_span(self.buffer_st, end),
         ));
     }
 
     fn report_error(&self, error: LexcialError) -> Result<(), LexcialError> {
         let context_window = 10; // Number of characters to show around the error
 
-        let start = self.buffer_st.saturating_sub(context_window);
-        let end = std::cmp::min(self.buffer_ed + context_window, self.source.len());
+        let start = self.char_boundary(self.buffer_st.saturating_sub(context_window));
+        let end = self.char_boundary(std::cmp::min(
+            self.buffer_ed + context_window,
+            self.source.len(),
+        ));
 
         let context_snippet = &self.source[start..end];
 
         // Count the number of characters (not bytes) from the start of the snippet to the error position
-        let error_pos_in_context = self.source[start..self.column].chars().count();
+        let error_pos_in_context = self.source[start..self.buffer_st].chars().count() + 1;
         let error_location_marker = " ".repeat(error_pos_in_context.saturating_sub(1)) + "^";
 
         // Context and Error Information
@@ -275,48 +502,79 @@ impl<'a> Lexer<'a> {
             self.file_path.display(),
             error // Assuming .to_string() returns the formatted error message
         ); // Suggestion for resolution (customize based on your error types)
-        let suggestion = match error.message {
-            LexError::InvalidCharacter(ref ch) => {
-                format!(
-                    "Suggestion: Unexpected character '{}'. Try removing or replacing it.",
-                    ch
-                )
-            }
-            LexError::InvalidTypeName(ref ch) => {
-                format!("Suggestion: Unexpected type'{}'.", ch)
-            }
-            LexError::InvalidNumber(ref n) => {
-                format!("Suggestion: Invalid number '{}'.", n)
-            }
-            LexError::InvalidIdentifier(ref i) => {
-                format!("Suggestion: Invalid identifier '{}'.", i)
-            }
-            LexError::InvalidOperator(ref o) => {
-                format!("Suggestion: Invalid operator '{}'.", o)
-            }
-            LexError::InvalidSymbol(ref s) => {
-                format!("Suggestion: Invalid symbol '{}'.", s)
-            }
-            LexError::InvalidStatement(ref s) => {
-                format!("Suggestion: Invalid statement '{}'.", s)
-            }
-            LexError::InvalidDoubleSymbol(ref s) => {
-                format!("Suggestion: Invalid double symbol '{}'.", s)
-            }
-            LexError::ExpectedQuote() => {
-                "Suggestion: Check the syntax around the error line, and add a double quote."
-                    .to_string()
-            }
-        };
-
-        eprintln!("{}\n{}", errortxt, suggestion);
+        if self.print_errors {
+            eprintln!("{}\nSuggestion: {}", errortxt, error.suggestion());
+        }
         Err(error)
         // std::process::exit(1);
     }
 
+    // Moves a byte index back until it no longer splits a multi-byte character
+    fn char_boundary(&self, mut index: usize) -> usize {
+        while !self.source.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+
     pub fn get_tokens(&self) -> &Vec<Token> {
         &self.tokens
     }
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+    pub fn get_warnings(&self) -> &Vec<LexWarning> {
+        &self.warnings
+    }
+    pub fn token_count(&self) -> usize {
+        self.tokens.len()
+    }
+    pub fn into_tokens(self) -> Vec<Token> {
+        self.tokens
+    }
+    // Lexes to the end, skipping past each error, and returns the tokens with every error found
+    pub fn finish(mut self) -> (Vec<Token>, Vec<LexcialError>) {
+        let errors = self.run_all().err().unwrap_or_default();
+        (self.tokens, errors)
+    }
+    // Like `finish` but keeps the tokens in the lexer. Errors past `max_errors` are
+    // returned without being written, a count of them is written in their place.
+    pub fn run_all(&mut self) -> Result<(), Vec<LexcialError>> {
+        let print_errors = self.print_errors;
+        let mut errors = Vec::new();
+        loop {
+            let below_max = self.max_errors.is_none_or(|max| errors.len() < max);
+            self.print_errors = print_errors && below_max;
+            match self.run() {
+                Ok(()) => break,
+                Err(error) => {
+                    self.recover(&error);
+                    errors.push(error);
+                }
+            }
+        }
+        self.print_errors = print_errors;
+        match self.max_errors {
+            Some(max) if print_errors && errors.len() > max => {
+                eprintln!("... and {} more errors", errors.len() - max);
+            }
+            _ => {}
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    fn recover(&mut self, error: &LexcialError) {
+        if matches!(error.message, LexError::LineTooLong(_)) {
+            while self.peek_char().is_ok_and(|c| c != '\n') {
+                self.next_char();
+            }
+        }
+        self.byte_string = false;
+        self.reset_state();
+    }
     #[inline]
     fn reset_state(&mut self) {
         self.state = State::EmptyState;
@@ -324,9 +582,23 @@ impl<'a> Lexer<'a> {
     }
 }
 
+// `///` documents the next item and `//!` the enclosing one, `////` is a plain comment
+fn doc_comment_kind(rest: &str) -> Option<fn(String) -> TokenType> {
+    if rest.starts_with("//") {
+        None
+    } else if rest.starts_with('/') {
+        Some(TokenType::DocComment)
+    } else if rest.starts_with('!') {
+        Some(TokenType::InnerDocComment)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::borrow::Cow;
     use crate::neo_tokens::{
         Assign, Statement, Symbol, TypeName, TypeValue, Operator
     };
@@ -451,62 +723,68 @@ mod test {
         let ans = vec![
             Token::new(
                 TokenType::Statement(Statement::Let),
-                TokenMetadata::new(1, 3),
+                TokenMetadata::new(1, 3).with_span(0, 3),
+            ),
+            Token::new(TokenType::Symbol(Symbol::Colon), TokenMetadata::new(1, 4).with_span(3, 4)),
+            Token::new(
+                TokenType::TypeName(TypeName::I32),
+                TokenMetadata::new(1, 7).with_span(4, 7),
             ),
-            Token::new(TokenType::Symbol(Symbol::Colon), TokenMetadata::new(1, 4)),
-            Token::new(TokenType::TypeName(TypeName::I32), TokenMetadata::new(1, 7)),
             Token::new(
                 TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
-                TokenMetadata::new(1, 9),
+                TokenMetadata::new(1, 9).with_span(8, 9),
+            ),
+            Token::new(
+                TokenType::Assign(Assign::Assign),
+                TokenMetadata::new(1, 11).with_span(10, 11),
             ),
-            Token::new(TokenType::Assign(Assign::Assign), TokenMetadata::new(1, 11)),
             Token::new(
                 TokenType::Symbol(Symbol::OpenParen),
-                TokenMetadata::new(1, 13),
+                TokenMetadata::new(1, 13).with_span(12, 13),
             ),
             Token::new(
                 TokenType::Symbol(Symbol::OpenParen),
-                TokenMetadata::new(1, 14),
+                TokenMetadata::new(1, 14).with_span(13, 14),
             ),
             Token::new(
                 TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("5"))),
-                TokenMetadata::new(1, 15),
+                TokenMetadata::new(1, 15).with_span(14, 15),
             ),
             Token::new(
                 TokenType::Operator(Operator::Add),
-                TokenMetadata::new(1, 17),
+                TokenMetadata::new(1, 17).with_span(16, 17),
             ),
             Token::new(
                 TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
-                TokenMetadata::new(1, 19),
+                TokenMetadata::new(1, 19).with_span(18, 19),
             ),
             Token::new(
                 TokenType::Symbol(Symbol::CloseParen),
-                TokenMetadata::new(1, 20),
+                TokenMetadata::new(1, 20).with_span(19, 20),
             ),
             Token::new(
                 TokenType::Operator(Operator::Divide),
-                TokenMetadata::new(1, 22),
+                TokenMetadata::new(1, 22).with_span(21, 22),
             ),
             Token::new(
                 TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("2"))),
-                TokenMetadata::new(1, 23),
+                TokenMetadata::new(1, 23).with_span(22, 23),
             ),
             Token::new(
                 TokenType::Symbol(Symbol::CloseParen),
-                TokenMetadata::new(1, 24),
+                TokenMetadata::new(1, 24).with_span(23, 24),
             ),
             Token::new(
                 TokenType::Operator(Operator::Add),
-                TokenMetadata::new(1, 25),
+                TokenMetadata::new(1, 25).with_span(24, 25),
             ),
             Token::new(
                 TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("2"))),
-                TokenMetadata::new(1, 26),
+                TokenMetadata::new(1, 26).with_span(25, 26),
             ),
             Token::new(
                 TokenType::Symbol(Symbol::Semicolon),
-                TokenMetadata::new(1, 27),
+                TokenMetadata::new(1, 27).with_span(26, 27),
             ),
         ];
         let mut lexer = Lexer::new(PathBuf::from("test"), code);
@@ -555,4 +833,519 @@ mod test {
         println!("{:?}", lexer.tokens);
         // assert_eq!(lexer.tokens, ans);
     }
+    #[test]
+    fn lexing_trailing_comment() {
+        let code = "let:i32 a = 5; // set a\nlet:i32 b = a;// set b\nb = 6 // no semicolon";
+        let ans = vec![
+            TokenType::Statement(Statement::Let),
+            TokenType::Symbol(Symbol::Colon),
+            TokenType::TypeName(TypeName::I32),
+            TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
+            TokenType::Assign(Assign::Assign),
+            TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("5"))),
+            TokenType::Symbol(Symbol::Semicolon),
+            TokenType::Statement(Statement::Let),
+            TokenType::Symbol(Symbol::Colon),
+            TokenType::TypeName(TypeName::I32),
+            TokenType::TypeValue(TypeValue::Identifier("b".to_string())),
+            TokenType::Assign(Assign::Assign),
+            TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
+            TokenType::Symbol(Symbol::Semicolon),
+            TokenType::TypeValue(TypeValue::Identifier("b".to_string())),
+            TokenType::Assign(Assign::Assign),
+            TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("6"))),
+        ];
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(token_types, ans);
+        let lines: Vec<usize> = lexer.tokens.iter().map(|token| token.metadata.line).collect();
+        assert_eq!(lines, [vec![1; 7], vec![2; 7], vec![3; 3]].concat());
+    }
+    #[test]
+    fn lexing_preserved_comments() {
+        let code = "// hi\nreturn; // bye";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_preserve_comments(true);
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Comment(" hi".to_string()),
+                TokenType::Statement(Statement::Return),
+                TokenType::Symbol(Symbol::Semicolon),
+                TokenType::Comment(" bye".to_string()),
+            ]
+        );
+        assert_eq!(lexer.tokens[0].metadata, TokenMetadata::new(1, 1).with_span(0, 5));
+        assert_eq!(lexer.tokens[3].metadata.line, 2);
+
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.run().unwrap();
+        assert_eq!(lexer.tokens.len(), 2);
+    }
+    #[test]
+    fn lexing_doc_comments() {
+        let code = "//! Module\n/// Adds\n// plain\n//// banner\nreturn;";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::InnerDocComment(" Module".to_string()),
+                TokenType::DocComment(" Adds".to_string()),
+                TokenType::Statement(Statement::Return),
+                TokenType::Symbol(Symbol::Semicolon),
+            ]
+        );
+        assert_eq!(lexer.tokens[1].metadata, TokenMetadata::new(2, 1).with_span(11, 19));
+    }
+    #[test]
+    fn lexing_integer_suffixes() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "255u8 5i64 -3i8");
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::TypeValue(TypeValue::TypedNumber(Cow::Borrowed("255"), TypeName::U8)),
+                TokenType::TypeValue(TypeValue::TypedNumber(Cow::Borrowed("5"), TypeName::I64)),
+                TokenType::Operator(Operator::Subtract),
+                TokenType::TypeValue(TypeValue::TypedNumber(Cow::Borrowed("3"), TypeName::I8)),
+            ]
+        );
+    }
+    #[test]
+    fn lexing_integer_suffix_out_of_range() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "256u8");
+        let error = lexer.run().unwrap_err();
+        assert_eq!(
+            error.message,
+            LexError::NumberOutOfRange("256u8".to_string(), TypeName::U8)
+        );
+        let mut lexer = Lexer::new(PathBuf::from("test"), "5x8");
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::InvalidNumber("5x8".to_string()));
+    }
+    #[test]
+    fn lexing_none() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "none nonempty");
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::TypeValue(TypeValue::NoneVoid),
+                TokenType::TypeValue(TypeValue::Identifier("nonempty".to_string())),
+            ]
+        );
+    }
+    #[test]
+    fn lexing_discard() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "_ _a a_");
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::TypeValue(TypeValue::Discard),
+                TokenType::TypeValue(TypeValue::Identifier("_a".to_string())),
+                TokenType::TypeValue(TypeValue::Identifier("a_".to_string())),
+            ]
+        );
+    }
+    #[test]
+    fn lexing_identifier_too_long() {
+        let code = "let short = 1;\nlet much_too_long = 2;";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_max_identifier_length(Some(8));
+        let error = lexer.run().unwrap_err();
+        assert_eq!(
+            error.message,
+            LexError::IdentifierTooLong("much_too_long".to_string(), 8)
+        );
+        assert_eq!(error.line, 2);
+
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        assert!(lexer.run().is_ok());
+    }
+    #[test]
+    fn lexing_line_too_long() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "let a = 1;\nlet abc = 12345;");
+        lexer.set_max_line_length(Some(12));
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::LineTooLong(12));
+        assert_eq!((error.line, error.column), (2, 13));
+    }
+    #[test]
+    fn lexing_power_and_comment() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "2 ** 10 * a // b ** c\n/ d");
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("2"))),
+                TokenType::Operator(Operator::Power),
+                TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("10"))),
+                TokenType::Operator(Operator::Multiply),
+                TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
+                TokenType::Operator(Operator::Divide),
+                TokenType::TypeValue(TypeValue::Identifier("d".to_string())),
+            ]
+        );
+    }
+    #[test]
+    fn lexing_declare() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "a := 5; let:i32 b = 1;");
+        lexer.run().unwrap();
+        assert_eq!(lexer.tokens[1].token_type, TokenType::Assign(Assign::Declare));
+        assert_eq!(lexer.tokens[5].token_type, TokenType::Symbol(Symbol::Colon));
+    }
+    #[test]
+    fn lexing_unterminated_multibyte_string() {
+        let code = "\"ééé";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::ExpectedQuote());
+        assert_eq!(error.line, 1);
+    }
+    #[test]
+    fn lexing_finish_recovers() {
+        let lexer = Lexer::new(PathBuf::from("test"), "let a = 1 $ 2;");
+        let (tokens, errors) = lexer.finish();
+        let token_types: Vec<TokenType> =
+            tokens.into_iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Statement(Statement::Let),
+                TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
+                TokenType::Assign(Assign::Assign),
+                TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("1"))),
+                TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("2"))),
+                TokenType::Symbol(Symbol::Semicolon),
+            ]
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, LexError::InvalidSymbol("$".to_string()));
+        assert_eq!((errors[0].line, errors[0].column), (1, 11));
+    }
+    #[test]
+    fn lexing_nested_block_comments() {
+        let code = "let /* outer /* inner */ still\n comment */ a = 1;";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Statement(Statement::Let),
+                TokenType::TypeValue(TypeValue::Identifier("a".to_string())),
+                TokenType::Assign(Assign::Assign),
+                TokenType::TypeValue(TypeValue::Number(Cow::Borrowed("1"))),
+                TokenType::Symbol(Symbol::Semicolon),
+            ]
+        );
+        assert_eq!(lexer.tokens[1].metadata.line, 2);
+
+        let code = "let a = 1;\n/* outer /* inner */\nlet b = 2;";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_print_errors(false);
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::UnterminatedComment());
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(lexer.tokens.len(), 5);
+    }
+    #[test]
+    fn lexing_control_character_suggestion() {
+        let cases = [
+            ("let a = \u{7};", "Invalid symbol '\\u{7}'."),
+            ("let a = $;", "Invalid symbol '$'."),
+            (
+                "let a = b\"\t\u{1b}é\";",
+                "Unexpected character 'é'. Try removing or replacing it.",
+            ),
+        ];
+        for (code, suggestion) in cases {
+            let mut lexer = Lexer::new(PathBuf::from("test"), code);
+            lexer.set_print_errors(false);
+            let error = lexer.run().unwrap_err();
+            assert_eq!(error.suggestion(), suggestion, "{:?}", code);
+        }
+        let error = LexcialError {
+            line: 1,
+            column: 1,
+            message: LexError::InvalidCharacter('\t'),
+        };
+        assert!(error.suggestion().starts_with("Unexpected character '\\t'."));
+    }
+    #[test]
+    fn lexing_finish_long_lines() {
+        let code = "let a = 1234567;\nlet b = 1;\nlet c = 1234567;";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_max_line_length(Some(12));
+        let (tokens, errors) = lexer.finish();
+        let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(tokens.len(), 11);
+    }
+    #[test]
+    fn lexing_string_escapes() {
+        let cases = [
+            (r#""caf\u{E9}""#, "café"),
+            (r#""\u{1F600}!""#, "\u{1F600}!"),
+            (r#""a\tb\n\"c\" \\""#, "a\tb\n\"c\" \\"),
+        ];
+        for (code, expected) in cases {
+            let mut lexer = Lexer::new(PathBuf::from("test"), code);
+            lexer.run().unwrap();
+            assert_eq!(
+                lexer.tokens[0].token_type,
+                TokenType::TypeValue(TypeValue::QuotedString(Cow::Borrowed(expected)))
+            );
+        }
+    }
+    #[test]
+    fn lexing_invalid_escapes() {
+        let cases = [
+            (r#""\u{D800}""#, LexError::InvalidCodePoint("D800".to_string())),
+            (r#""\u{110000}""#, LexError::InvalidCodePoint("110000".to_string())),
+            (r#""\u{12""#, LexError::InvalidEscape("\\u{12".to_string())),
+            (r#""\u41""#, LexError::InvalidEscape("\\u".to_string())),
+            (r#""\q""#, LexError::InvalidEscape("\\q".to_string())),
+        ];
+        for (code, expected) in cases {
+            let mut lexer = Lexer::new(PathBuf::from("test"), code);
+            assert_eq!(lexer.run().unwrap_err().message, expected);
+        }
+    }
+    #[test]
+    fn lexing_raw_identifiers() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "let r#fn = r#type + r#x;\nfn");
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        let identifier = |name: &str| TokenType::TypeValue(TypeValue::Identifier(name.to_string()));
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Statement(Statement::Let),
+                identifier("fn"),
+                TokenType::Assign(Assign::Assign),
+                identifier("type"),
+                TokenType::Operator(Operator::Add),
+                identifier("x"),
+                TokenType::Symbol(Symbol::Semicolon),
+                TokenType::Statement(Statement::Function),
+            ]
+        );
+        let mut lexer = Lexer::new(PathBuf::from("test"), "r# fn");
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::InvalidSymbol("#".to_string()));
+    }
+    #[test]
+    fn lexing_statements_on_one_line() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "let:i32 a=1; let:i32 b=2;");
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        let statement = |name: &str, value: &str| {
+            vec![
+                TokenType::Statement(Statement::Let),
+                TokenType::Symbol(Symbol::Colon),
+                TokenType::TypeName(TypeName::I32),
+                TokenType::TypeValue(TypeValue::Identifier(name.to_string())),
+                TokenType::Assign(Assign::Assign),
+                TokenType::TypeValue(TypeValue::Number(value.to_string().into())),
+                TokenType::Symbol(Symbol::Semicolon),
+            ]
+        };
+        assert_eq!(token_types, [statement("a", "1"), statement("b", "2")].concat());
+        assert!(lexer.tokens.iter().all(|token| token.metadata.line == 1));
+    }
+    #[test]
+    fn lexing_byte_strings() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), r#"b"A\x00\xff\"" b"" b "x""#);
+        lexer.run().unwrap();
+        let token_types: Vec<TokenType> = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::TypeValue(TypeValue::Bytes(vec![b'A', 0, 0xff, b'"'])),
+                TokenType::TypeValue(TypeValue::Bytes(Vec::new())),
+                TokenType::TypeValue(TypeValue::Identifier("b".to_string())),
+                TokenType::TypeValue(TypeValue::QuotedString(Cow::Borrowed("x"))),
+            ]
+        );
+        assert_eq!(token_types[0].to_string(), r#"b"A\x00\xFF\"""#);
+        let cases = [
+            (r#"b"\x4""#, LexError::InvalidByteEscape("\\x4".to_string())),
+            (r#"b"\xZZ""#, LexError::InvalidByteEscape("\\xZZ".to_string())),
+            (r#"b"\u{41}""#, LexError::InvalidByteEscape("\\u".to_string())),
+            (r#"b"é""#, LexError::InvalidCharacter('é')),
+        ];
+        for (code, expected) in cases {
+            let mut lexer = Lexer::new(PathBuf::from("test"), code);
+            assert_eq!(lexer.run().unwrap_err().message, expected);
+        }
+        let mut lexer = Lexer::new(PathBuf::from("test"), r#"b"\u{41}""#);
+        let suggestion = lexer.run().unwrap_err().suggestion();
+        assert!(suggestion.contains("\\xNN") && !suggestion.contains("\\u{"));
+    }
+    #[test]
+    fn lexing_crlf_line_ends() {
+        let code = "let a = 1; // one\r\nlet b = 2;\r\n";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_preserve_comments(true);
+        lexer.run().unwrap();
+        assert_eq!(lexer.line, 3);
+        assert_eq!(
+            lexer.tokens[5].token_type,
+            TokenType::Comment(" one".to_string())
+        );
+        let last = lexer.tokens.last().unwrap();
+        assert_eq!((last.metadata.line, last.metadata.column), (2, 10));
+        let mut unix = Lexer::new(PathBuf::from("test"), "let a = 1; // one\nlet b = 2;\n");
+        unix.set_preserve_comments(true);
+        unix.run().unwrap();
+        // Byte spans differ by the `\r`s, everything else is the same
+        let positions = |lexer: &Lexer| -> Vec<(TokenType, usize, usize)> {
+            lexer
+                .tokens
+                .iter()
+                .map(|token| (token.token_type.clone(), token.metadata.line, token.metadata.column))
+                .collect()
+        };
+        assert_eq!(positions(&lexer), positions(&unix));
+    }
+    #[test]
+    fn lexing_void_spelling() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "fn main() -> Void {}");
+        lexer.run().unwrap();
+        assert_eq!(lexer.tokens[5].token_type, TokenType::TypeName(TypeName::Void));
+        let mut lexer = Lexer::new(PathBuf::from("test"), "fn main() -> void {}");
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::InvalidTypeName("void".to_string()));
+        assert_eq!((error.line, error.column), (1, 17));
+    }
+    #[test]
+    fn lexing_loop_labels() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "'outer: while (a) { break 'outer; }");
+        lexer.run().unwrap();
+        let types: Vec<TokenType> = lexer.tokens.iter().map(|t| t.token_type.clone()).collect();
+        assert_eq!(types[0], TokenType::Label("outer".to_string()));
+        assert_eq!(types[1], TokenType::Symbol(Symbol::Colon));
+        assert_eq!(types[2], TokenType::Statement(Statement::While));
+        assert_eq!(types[7], TokenType::Statement(Statement::Break));
+        assert_eq!(types[8], TokenType::Label("outer".to_string()));
+        let span = &lexer.tokens[8].metadata;
+        assert_eq!((span.start, span.end), (26, 32));
+        let mut lexer = Lexer::new(PathBuf::from("test"), "break ';");
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::InvalidSymbol("'".to_string()));
+    }
+    #[test]
+    fn lexing_attributes() {
+        let mut lexer = Lexer::new(PathBuf::from("test"), "@inline\nfn f() {}");
+        lexer.run().unwrap();
+        assert_eq!(lexer.tokens[0].token_type, TokenType::Attribute("inline".to_string()));
+        assert_eq!((lexer.tokens[0].metadata.start, lexer.tokens[0].metadata.end), (0, 7));
+        assert_eq!(lexer.tokens[1].token_type, TokenType::Statement(Statement::Function));
+        let mut lexer = Lexer::new(PathBuf::from("test"), "@ fn f() {}");
+        let error = lexer.run().unwrap_err();
+        assert_eq!(error.message, LexError::InvalidSymbol("@".to_string()));
+    }
+    #[test]
+    fn lexing_shebang() {
+        let code = "#!/usr/bin/env nukleus\nfn main() {}";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.run().unwrap();
+        assert_eq!(lexer.tokens[0].token_type, TokenType::Statement(Statement::Function));
+        assert_eq!(lexer.tokens[0].metadata.line, 2);
+        assert_eq!(lexer.tokens.len(), 6);
+        let mut lexer = Lexer::new(PathBuf::from("test"), "fn main() {}\n#!/bin/sh");
+        let error = lexer.run().unwrap_err();
+        assert_eq!((error.message, error.line), (LexError::InvalidSymbol("#".to_string()), 2));
+    }
+    #[test]
+    fn lexing_mixed_indentation() {
+        let code = "fn main() {\n\t let a = 1;\n\tlet b = 2;\n    \t\n  \treturn;\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.run().unwrap();
+        assert!(lexer.get_warnings().is_empty());
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_lint_mixed_indentation(true);
+        lexer.run().unwrap();
+        assert_eq!(
+            lexer.get_warnings(),
+            &vec![LexWarning::MixedIndentation(2), LexWarning::MixedIndentation(5)]
+        );
+        assert_eq!(
+            lexer.get_warnings()[0].to_string(),
+            "line 2 mixes tabs and spaces in its indentation"
+        );
+    }
+    #[test]
+    fn lexing_byte_spans() {
+        let code = "fn main() -> i32 {\n\tlet:i32 größe = 12; // note\n\tprintln(\"é\");\n}";
+        let mut lexer = Lexer::new(PathBuf::from("test"), code);
+        lexer.set_preserve_comments(true);
+        lexer.run().unwrap();
+        assert_eq!(lexer.token_count(), lexer.get_tokens().len());
+        let text = |index: usize| {
+            let metadata = lexer.get_tokens()[index].metadata;
+            &code[metadata.start..metadata.end]
+        };
+        let expected = [
+            "fn", "main", "(", ")", "->", "i32", "{", "let", ":", "i32", "größe", "=", "12", ";",
+            "// note", "println", "(", "\"é\"", ")", ";", "}",
+        ];
+        let texts: Vec<&str> = (0..lexer.token_count()).map(text).collect();
+        assert_eq!(texts, expected);
+    }
 }
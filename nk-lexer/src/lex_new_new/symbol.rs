@@ -54,11 +54,14 @@ pub fn double_symbol_to_token(
         "|=" => Ok(TokenType::Assign(Assign::BitOrAssign)),
         "^=" => Ok(TokenType::Assign(Assign::BitXorAssign)),
         "->" => Ok(TokenType::Symbol(Symbol::Arrow)),
+        "=>" => Ok(TokenType::Symbol(Symbol::FatArrow)),
         "::" => Ok(TokenType::Symbol(Symbol::DoubleColon)),
+        ":=" => Ok(TokenType::Assign(Assign::Declare)),
         "&&" => Ok(TokenType::Logical(Logical::And)),
         "||" => Ok(TokenType::Logical(Logical::Or)),
         "<<" => Ok(TokenType::Operator(Operator::ShiftLeft)),
         ">>" => Ok(TokenType::Operator(Operator::ShiftRight)),
+        "**" => Ok(TokenType::Operator(Operator::Power)),
         "//" => Ok(TokenType::Symbol(Symbol::Comment)),
         _ => Err(LexcialError {
             line,
@@ -1,9 +1,10 @@
 use std::borrow::Cow;
+use std::str::Chars;
 
 use crate::lex_new_new::errors::LexError;
 use crate::lex_new_new::errors::LexcialError;
 // use crate::tokens_new::{TokenType, TypeValue};
-use crate::neo_tokens::{TokenType, TypeValue};
+use crate::neo_tokens::{TokenType, TypeName, TypeValue};
 
 #[allow(dead_code)]
 pub fn number_to_token(
@@ -11,6 +12,10 @@ pub fn number_to_token(
     line: usize,
     column: usize,
 ) -> Result<TokenType, LexcialError> {
+    if let Some(suffix_start) = number.find(|c: char| c.is_alphabetic()) {
+        let (digits, suffix) = number.split_at(suffix_start);
+        return typed_number_to_token(digits, suffix, line, column);
+    }
     //check if the number is parseable while not changing the type of number to i32
     let trimed_number = number;
     let test_parse = trimed_number.trim_matches('-').parse::<u64>();
@@ -26,3 +31,145 @@ pub fn number_to_token(
         }),
     }
 }
+
+// A literal with a type suffix like `255u8`, the value has to fit the suffix type
+fn typed_number_to_token(
+    digits: &str,
+    suffix: &str,
+    line: usize,
+    column: usize,
+) -> Result<TokenType, LexcialError> {
+    let error = |message| LexcialError {
+        line,
+        column,
+        message,
+    };
+    let number = || format!("{}{}", digits, suffix);
+    let (type_name, min, max) = match suffix {
+        "i8" => (TypeName::I8, i8::MIN as i128, i8::MAX as i128),
+        "i16" => (TypeName::I16, i16::MIN as i128, i16::MAX as i128),
+        "i32" => (TypeName::I32, i32::MIN as i128, i32::MAX as i128),
+        "i64" => (TypeName::I64, i64::MIN as i128, i64::MAX as i128),
+        "u8" => (TypeName::U8, 0, u8::MAX as i128),
+        "u16" => (TypeName::U16, 0, u16::MAX as i128),
+        "u32" => (TypeName::U32, 0, u32::MAX as i128),
+        "u64" => (TypeName::U64, 0, u64::MAX as i128),
+        _ => return Err(error(LexError::InvalidNumber(number()))),
+    };
+    let value = digits
+        .parse::<i128>()
+        .map_err(|_| error(LexError::InvalidNumber(number())))?;
+    if value < min || value > max {
+        return Err(error(LexError::NumberOutOfRange(number(), type_name)));
+    }
+    Ok(TokenType::TypeValue(TypeValue::TypedNumber(
+        Cow::Owned(digits.to_owned()),
+        type_name,
+    )))
+}
+
+// The text between the quotes of a string literal, with `\n`, `\t`, `\r`, `\0`, `\\`, `\"`
+// and `\u{1F600}` escapes replaced by the characters they stand for
+pub fn string_to_token(raw: &str, line: usize, column: usize) -> Result<TokenType, LexcialError> {
+    unescape(raw)
+        .map(|text| TokenType::TypeValue(TypeValue::QuotedString(Cow::Owned(text))))
+        .map_err(|message| LexcialError {
+            line,
+            column,
+            message,
+        })
+}
+fn unescape(raw: &str) -> Result<String, LexError> {
+    let mut text = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('u') => unicode_escape(&mut chars)?,
+            Some(other) => simple_escape(other)?,
+            None => return Err(LexError::InvalidEscape("\\".to_string())),
+        };
+        text.push(escaped);
+    }
+    Ok(text)
+}
+// `\u{...}` with one to six hex digits naming a unicode scalar value
+fn unicode_escape(chars: &mut Chars) -> Result<char, LexError> {
+    if chars.next() != Some('{') {
+        return Err(LexError::InvalidEscape("\\u".to_string()));
+    }
+    let mut digits = String::new();
+    loop {
+        match chars.next() {
+            Some('}') if !digits.is_empty() => break,
+            Some(c) if c.is_ascii_hexdigit() && digits.len() < 6 => digits.push(c),
+            _ => return Err(LexError::InvalidEscape(format!("\\u{{{}", digits))),
+        }
+    }
+    u32::from_str_radix(&digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or(LexError::InvalidCodePoint(digits))
+}
+// The escapes shared by strings and byte strings
+fn simple_escape(c: char) -> Result<char, LexError> {
+    match c {
+        'n' => Ok('\n'),
+        't' => Ok('\t'),
+        'r' => Ok('\r'),
+        '0' => Ok('\0'),
+        '\\' => Ok('\\'),
+        '"' => Ok('"'),
+        other => Err(LexError::InvalidEscape(format!("\\{}", other))),
+    }
+}
+
+// The text between the quotes of `b"..."`, ASCII characters and the string escapes
+// except `\u{...}`, plus `\xNN` for any byte
+pub fn byte_string_to_token(
+    raw: &str,
+    line: usize,
+    column: usize,
+) -> Result<TokenType, LexcialError> {
+    unescape_bytes(raw)
+        .map(|bytes| TokenType::TypeValue(TypeValue::Bytes(bytes)))
+        .map_err(|message| LexcialError {
+            line,
+            column,
+            message,
+        })
+}
+fn unescape_bytes(raw: &str) -> Result<Vec<u8>, LexError> {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if !c.is_ascii() {
+            return Err(LexError::InvalidCharacter(c));
+        }
+        if c != '\\' {
+            bytes.push(c as u8);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('x') => hex_escape(&mut chars)?,
+            Some(other) => simple_escape(other)
+                .map_err(|_| LexError::InvalidByteEscape(format!("\\{}", other)))?
+                as u8,
+            None => return Err(LexError::InvalidByteEscape("\\".to_string())),
+        };
+        bytes.push(escaped);
+    }
+    Ok(bytes)
+}
+// `\xNN` with exactly two hex digits
+fn hex_escape(chars: &mut Chars) -> Result<u8, LexError> {
+    let digits: String = chars.take(2).collect();
+    if digits.len() != 2 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(LexError::InvalidByteEscape(format!("\\x{}", digits)));
+    }
+    u8::from_str_radix(&digits, 16)
+        .map_err(|_| LexError::InvalidByteEscape(format!("\\x{}", digits)))
+}
@@ -3,6 +3,7 @@ mod lex;
 pub mod lex_new;
 pub mod lex_new_new;
 pub mod neo_tokens;
+mod relex;
 mod tokens;
 pub mod trie_lex;
 pub mod trie_tokens;
@@ -11,5 +12,69 @@ pub mod tokens_new;
 pub use lex::lexer;
 pub use tokens::*;
 pub use error::LexerError;
+pub use lex_new_new::{LexError, LexWarning, LexcialError};
+pub use relex::relex;
+
+use std::path::{Path, PathBuf};
+
+// Lexes a whole source with `lex_new_new::Lexer`, for using the lexer on its own.
+// Lexing continues past errors, so every error in the source is returned.
+// The errors are only returned, reporting them is left to the caller.
+pub fn lex(source: &str) -> Result<Vec<neo_tokens::Token>, Vec<LexcialError>> {
+    let mut lexer = lex_new_new::Lexer::new(PathBuf::from("<source>"), source);
+    lexer.set_print_errors(false);
+    finish(lexer)
+}
+// Like `lex`, but also writes the first `max_errors` errors of the file at `path` to
+// stderr with their context, and a count of the rest
+pub fn lex_file(
+    path: &Path,
+    source: &str,
+    max_errors: usize,
+) -> Result<Vec<neo_tokens::Token>, Vec<LexcialError>> {
+    let mut lexer = lex_new_new::Lexer::new(path.to_path_buf(), source);
+    lexer.set_max_errors(Some(max_errors));
+    finish(lexer)
+}
+fn finish(lexer: lex_new_new::Lexer) -> Result<Vec<neo_tokens::Token>, Vec<LexcialError>> {
+    match lexer.finish() {
+        (tokens, errors) if errors.is_empty() => Ok(tokens),
+        (_, errors) => Err(errors),
+    }
+}
 
 // benchmark between the two lexers
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use neo_tokens::{Statement, Symbol, TokenType};
+
+    #[test]
+    fn lex_source() {
+        let tokens = lex("return;").unwrap();
+        let token_types: Vec<TokenType> =
+            tokens.into_iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Statement(Statement::Return),
+                TokenType::Symbol(Symbol::Semicolon),
+            ]
+        );
+    }
+    #[test]
+    fn lex_source_error() {
+        let errors = lex("let a = \"open;").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, LexError::ExpectedQuote());
+    }
+    #[test]
+    fn lex_file_source() {
+        let path = Path::new("test.nk");
+        assert_eq!(lex_file(path, "return;", 1).unwrap(), lex("return;").unwrap());
+        // Errors past the maximum are still returned, only their reports are left out
+        let errors = lex_file(path, "let a = $ $ $;", 1).unwrap_err();
+        assert_eq!(errors.len(), 3);
+    }
+}
@@ -12,6 +12,7 @@ pub use operators::Operator;
 
 pub use statements::Statement;
 
+pub use types::byte_string_literal;
 pub use types::TypeName;
 pub use types::TypeValue;
 
@@ -21,11 +22,22 @@ pub use symbols::Symbol;
 pub struct TokenMetadata {
     pub line: usize,
     pub column: usize,
+    // Byte offsets of the token in the source, `end` is exclusive
+    pub start: usize,
+    pub end: usize,
 }
 
 impl TokenMetadata {
     pub fn new(line: usize, column: usize /* , src_location: Option<PathBuf> */) -> Self {
-        TokenMetadata { line, column }
+        TokenMetadata {
+            line,
+            column,
+            start: 0,
+            end: 0,
+        }
+    }
+    pub fn with_span(self, start: usize, end: usize) -> Self {
+        TokenMetadata { start, end, ..self }
     }
 }
 impl fmt::Display for TokenMetadata {
@@ -42,6 +54,16 @@ pub enum TokenType {
     TypeName(TypeName),
     TypeValue(TypeValue),
     Symbol(Symbol),
+    // Text after `//`, only produced when the lexer preserves comments
+    Comment(String),
+    // Text after `///`, documenting the item that follows
+    DocComment(String),
+    // Text after `//!`, documenting the enclosing module
+    InnerDocComment(String),
+    // A loop label like `'outer`, without the quote
+    Label(String),
+    // An attribute like `@inline`, without the `@`
+    Attribute(String),
     EOF,
 }
 
@@ -75,7 +97,43 @@ impl fmt::Display for TokenType {
             TokenType::TypeName(typename) => write!(f, "{}", typename),
             TokenType::TypeValue(typeval) => write!(f, "{}", typeval),
             TokenType::Symbol(symbol) => write!(f, "{}", symbol),
-            _ => write!(f, "EOF"),
+            TokenType::Comment(text) => write!(f, "//{}", text),
+            TokenType::DocComment(text) => write!(f, "///{}", text),
+            TokenType::InnerDocComment(text) => write!(f, "//!{}", text),
+            TokenType::Label(name) => write!(f, "'{}", name),
+            TokenType::Attribute(name) => write!(f, "@{}", name),
+            TokenType::EOF => write!(f, "EOF"),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn token(token_type: TokenType) -> Token {
+        Token::new(token_type, TokenMetadata::new(1, 1))
+    }
+    #[test]
+    fn display_surface_syntax() {
+        assert_eq!(token(TokenType::Operator(Operator::Add)).to_string(), "+");
+        assert_eq!(token(TokenType::Logical(Logical::NotEquals)).to_string(), "!=");
+        assert_eq!(token(TokenType::Assign(Assign::AddAssign)).to_string(), "+=");
+        assert_eq!(token(TokenType::Statement(Statement::Function)).to_string(), "fn");
+        assert_eq!(token(TokenType::Symbol(Symbol::Arrow)).to_string(), "->");
+        assert_eq!(token(TokenType::TypeName(TypeName::Void)).to_string(), "Void");
+        assert_eq!(token(TokenType::TypeName(TypeName::QuotedString)).to_string(), "String");
+        assert_eq!(token(TokenType::TypeName(TypeName::I32)).to_string(), "i32");
+    }
+    #[test]
+    fn display_values() {
+        let string = TypeValue::QuotedString(Cow::Borrowed("hi"));
+        assert_eq!(token(TokenType::TypeValue(string)).to_string(), "\"hi\"");
+        assert_eq!(TypeValue::Char('c').to_string(), "'c'");
+        assert_eq!(TypeValue::Number(Cow::Borrowed("42")).to_string(), "42");
+        assert_eq!(TypeValue::Identifier("main".to_string()).to_string(), "main");
+        assert_eq!(token(TokenType::Comment(" hi".to_string())).to_string(), "// hi");
+        assert_eq!(token(TokenType::EOF).to_string(), "EOF");
+    }
+}
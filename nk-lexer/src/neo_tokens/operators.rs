@@ -9,6 +9,7 @@ pub enum Operator {
     Multiply,
     Divide,
     Remainder,
+    Power,
 
     ShiftLeft,
     ShiftRight,
@@ -26,6 +27,7 @@ impl Operator {
             Operator::Multiply => "*",
             Operator::Divide => "/",
             Operator::Remainder => "%",
+            Operator::Power => "**",
             Operator::ShiftLeft => "<<",
             Operator::ShiftRight => ">>",
             Operator::BitAnd => "&",
@@ -83,6 +85,8 @@ impl fmt::Display for Logical {
 pub enum Assign {
     // Default
     Assign,
+    // `:=`, declares a variable with the type of its value
+    Declare,
 
     // Normal Operator Assigns
     AddAssign,
@@ -102,6 +106,7 @@ impl Assign {
     fn as_str(&self) -> &'static str {
         match *self {
             Assign::Assign => "=",
+            Assign::Declare => ":=",
             Assign::AddAssign => "+=",
             Assign::SubAssign => "-=",
             Assign::MulAssign => "*=",
@@ -12,13 +12,16 @@ pub enum Statement {
     Println,
     Scanln,
     Break,
+    Continue,
     If,
     Else,
     ElseIf,
     For,
     While,
+    Loop,
     Function,
     Inject,
+    Match,
 }
 impl Statement {
     // Returns a string representation of the statement.
@@ -33,13 +36,16 @@ impl Statement {
             Statement::Println => "println",
             Statement::Scanln => "scanln",
             Statement::Break => "break",
+            Statement::Continue => "continue",
             Statement::If => "if",
             Statement::Else => "else",
             Statement::ElseIf => "else if",
             Statement::For => "for",
             Statement::While => "while",
+            Statement::Loop => "loop",
             Statement::Function => "fn",
             Statement::Inject => "inject",
+            Statement::Match => "match",
         }
     }
 }
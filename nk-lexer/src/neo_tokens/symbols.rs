@@ -19,6 +19,7 @@ pub enum Symbol {
     CloseSquare,
     Comment,
     Arrow,
+    FatArrow,
     Semicolon,
 }
 impl Symbol {
@@ -40,6 +41,7 @@ impl Symbol {
             Symbol::CloseSquare => "]",
             Symbol::Comment => "//",
             Symbol::Arrow => "->",
+            Symbol::FatArrow => "=>",
             Symbol::Semicolon => ";",
         }
     }
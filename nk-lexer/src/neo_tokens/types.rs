@@ -19,13 +19,17 @@ pub enum TypeName {
     Bool,
     Float,
     Number,
+    Array,
+    Map,
+    Bytes,
+    Tuple,
 }
 impl TypeName {
-    // Returns a string representation of the type.
+    // Returns the type as it is written in source code.
     #[allow(dead_code)]
     fn as_str(&self) -> &'static str {
         match *self {
-            TypeName::Void => "void",
+            TypeName::Void => "Void",
             TypeName::I8 => "i8",
             TypeName::I16 => "i16",
             TypeName::I32 => "i32",
@@ -34,11 +38,15 @@ impl TypeName {
             TypeName::U16 => "u16",
             TypeName::U32 => "u32",
             TypeName::U64 => "u64",
-            TypeName::QuotedString => "string",
-            TypeName::Char => "char",
-            TypeName::Bool => "bool",
+            TypeName::QuotedString => "String",
+            TypeName::Char => "Char",
+            TypeName::Bool => "Bool",
             TypeName::Float => "float",
             TypeName::Number => "number",
+            TypeName::Array => "Array",
+            TypeName::Map => "Map",
+            TypeName::Bytes => "Bytes",
+            TypeName::Tuple => "Tuple",
         }
     }
 }
@@ -54,11 +62,17 @@ impl fmt::Display for TypeName {
 pub enum TypeValue {
     NoneVoid,
     Number(Cow<'static, str>),
+    // Digits of an integer literal with a type suffix, `255u8`
+    TypedNumber(Cow<'static, str>, TypeName),
     QuotedString(Cow<'static, str>),
     Bool(bool),
     Char(char),
     //Float(f64),
     Identifier(String),
+    // A lone `_`, the wildcard of `match` and the name of ignored `let` bindings
+    Discard,
+    // A `b"..."` literal
+    Bytes(Vec<u8>),
 }
 impl TypeValue {
     // Returns a string representation of the type.
@@ -69,7 +83,9 @@ impl TypeValue {
             TypeValue::QuotedString(_) => TypeName::QuotedString,
             TypeValue::Bool(_) => TypeName::Bool,
             TypeValue::Number(_) => TypeName::Number,
+            TypeValue::TypedNumber(_, type_name) => type_name,
             TypeValue::Char(_) => TypeName::Char,
+            TypeValue::Bytes(_) => TypeName::Bytes,
             _ => panic!("Type is not a valid type"),
         }
     }
@@ -78,13 +94,33 @@ impl TypeValue {
 impl fmt::Display for TypeValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            TypeValue::NoneVoid => write!(f, "None"),
-            TypeValue::QuotedString(ref s) => write!(f, "{}", s),
+            TypeValue::NoneVoid => write!(f, "none"),
+            TypeValue::QuotedString(ref s) => write!(f, "\"{}\"", s),
             //TypeValue::Float(n) => write!(f, "Float({})", n),
             TypeValue::Identifier(ref s) => write!(f, "{}", s),
+            TypeValue::Discard => write!(f, "_"),
+            TypeValue::Bytes(ref bytes) => write!(f, "{}", byte_string_literal(bytes)),
             TypeValue::Bool(b) => write!(f, "{}", b),
-            TypeValue::Char(c) => write!(f, "{}", c),
+            TypeValue::Char(c) => write!(f, "'{}'", c),
             TypeValue::Number(ref s) => write!(f, "{}", s),
+            TypeValue::TypedNumber(ref s, type_name) => write!(f, "{}{}", s, type_name),
+        }
+    }
+}
+
+// Writes bytes as a `b"..."` literal, bytes that are not printable ASCII as `\xNN`
+pub fn byte_string_literal(bytes: &[u8]) -> String {
+    let mut literal = String::from("b\"");
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                literal.push('\\');
+                literal.push(byte as char);
+            }
+            b' '..=b'~' => literal.push(byte as char),
+            _ => literal.push_str(&format!("\\x{:02X}", byte)),
         }
     }
+    literal.push('"');
+    literal
 }
@@ -0,0 +1,161 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::lex_new_new::{LexcialError, Lexer};
+use crate::neo_tokens::Token;
+
+// Lexes a source again after an edit, reusing the tokens that the edit can not change.
+// `changed` are the bytes of the previous source that were replaced by `text`, `tokens` are
+// the tokens of the previous source and `source` is the source after the edit.
+// The result is the same as lexing the whole `source` again.
+pub fn relex(
+    tokens: &[Token],
+    changed: Range<usize>,
+    text: &str,
+    source: &str,
+) -> Result<Vec<Token>, Vec<LexcialError>> {
+    // A line start inside a block comment is not a place to restart, and block comments
+    // leave no tokens to find them by. The end of any comment of the previous source
+    // outside the edit is still in the source, so without both markers there is none.
+    if source.contains("/*") || source.contains("*/") {
+        return lex_region(source, 1);
+    }
+    let start = restart_point(tokens, line_start(source, changed.start));
+    let mut resync = resync_point(tokens, &changed, text, source);
+    let end = resync.map_or(source.len(), |(end, _)| end);
+    let line = 1 + count_lines(&source[..start]);
+    let lexed = match lex_region(&source[start..end], line) {
+        // A string that is still open at the resync point goes on past it
+        Err(_) if resync.is_some() => {
+            resync = None;
+            lex_region(&source[start..], line)?
+        }
+        result => result?,
+    };
+
+    let mut result: Vec<Token> = tokens
+        .iter()
+        .take_while(|token| token.metadata.start < start)
+        .cloned()
+        .collect();
+    result.extend(lexed.into_iter().map(|mut token| {
+        token.metadata.start += start;
+        token.metadata.end += start;
+        token
+    }));
+    let Some((end, old_end)) = resync else {
+        return Ok(result);
+    };
+    let kept = tokens
+        .iter()
+        .skip_while(|token| token.metadata.start < old_end);
+    // The text after the resync point is unchanged, only the lines before it moved
+    let shift = |offset: usize| offset + end - old_end;
+    let end_line = line + count_lines(&source[start..end]);
+    let mut lines_moved = None;
+    for token in kept {
+        let mut token = token.clone();
+        let moved = *lines_moved.get_or_insert_with(|| {
+            let between = count_lines(&source[end..shift(token.metadata.start)]);
+            end_line as isize - (token.metadata.line - between) as isize
+        });
+        token.metadata.start = shift(token.metadata.start);
+        token.metadata.end = shift(token.metadata.end);
+        token.metadata.line = (token.metadata.line as isize + moved) as usize;
+        result.push(token);
+    }
+    Ok(result)
+}
+
+fn lex_region(region: &str, line: usize) -> Result<Vec<Token>, Vec<LexcialError>> {
+    let mut lexer = Lexer::new(PathBuf::from("<source>"), region);
+    lexer.set_print_errors(false);
+    lexer.set_first_line(line);
+    match lexer.finish() {
+        (tokens, errors) if errors.is_empty() => Ok(tokens),
+        (_, errors) => Err(errors),
+    }
+}
+// A line start at or before `offset` that no token spans, the lexer starts empty there
+fn restart_point(tokens: &[Token], mut offset: usize) -> usize {
+    while let Some(token) = tokens.iter().find(|token| spans(token, offset)) {
+        offset = token.metadata.start;
+    }
+    offset
+}
+// The first line start after the edit that no previous token spans, in the new and the
+// previous source, or `None` when the rest of the source has to be lexed again
+fn resync_point(
+    tokens: &[Token],
+    changed: &Range<usize>,
+    text: &str,
+    source: &str,
+) -> Option<(usize, usize)> {
+    let mut from = changed.start + text.len();
+    loop {
+        let end = from + source[from..].find('\n')? + 1;
+        let old_end = end + changed.len() - text.len();
+        match tokens.iter().find(|token| spans(token, old_end)) {
+            Some(token) => from = token.metadata.end + text.len() - changed.len(),
+            None => return Some((end, old_end)),
+        }
+    }
+}
+
+fn spans(token: &Token, offset: usize) -> bool {
+    token.metadata.start < offset && offset < token.metadata.end
+}
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1)
+}
+fn count_lines(text: &str) -> usize {
+    text.bytes().filter(|&byte| byte == b'\n').count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lex;
+
+    #[test]
+    fn relex_matches_full_lex() {
+        let source = "fn main() {\n    let a = 1;\n    let s = \"one\ntwo\";\n    return a;\n}\n";
+        let edits = [
+            // Renames `a` in the first `let`
+            (20..21, "total"),
+            // Adds a line
+            (27..27, "    let b = a + 2;\n"),
+            // Comments out a line
+            (27..27, "//"),
+            // Edits inside the string that spans two lines
+            (44..47, "three\nfour"),
+            // Joins two lines
+            (26..31, " "),
+            // Deletes everything after the first line
+            (12..source.len(), ""),
+        ];
+        let tokens = lex(source).unwrap();
+        for (changed, text) in edits {
+            let mut edited = source.to_string();
+            edited.replace_range(changed.clone(), text);
+            let relexed = relex(&tokens, changed.clone(), text, &edited);
+            assert_eq!(relexed, lex(&edited), "{:?} {:?}", changed, text);
+        }
+    }
+    #[test]
+    fn relex_block_comments() {
+        let source = "/* a\nb */ let c = 1;\nlet d = 2;\n";
+        let tokens = lex(source).unwrap();
+        let edited = " a\nb */ let c = 1;\nlet d = 2;\n";
+        assert_eq!(relex(&tokens, 0..2, "", edited), lex(edited));
+    }
+    #[test]
+    fn relex_reports_errors() {
+        let source = "let a = 1;\nlet b = 2;\n";
+        let tokens = lex(source).unwrap();
+        let edited = "let a = \"1;\nlet b = 2;\n";
+        assert_eq!(relex(&tokens, 8..8, "\"", edited), lex(edited));
+    }
+}
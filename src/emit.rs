@@ -0,0 +1,81 @@
+use astgen::parser_new::AstGenError;
+use lexer::neo_tokens::{Token, TokenType};
+use lexer::LexcialError;
+use serde::Serialize;
+
+// A token as it is written by `--emit=tokens-json`
+#[derive(Debug, Serialize)]
+struct TokenEntry {
+    kind: &'static str,
+    text: String,
+    line: usize,
+    column: usize,
+}
+impl From<&Token> for TokenEntry {
+    fn from(token: &Token) -> Self {
+        TokenEntry {
+            kind: token_kind(&token.token_type),
+            text: token.to_string(),
+            line: token.metadata.line,
+            column: token.metadata.column,
+        }
+    }
+}
+
+fn token_kind(token_type: &TokenType) -> &'static str {
+    match token_type {
+        TokenType::Operator(_) => "Operator",
+        TokenType::Logical(_) => "Logical",
+        TokenType::Assign(_) => "Assign",
+        TokenType::Statement(_) => "Statement",
+        TokenType::TypeName(_) => "TypeName",
+        TokenType::TypeValue(_) => "TypeValue",
+        TokenType::Symbol(_) => "Symbol",
+        TokenType::Comment(_) => "Comment",
+        TokenType::DocComment(_) => "DocComment",
+        TokenType::InnerDocComment(_) => "InnerDocComment",
+        TokenType::Label(_) => "Label",
+        TokenType::Attribute(_) => "Attribute",
+        TokenType::EOF => "EOF",
+    }
+}
+
+// Serializes the token stream to a JSON array for external tools
+pub fn tokens_json(tokens: &[Token]) -> Result<String, serde_json::Error> {
+    let entries: Vec<TokenEntry> = tokens.iter().map(TokenEntry::from).collect();
+    serde_json::to_string(&entries)
+}
+
+// A lexer or parser error as it is written by `--error-format=json`
+#[derive(Debug, Serialize)]
+pub struct ErrorEntry {
+    message: String,
+    line: usize,
+    column: usize,
+    suggestion: String,
+}
+impl From<&LexcialError> for ErrorEntry {
+    fn from(error: &LexcialError) -> Self {
+        ErrorEntry {
+            message: error.message.to_string(),
+            line: error.line,
+            column: error.column,
+            suggestion: error.suggestion(),
+        }
+    }
+}
+impl From<&AstGenError> for ErrorEntry {
+    fn from(error: &AstGenError) -> Self {
+        ErrorEntry {
+            message: error.message.to_string(),
+            line: error.line,
+            column: error.column,
+            suggestion: error.suggestion(),
+        }
+    }
+}
+
+// Serializes diagnostics to a JSON array for editors and other tools
+pub fn errors_json(errors: &[ErrorEntry]) -> Result<String, serde_json::Error> {
+    serde_json::to_string(errors)
+}
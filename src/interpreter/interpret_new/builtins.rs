@@ -0,0 +1,463 @@
+use std::cmp::Ordering;
+
+use astgen::ast::ASTOperator;
+
+use super::error::RuntimeError;
+use super::value::Value;
+
+// Functions that are available without being defined in the program
+pub fn call(name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    match name {
+        "keys" => {
+            let entries = map_argument(name, args)?;
+            Ok(Value::Array(
+                entries.into_iter().map(|(key, _)| key).collect(),
+            ))
+        }
+        "values" => {
+            let entries = map_argument(name, args)?;
+            Ok(Value::Array(
+                entries.into_iter().map(|(_, value)| value).collect(),
+            ))
+        }
+        "range" => range(args),
+        "sort" => {
+            let values = array_argument(name, single_argument(args)?)?;
+            sort_values(values, &mut |a, b| {
+                a.ordering(b)
+                    .flatten()
+                    .ok_or_else(|| unordered(name, a, b))
+            })
+            .map(Value::Array)
+        }
+        "min" => extreme(name, Ordering::Less, args),
+        "max" => extreme(name, Ordering::Greater, args),
+        "sum" => sum(single_argument(args)?),
+        "get" => get(args),
+        "is_none" => Ok(Value::Bool(single_argument(args)? == Value::None)),
+        "is_some" => Ok(Value::Bool(single_argument(args)? != Value::None)),
+        "format" => format(args),
+        "repeat" => repeat(args),
+        "reverse" => reverse(single_argument(args)?),
+        "split_whitespace" => {
+            let text = string_argument(name, single_argument(args)?)?;
+            Ok(string_array(text.split_whitespace()))
+        }
+        "lines" => {
+            let text = string_argument(name, single_argument(args)?)?;
+            Ok(string_array(text.lines()))
+        }
+        "type_assert" => type_assert(args),
+        "to_int" => to_int(single_argument(args)?),
+        "to_float" => to_float(single_argument(args)?),
+        "to_bool" => to_bool(single_argument(args)?),
+        "wrapping_add" => wrapping(name, ASTOperator::Add, args),
+        "wrapping_sub" => wrapping(name, ASTOperator::Subtract, args),
+        "wrapping_mul" => wrapping(name, ASTOperator::Multiply, args),
+        "contains" => Ok(Value::Bool(search(name, args)?.is_some())),
+        "index_of" => {
+            let index = search(name, args)?.map_or(Ok(-1), i64::try_from);
+            Ok(Value::I64(index.map_err(|_| {
+                RuntimeError::IntegerOverflow("i64".to_string())
+            })?))
+        }
+        _ => Err(RuntimeError::UndefinedFunction(name.to_string())),
+    }
+}
+
+// Predefined constants like `I32_MAX`, visible everywhere unless a variable shadows them
+pub fn constant(name: &str) -> Option<Value> {
+    let value = match name {
+        "I8_MIN" => Value::I8(i8::MIN),
+        "I8_MAX" => Value::I8(i8::MAX),
+        "I16_MIN" => Value::I16(i16::MIN),
+        "I16_MAX" => Value::I16(i16::MAX),
+        "I32_MIN" => Value::I32(i32::MIN),
+        "I32_MAX" => Value::I32(i32::MAX),
+        "I64_MIN" => Value::I64(i64::MIN),
+        "I64_MAX" => Value::I64(i64::MAX),
+        "U8_MIN" => Value::U8(u8::MIN),
+        "U8_MAX" => Value::U8(u8::MAX),
+        "U16_MIN" => Value::U16(u16::MIN),
+        "U16_MAX" => Value::U16(u16::MAX),
+        "U32_MIN" => Value::U32(u32::MIN),
+        "U32_MAX" => Value::U32(u32::MAX),
+        "U64_MIN" => Value::U64(u64::MIN),
+        "U64_MAX" => Value::U64(u64::MAX),
+        "F32_MIN" => Value::F32(f32::MIN),
+        "F32_MAX" => Value::F32(f32::MAX),
+        "F64_MIN" => Value::F64(f64::MIN),
+        "F64_MAX" => Value::F64(f64::MAX),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// range(start, end, step), `end` is excluded and a negative step counts down
+fn range(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [start, end, step] = <[Value; 3]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(3, args.len()))?;
+    let (mut current, end, step) = match (start.as_integer(), end.as_integer(), step.as_integer()) {
+        (Some(start), Some(end), Some(step)) => (start, end, step),
+        _ => {
+            return Err(RuntimeError::InvalidArgument(
+                "range".to_string(),
+                "expected integers".to_string(),
+            ))
+        }
+    };
+    if step == 0 {
+        return Err(RuntimeError::InvalidArgument(
+            "range".to_string(),
+            "step must not be zero".to_string(),
+        ));
+    }
+    let mut values = Vec::new();
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        values.push(
+            start
+                .with_integer(current)
+                .ok_or_else(|| RuntimeError::IntegerOverflow(start.type_name().to_string()))?,
+        );
+        current += step;
+    }
+    Ok(Value::Array(values))
+}
+
+// get(map, key), `none` instead of an error when the key is missing
+fn get(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [map, key] = <[Value; 2]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+    match map.index(&key) {
+        Err(RuntimeError::MissingKey(_)) => Ok(Value::None),
+        result => result,
+    }
+}
+
+// repeat(s, n), `s` written `n` times, empty for zero
+fn repeat(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [text, count] = <[Value; 2]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+    let text = string_argument("repeat", text)?;
+    let count = count.as_integer().ok_or_else(|| {
+        RuntimeError::InvalidArgument(
+            "repeat".to_string(),
+            "expected an integer count".to_string(),
+        )
+    })?;
+    let count = usize::try_from(count).map_err(|_| {
+        RuntimeError::InvalidArgument(
+            "repeat".to_string(),
+            format!("count must not be negative, found {}", count),
+        )
+    })?;
+    Ok(Value::QuotedString(text.repeat(count)))
+}
+
+// reverse(x), an Array or Bytes back to front, or the characters of a String
+fn reverse(value: Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Array(values) => Ok(Value::Array(values.into_iter().rev().collect())),
+        Value::Bytes(bytes) => Ok(Value::Bytes(bytes.into_iter().rev().collect())),
+        Value::QuotedString(text) => Ok(Value::QuotedString(text.chars().rev().collect())),
+        other => Err(RuntimeError::InvalidArgument(
+            "reverse".to_string(),
+            format!("expected Array or String, found {}", other.type_name()),
+        )),
+    }
+}
+
+// type_assert(x, name), `x` itself when its type is called `name`
+fn type_assert(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [value, name] = <[Value; 2]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+    match name {
+        Value::QuotedString(name) if name == value.type_name() => Ok(value),
+        Value::QuotedString(name) => Err(RuntimeError::MismatchedTypes(
+            name,
+            value.type_name().to_string(),
+        )),
+        other => Err(RuntimeError::InvalidArgument(
+            "type_assert".to_string(),
+            format!("expected a String type name, found {}", other.type_name()),
+        )),
+    }
+}
+
+// to_int(x), an i64 from a decimal string, an integer or a float, which is truncated
+fn to_int(value: Value) -> Result<Value, RuntimeError> {
+    let n = match &value {
+        Value::QuotedString(text) => text.trim().parse::<i64>().ok(),
+        Value::F32(_) | Value::F64(_) => value
+            .as_float()
+            .filter(|n| n.is_finite() && n.abs() < i64::MAX as f64)
+            .map(|n| n as i64),
+        Value::Bool(b) => Some(i64::from(*b)),
+        _ => value.as_integer().and_then(|n| i64::try_from(n).ok()),
+    };
+    n.map(Value::I64)
+        .ok_or_else(|| conversion_error("to_int", &value, "an integer"))
+}
+// to_float(x), an f64 from a decimal string or a number
+fn to_float(value: Value) -> Result<Value, RuntimeError> {
+    let n = match &value {
+        Value::QuotedString(text) => text.trim().parse::<f64>().ok(),
+        _ => value.as_float(),
+    };
+    n.map(Value::F64)
+        .ok_or_else(|| conversion_error("to_float", &value, "a float"))
+}
+// to_bool(x), `true` or `false` from a string, a number is true when it is not zero
+fn to_bool(value: Value) -> Result<Value, RuntimeError> {
+    let b = match &value {
+        Value::Bool(b) => Some(*b),
+        Value::QuotedString(text) => match text.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => value.as_float().map(|n| n != 0.0),
+    };
+    b.map(Value::Bool)
+        .ok_or_else(|| conversion_error("to_bool", &value, "a Bool"))
+}
+fn conversion_error(name: &str, value: &Value, expected: &str) -> RuntimeError {
+    let found = match value {
+        Value::QuotedString(text) => format!("\"{}\"", text),
+        other => other.type_name().to_string(),
+    };
+    RuntimeError::InvalidArgument(
+        name.to_string(),
+        format!("cannot convert {} to {}", found, expected),
+    )
+}
+
+// wrapping_add(a, b) and friends, the operator without the overflow check
+fn wrapping(name: &str, op: ASTOperator, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [a, b] = <[Value; 2]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+    a.wrapping(&op, &b).ok_or_else(|| {
+        RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!(
+                "expected integers of the same type, found {} and {}",
+                a.type_name(),
+                b.type_name()
+            ),
+        )
+    })
+}
+
+// min(a, b) and max(a, b) of two numbers, min(array) and max(array) of the elements
+// The first of equal values is kept, NaN can not be ordered and is an error
+fn extreme(name: &str, wanted: Ordering, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let values = match <[Value; 1]>::try_from(args) {
+        Ok([array]) => array_argument(name, array)?,
+        Err(args) if args.len() == 2 => args,
+        Err(args) => return Err(RuntimeError::MismatchedArgumentCount(2, args.len())),
+    };
+    let mut values = values.into_iter();
+    let first = values.next().ok_or_else(|| {
+        RuntimeError::InvalidArgument(name.to_string(), "the Array is empty".to_string())
+    })?;
+    let mut best = number_argument(name, first)?;
+    for value in values {
+        let value = number_argument(name, value)?;
+        let ordering = value.ordering(&best).flatten();
+        if ordering.ok_or_else(|| unordered(name, &value, &best))? == wanted {
+            best = value;
+        }
+    }
+    Ok(best)
+}
+// The sum has the type of the elements, `sum` of an empty Array is `0`
+fn sum(value: Value) -> Result<Value, RuntimeError> {
+    let mut values = array_argument("sum", value)?.into_iter();
+    let Some(first) = values.next() else {
+        return Ok(Value::I64(0));
+    };
+    let mut total = number_argument("sum", first)?;
+    for value in values {
+        total = total.binary(&ASTOperator::Add, &number_argument("sum", value)?)?;
+    }
+    Ok(total)
+}
+
+// contains(haystack, needle) and index_of(haystack, needle), a substring of a String or an
+// element of an Array, the position is counted in characters for a String
+fn search(name: &str, args: Vec<Value>) -> Result<Option<usize>, RuntimeError> {
+    let [haystack, needle] = <[Value; 2]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+    match (haystack, needle) {
+        (Value::QuotedString(text), Value::QuotedString(needle)) => {
+            Ok(text.find(&needle).map(|byte| text[..byte].chars().count()))
+        }
+        (Value::QuotedString(_), needle) => Err(RuntimeError::MismatchedTypes(
+            "String".to_string(),
+            needle.type_name().to_string(),
+        )),
+        (Value::Array(values), needle) => Ok(values.iter().position(|value| {
+            // `==` of the language, elements that do not compare with the needle differ
+            matches!(value.binary(&ASTOperator::Equals, &needle), Ok(Value::Bool(true)))
+        })),
+        (other, _) => Err(RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!("expected String or Array, found {}", other.type_name()),
+        )),
+    }
+}
+
+// format(fmt, ...), C-style `%d`, `%s`, `%f` and `%%` specifiers
+fn format(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let mut args = args.into_iter();
+    let template = match args.next() {
+        Some(Value::QuotedString(template)) => template,
+        Some(other) => {
+            return Err(format_error(format!(
+                "expected String format, found {}",
+                other.type_name()
+            )))
+        }
+        None => return Err(RuntimeError::MismatchedArgumentCount(1, 0)),
+    };
+    let values: Vec<Value> = args.collect();
+    let mut remaining = values.iter();
+    let mut used = 0;
+    let mut output = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        let specifier = chars
+            .next()
+            .ok_or_else(|| format_error("format string ends with '%'".to_string()))?;
+        if specifier == '%' {
+            output.push('%');
+            continue;
+        }
+        used += 1;
+        let value = remaining.next().ok_or_else(|| {
+            format_error(format!(
+                "missing argument for '%{}', {} given",
+                specifier,
+                values.len()
+            ))
+        })?;
+        let text = match specifier {
+            'd' => value.as_integer().map(|_| value.to_string()),
+            'f' => value.as_float().map(|n| format!("{:.6}", n)),
+            's' => Some(value.to_string()),
+            _ => return Err(format_error(format!("unknown specifier '%{}'", specifier))),
+        };
+        let text = text.ok_or_else(|| {
+            format_error(format!(
+                "'%{}' cannot format {}",
+                specifier,
+                value.type_name()
+            ))
+        })?;
+        output.push_str(&text);
+    }
+    if used != values.len() {
+        return Err(format_error(format!(
+            "{} arguments given but the format string uses {}",
+            values.len(),
+            used
+        )));
+    }
+    Ok(Value::QuotedString(output))
+}
+// A stable merge sort, unlike `slice::sort_by` it stops at the first error of `compare`
+// and can not panic on a comparison that is not a total order
+pub fn sort_values(
+    mut values: Vec<Value>,
+    compare: &mut dyn FnMut(&Value, &Value) -> Result<Ordering, RuntimeError>,
+) -> Result<Vec<Value>, RuntimeError> {
+    if values.len() < 2 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let mut left = sort_values(values, compare)?.into_iter().peekable();
+    let mut right = sort_values(right, compare)?.into_iter().peekable();
+    let mut sorted = Vec::with_capacity(left.len() + right.len());
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        let next = match compare(a, b)? {
+            Ordering::Greater => right.next(),
+            _ => left.next(),
+        };
+        sorted.extend(next);
+    }
+    sorted.extend(left);
+    sorted.extend(right);
+    Ok(sorted)
+}
+fn unordered(name: &str, a: &Value, b: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument(
+        name.to_string(),
+        format!("can not order {} and {}", a.type_name(), b.type_name()),
+    )
+}
+fn format_error(message: String) -> RuntimeError {
+    RuntimeError::InvalidArgument("format".to_string(), message)
+}
+
+pub fn array_argument(name: &str, value: Value) -> Result<Vec<Value>, RuntimeError> {
+    match value {
+        Value::Array(values) => Ok(values),
+        other => Err(RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!("expected Array, found {}", other.type_name()),
+        )),
+    }
+}
+pub fn string_argument(name: &str, value: Value) -> Result<String, RuntimeError> {
+    match value {
+        Value::QuotedString(text) => Ok(text),
+        other => Err(RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!("expected String, found {}", other.type_name()),
+        )),
+    }
+}
+fn number_argument(name: &str, value: Value) -> Result<Value, RuntimeError> {
+    match value.as_float() {
+        Some(_) => Ok(value),
+        None => Err(RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!("expected a number, found {}", value.type_name()),
+        )),
+    }
+}
+// The name of the function a function value refers to
+pub fn function_argument(name: &str, value: Value) -> Result<String, RuntimeError> {
+    match value {
+        Value::Function(function) => Ok(function),
+        other => Err(RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!("expected Function, found {}", other.type_name()),
+        )),
+    }
+}
+// The parts of a String split by `split_whitespace` or `lines`
+fn string_array<'a>(parts: impl Iterator<Item = &'a str>) -> Value {
+    Value::Array(parts.map(|part| Value::QuotedString(part.to_string())).collect())
+}
+fn single_argument(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [value] = <[Value; 1]>::try_from(args)
+        .map_err(|args| RuntimeError::MismatchedArgumentCount(1, args.len()))?;
+    Ok(value)
+}
+fn map_argument(name: &str, args: Vec<Value>) -> Result<Vec<(Value, Value)>, RuntimeError> {
+    let count = args.len();
+    let mut args = args.into_iter();
+    match (args.next(), count) {
+        (Some(Value::Map(entries)), 1) => Ok(entries),
+        (Some(other), 1) => Err(RuntimeError::InvalidArgument(
+            name.to_string(),
+            format!("expected Map, found {}", other.type_name()),
+        )),
+        _ => Err(RuntimeError::MismatchedArgumentCount(1, count)),
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use astgen::parser_new::AstGenError;
+use lexer::LexcialError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum RuntimeError {
+    NoMainFunction(),
+    MainWithParameters(),
+    DuplicateFunction(String),
+    UndefinedVariable(String),
+    UninitializedVariable(String),
+    AlreadyDeclared(String),
+    // A function, parameter or variable named like a `--define` constant
+    DefinedName(String),
+    UndefinedFunction(String),
+    // Name and type of a variable that was called like a function
+    NotCallable(String, String),
+    MismatchedArgumentCount(usize, usize),
+    MismatchedArgumentType(usize, String, String, String),
+    MismatchedTypes(String, String),
+    // Variable, its type and the type of the assigned value
+    AssignmentMismatch(String, String, String),
+    ReturnValueInVoidFunction(String),
+    MissingReturnValue(String, String),
+    InvalidOperands(String, String, String),
+    InvalidCondition(String),
+    InvalidAssignment(String),
+    InvalidMapKey(String),
+    InvalidArgument(String, String),
+    NotIndexable(String),
+    InvalidIndex(String, String),
+    IndexOutOfBounds(i128, usize),
+    MissingKey(String),
+    // Type of the target and the name of the field
+    NoField(String, String),
+    // Number of names and number of values of a tuple destructuring
+    DestructureArity(usize, usize),
+    DivisionByZero(),
+    NegativeExponent(i128),
+    VoidValue(),
+    // Function and type of a result dropped by a call statement with `set_strict_discard`
+    DiscardedValue(String, String),
+    NoneValue(String),
+    NoMatchingArm(String),
+    ReturnInBlock(),
+    // A block expression whose value is used but that does not end in an expression
+    VoidBlock(),
+    // `break` or `continue` in a block expression, which can not leave the loop around it
+    LoopControlInBlock(String),
+    // Raised by `panic(message)`, with the line of the call
+    Panic(String, usize),
+    IntegerOverflow(String),
+    UnsupportedStatement(String),
+    Output(String),
+    // The text between `{:` and `}` of a format string
+    InvalidFormatSpec(String),
+    // `exit(code)` was called, unwinds like an error but is not a failure
+    Exit(i32),
+}
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::NoMainFunction() => write!(f, "no `main` function found"),
+            RuntimeError::MainWithParameters() => write!(f, "`main` must not take parameters"),
+            RuntimeError::DuplicateFunction(name) => {
+                write!(f, "function `{}` is defined more than once", name)
+            }
+            RuntimeError::UndefinedVariable(name) => write!(f, "undefined variable `{}`", name),
+            RuntimeError::UninitializedVariable(name) => {
+                write!(f, "use of uninitialized variable '{}'", name)
+            }
+            RuntimeError::AlreadyDeclared(name) => {
+                write!(f, "`{}` is already declared in this scope", name)
+            }
+            RuntimeError::DefinedName(name) => {
+                write!(f, "`{}` is already defined with --define", name)
+            }
+            RuntimeError::UndefinedFunction(name) => write!(f, "undefined function `{}`", name),
+            RuntimeError::NotCallable(name, found) => {
+                write!(f, "`{}` is {}, not a function", name, found)
+            }
+            RuntimeError::MismatchedArgumentCount(expected, found) => write!(
+                f,
+                "mismatched argument count: expected {}, found {}",
+                expected, found
+            ),
+            RuntimeError::MismatchedArgumentType(position, function, expected, found) => write!(
+                f,
+                "argument {} to '{}' expected {}, got {}",
+                position, function, expected, found
+            ),
+            RuntimeError::AssignmentMismatch(name, expected, found) => write!(
+                f,
+                "cannot assign {} to `{}` of type {}",
+                found, name, expected
+            ),
+            RuntimeError::MismatchedTypes(expected, found) => {
+                write!(
+                    f,
+                    "mismatched types: expected {}, found {}",
+                    expected, found
+                )
+            }
+            RuntimeError::ReturnValueInVoidFunction(function) => write!(
+                f,
+                "`return` with a value in `{}`, which returns Void",
+                function
+            ),
+            RuntimeError::MissingReturnValue(function, type_name) => {
+                write!(
+                    f,
+                    "`{}` must return a value of type {}",
+                    function, type_name
+                )
+            }
+            RuntimeError::InvalidOperands(op, left, right) => {
+                write!(f, "cannot apply `{}` to {} and {}", op, left, right)
+            }
+            RuntimeError::InvalidCondition(found) => {
+                write!(f, "condition must be Bool, found {}", found)
+            }
+            RuntimeError::InvalidAssignment(target) => {
+                write!(f, "cannot assign to `{}`", target)
+            }
+            RuntimeError::InvalidMapKey(found) => {
+                write!(
+                    f,
+                    "map keys must be integers, String, Bool or char, found {}",
+                    found
+                )
+            }
+            RuntimeError::InvalidArgument(builtin, message) => {
+                write!(f, "invalid argument to `{}`: {}", builtin, message)
+            }
+            RuntimeError::NotIndexable(found) => write!(f, "cannot index into {}", found),
+            RuntimeError::NoField(found, name) => write!(f, "{} has no field `{}`", found, name),
+            RuntimeError::DestructureArity(names, values) => write!(
+                f,
+                "cannot destructure a tuple of {} values into {} names",
+                values, names
+            ),
+            RuntimeError::InvalidIndex(container, index) => {
+                write!(f, "cannot index {} with {}", container, index)
+            }
+            RuntimeError::IndexOutOfBounds(index, len) => write!(
+                f,
+                "index out of bounds: the length is {} but the index is {}",
+                len, index
+            ),
+            RuntimeError::MissingKey(key) => write!(f, "key \"{}\" not found in map", key),
+            RuntimeError::DivisionByZero() => write!(f, "division by zero"),
+            RuntimeError::NegativeExponent(exponent) => write!(
+                f,
+                "integer power with negative exponent {}, use a float base",
+                exponent
+            ),
+            RuntimeError::VoidValue() => write!(f, "cannot use void value here"),
+            RuntimeError::DiscardedValue(name, type_name) => write!(
+                f,
+                "the {} returned by `{}` is not used, write `let _ = {}(...);` to drop it",
+                type_name, name, name
+            ),
+            RuntimeError::NoMatchingArm(value) => {
+                write!(f, "no match arm for {}, add a `_` arm", value)
+            }
+            RuntimeError::ReturnInBlock() => {
+                write!(f, "cannot return from inside a block expression")
+            }
+            RuntimeError::VoidBlock() => write!(
+                f,
+                "block has no value, end it with an expression that has no `;`"
+            ),
+            RuntimeError::LoopControlInBlock(keyword) => {
+                write!(f, "cannot {} from inside a block expression", keyword)
+            }
+            RuntimeError::Panic(message, line) => {
+                write!(f, "panicked at line {}: {}", line, message)
+            }
+            RuntimeError::NoneValue(op) => {
+                write!(
+                    f,
+                    "cannot use none with '{}', check it with is_none first",
+                    op
+                )
+            }
+            RuntimeError::IntegerOverflow(type_name) => {
+                write!(f, "integer overflow: value does not fit in {}", type_name)
+            }
+            RuntimeError::UnsupportedStatement(statement) => {
+                write!(f, "`{}` is not supported by the interpreter", statement)
+            }
+            RuntimeError::Output(error) => write!(f, "failed to write output: {}", error),
+            RuntimeError::InvalidFormatSpec(spec) => {
+                write!(f, "invalid format spec `{{:{}}}`", spec)
+            }
+            RuntimeError::Exit(code) => write!(f, "exit with code {}", code),
+        }
+    }
+}
+
+// Any error of `Interpreter::eval_source`, from lexing the source to running it
+#[derive(Debug)]
+#[allow(missing_docs)]
+pub enum EvalError {
+    Lex(Vec<LexcialError>),
+    Parse(Vec<AstGenError>),
+    Runtime(RuntimeError),
+    // The program stopped itself with `exit(code)`
+    Exit(i32),
+}
+impl From<RuntimeError> for EvalError {
+    fn from(error: RuntimeError) -> Self {
+        match error {
+            RuntimeError::Exit(code) => EvalError::Exit(code),
+            error => EvalError::Runtime(error),
+        }
+    }
+}
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = match self {
+            EvalError::Lex(errors) => errors
+                .iter()
+                .map(|error| format!("{}:{}: {}", error.line, error.column, error))
+                .collect(),
+            EvalError::Parse(errors) => errors
+                .iter()
+                .map(|error| format!("{}:{}: {}", error.line, error.column, error))
+                .collect(),
+            EvalError::Runtime(error) => vec![format!("Runtime Error: {}", error)],
+            EvalError::Exit(code) => vec![format!("exit with code {}", code)],
+        };
+        write!(f, "{}", lines.join("\n"))
+    }
+}
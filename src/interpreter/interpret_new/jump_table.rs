@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use astgen::ast::ASTtypevalue;
+use astgen::parser_new::walk_all;
+use astgen::AST;
+
+// Fewer arms than this are scanned faster than a table is built
+const MIN_ARMS: usize = 4;
+
+// Arms of a `match` on dense integer literals, indexed by `value - first`
+#[derive(Debug, PartialEq)]
+pub struct JumpTable {
+    first: i128,
+    arms: Vec<Option<usize>>,
+    // Position of the `_` arm
+    default: Option<usize>,
+}
+
+impl JumpTable {
+    // Only built when every arm before the `_` is an untyped integer literal, which equals
+    // an integer value exactly when their numbers are equal, and the literals cover at
+    // least half of the range between the smallest and the largest one.
+    pub fn build(arms: &[(AST, AST)]) -> Option<JumpTable> {
+        let mut literals = Vec::new();
+        let mut default = None;
+        for (position, (pattern, _)) in arms.iter().enumerate() {
+            match pattern {
+                AST::TypeValue(ASTtypevalue::Discard) => {
+                    default = Some(position);
+                    break;
+                }
+                AST::TypeValue(ASTtypevalue::I64(n)) => literals.push((i128::from(*n), position)),
+                _ => return None,
+            }
+        }
+        let first = literals.iter().map(|(n, _)| *n).min()?;
+        let last = literals.iter().map(|(n, _)| *n).max()?;
+        let size = usize::try_from(last - first + 1).ok()?;
+        if literals.len() < MIN_ARMS || size > literals.len() * 2 {
+            return None;
+        }
+        let mut table = vec![None; size];
+        for (n, position) in literals {
+            // The first of duplicate arms wins, as in the linear scan
+            if let Some(slot) = usize::try_from(n - first)
+                .ok()
+                .and_then(|i| table.get_mut(i))
+            {
+                slot.get_or_insert(position);
+            }
+        }
+        Some(JumpTable {
+            first,
+            arms: table,
+            default,
+        })
+    }
+    // Position of the arm for an integer value, `None` when no arm matches
+    pub fn lookup(&self, value: i128) -> Option<usize> {
+        value
+            .checked_sub(self.first)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|offset| self.arms.get(offset).copied().flatten())
+            .or(self.default)
+    }
+}
+
+// Tables of the `match` expressions in the statements that have one, by the address of
+// their arms. The addresses stay valid as long as the statements are neither changed nor dropped.
+pub fn build_all(statements: &mut [AST]) -> HashMap<usize, JumpTable> {
+    let mut tables = HashMap::new();
+    walk_all(statements, &mut |value| {
+        if let ASTtypevalue::Match { arms, .. } = value {
+            if let Some(table) = JumpTable::build(arms) {
+                tables.insert(arms.as_ptr() as usize, table);
+            }
+        }
+    });
+    tables
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arms(patterns: &[ASTtypevalue]) -> Vec<(AST, AST)> {
+        patterns
+            .iter()
+            .enumerate()
+            .map(|(position, pattern)| {
+                let arm = AST::TypeValue(ASTtypevalue::I64(position as i64));
+                (AST::TypeValue(pattern.clone()), arm)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build() {
+        let dense: Vec<ASTtypevalue> = (0..8).map(ASTtypevalue::I64).collect();
+        assert!(JumpTable::build(&arms(&dense)).is_some());
+        let sparse: Vec<ASTtypevalue> = (0..8).map(|n| ASTtypevalue::I64(n * 3)).collect();
+        assert_eq!(JumpTable::build(&arms(&sparse)), None);
+        let short: Vec<ASTtypevalue> = (0..3).map(ASTtypevalue::I64).collect();
+        assert_eq!(JumpTable::build(&arms(&short)), None);
+        let mut typed = dense.clone();
+        typed[3] = ASTtypevalue::U8(3);
+        assert_eq!(JumpTable::build(&arms(&typed)), None);
+    }
+    #[test]
+    fn test_build_all() {
+        let dense: Vec<ASTtypevalue> = (0..8).map(ASTtypevalue::I64).collect();
+        let matches = |patterns: &[ASTtypevalue]| {
+            AST::TypeValue(ASTtypevalue::Match {
+                value: Box::new(AST::TypeValue(ASTtypevalue::I64(0))),
+                arms: arms(patterns),
+            })
+        };
+        let short: Vec<ASTtypevalue> = (0..3).map(ASTtypevalue::I64).collect();
+        let block = ASTtypevalue::Block {
+            statements: vec![matches(&short)],
+            value: Some(Box::new(matches(&dense))),
+        };
+        let mut statements = vec![matches(&dense), AST::TypeValue(block)];
+        let tables = build_all(&mut statements);
+        assert_eq!(tables.len(), 2);
+        for statement in &statements {
+            if let AST::TypeValue(ASTtypevalue::Match { arms, .. }) = statement {
+                assert!(tables.contains_key(&(arms.as_ptr() as usize)));
+            }
+        }
+    }
+    #[test]
+    fn test_lookup() {
+        let mut patterns: Vec<ASTtypevalue> = (-2..3).map(ASTtypevalue::I64).collect();
+        patterns.push(ASTtypevalue::I64(0));
+        patterns.push(ASTtypevalue::Discard);
+        patterns.push(ASTtypevalue::QuotedString("after".to_string()));
+        let table = JumpTable::build(&arms(&patterns)).expect("arms should be dense");
+        let cases = [(-2, 0), (0, 2), (2, 4), (-3, 6), (3, 6), (i128::MIN, 6)];
+        for (value, position) in cases {
+            assert_eq!(table.lookup(value), Some(position), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,3101 @@
+mod builtins;
+mod error;
+mod jump_table;
+mod trace;
+mod value;
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Instant;
+
+use astgen::ast::{
+    ASTOperator, ASTfunctiontype, ASTlogic, ASTstatement, ASTtypecomp, ASTtypename, ASTtypevalue,
+};
+use astgen::parser_new::Parser;
+use astgen::AST;
+use lexer::neo_tokens::{Statement, TokenType};
+
+pub use error::{EvalError, RuntimeError};
+pub use value::Value;
+
+use jump_table::JumpTable;
+
+struct Function {
+    args: Vec<ASTtypecomp>,
+    statements: Vec<AST>,
+    return_type: ASTtypename,
+    // `@memoize`, results are cached by the argument values.
+    // Only correct for functions without side effects, which is left to the user.
+    memoize: bool,
+    // Tables of the `match` expressions in `statements`, built when the function is loaded.
+    // The statements are never changed after that, so the addresses of the arms stay valid.
+    jump_tables: HashMap<usize, JumpTable>,
+}
+
+enum Flow {
+    Next,
+    Return(Value),
+    // Leave or restart the loop with the label, the innermost loop without one
+    // A `break` leaving a `loop` may carry the value of the loop
+    Break(Option<String>, Option<Value>),
+    Continue(Option<String>),
+}
+
+// Tree walking interpreter for the ASTs generated by `astgen::parser_new`
+pub struct Interpreter {
+    functions: HashMap<String, Rc<Function>>,
+    // One frame per function call, each frame is a stack of block scopes
+    frames: Vec<Vec<HashMap<String, Value>>>,
+    // Print each statement and function call to the error output before it runs
+    trace: bool,
+    // Print the scopes of the current call to the error output after each statement
+    dump_scopes: bool,
+    // Where `print` and `println` write to, stdout unless replaced
+    output: Box<dyn Write>,
+    // Where `dbg`, traces and the diagnostics of `eval_source` go, stderr unless replaced
+    error_output: Box<dyn Write>,
+    // Milliseconds returned by `time_ms()`, time since the interpreter was created unless replaced
+    clock: Box<dyn Fn() -> i64>,
+    // Reject integer and float operands instead of promoting them to a float
+    strict_types: bool,
+    // Reject calls used as statements that drop a value other than `Void`
+    strict_discard: bool,
+    // The function of each call in `frames`, for the tables of its `match` expressions
+    calls: Vec<Rc<Function>>,
+    // Results of `@memoize` functions, by the function name and the arguments
+    memos: HashMap<(String, Vec<Value>), Value>,
+    // Constants given with `--define`, the program can not declare the same names
+    defines: HashMap<String, Value>,
+}
+
+// Runs a whole program from source with a new interpreter, the full pipeline in one call.
+// An empty program is the `NoMainFunction` error like any other program without `main`.
+pub fn run_source(source: &str) -> Result<Value, EvalError> {
+    Interpreter::new().eval_source(source)
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter {
+            functions: HashMap::new(),
+            frames: Vec::new(),
+            trace: false,
+            dump_scopes: false,
+            output: Box::new(std::io::stdout()),
+            error_output: Box::new(std::io::stderr()),
+            clock: Box::new(start_clock(Instant::now())),
+            strict_types: false,
+            strict_discard: false,
+            calls: Vec::new(),
+            memos: HashMap::new(),
+            defines: HashMap::new(),
+        }
+    }
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+    pub fn set_error_output(&mut self, error_output: Box<dyn Write>) {
+        self.error_output = error_output;
+    }
+    pub fn set_clock(&mut self, clock: Box<dyn Fn() -> i64>) {
+        self.clock = clock;
+    }
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+    pub fn set_dump_scopes(&mut self, dump_scopes: bool) {
+        self.dump_scopes = dump_scopes;
+    }
+    // Block scopes of the function running now, the innermost last
+    pub fn scopes(&self) -> &[HashMap<String, Value>] {
+        self.frames.last().map_or(&[], |scopes| scopes.as_slice())
+    }
+    // Integer literals still adapt to other integer types, they have no type of their own
+    pub fn set_strict_types(&mut self, strict_types: bool) {
+        self.strict_types = strict_types;
+    }
+    // `let _ = f();` still drops the value of `f()` in strict mode
+    pub fn set_strict_discard(&mut self, strict_discard: bool) {
+        self.strict_discard = strict_discard;
+    }
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.defines.insert(name.to_string(), value);
+    }
+    // Loads every function of the program and runs `main`, returning its value.
+    // Each run starts from an empty program, only the settings and `--define`s are kept.
+    pub fn run(&mut self, program: &[AST]) -> Result<Value, RuntimeError> {
+        self.functions.clear();
+        self.frames.clear();
+        self.calls.clear();
+        self.memos.clear();
+        for ast in program {
+            self.load(ast)?;
+        }
+        match self.functions.get("main") {
+            None => return Err(RuntimeError::NoMainFunction()),
+            Some(main) if !main.args.is_empty() => return Err(RuntimeError::MainWithParameters()),
+            Some(_) => {}
+        }
+        self.call_function("main", Vec::new())
+    }
+    // Lexes, parses and runs a whole program from source, returning the value of `main`.
+    // This is the entry point for embedding, the CLI drives the stages itself.
+    // Parser warnings and the error, if there is one, are written to the error output.
+    pub fn eval_source(&mut self, source: &str) -> Result<Value, EvalError> {
+        let result = self.eval(source);
+        if let Err(error) = &result {
+            if !matches!(error, EvalError::Exit(_)) {
+                // The error is returned either way, failing to also write it changes nothing
+                let _ = writeln!(self.error_output, "{}", error);
+            }
+        }
+        result
+    }
+    // Runs one line of the REPL, keeping the variables and functions of the lines before it.
+    // A line starting a function defines it, any other line runs like the body of `main`.
+    pub fn eval_line(&mut self, line: &str) -> Result<Value, EvalError> {
+        let result = self.eval_statements(line);
+        if let Err(error) = &result {
+            if !matches!(error, EvalError::Exit(_)) {
+                let _ = writeln!(self.error_output, "{}", error);
+            }
+        }
+        result
+    }
+    fn eval(&mut self, source: &str) -> Result<Value, EvalError> {
+        let program = self.parse_source(source)?;
+        self.run(&program).map_err(EvalError::from)
+    }
+    fn eval_statements(&mut self, line: &str) -> Result<Value, EvalError> {
+        let tokens = lexer::lex(line).map_err(EvalError::Lex)?;
+        let defines_function = tokens.first().is_some_and(|token| {
+            matches!(
+                token.token_type,
+                TokenType::Attribute(_)
+                    | TokenType::Statement(Statement::Function)
+                    | TokenType::Statement(Statement::Public)
+            )
+        });
+        if defines_function {
+            for ast in self.parse_source(line)? {
+                // Typing a function again replaces the old one and forgets its results
+                if let AST::Statement(ASTstatement::Function { name, .. }) = &ast {
+                    self.functions.remove(name);
+                    self.memos.retain(|(memo, _), _| memo != name);
+                }
+                self.load(&ast)?;
+            }
+            return Ok(Value::Void);
+        }
+        // Kept on the first line, so the errors point at the line that was typed
+        let program = self.parse_source(&format!("fn main() {{ {} }}", line))?;
+        let Some(AST::Statement(ASTstatement::Function { statements, .. })) = program.first()
+        else {
+            return Ok(Value::Void);
+        };
+        // The line runs in the outermost frame, which is kept between lines
+        self.scopes_mut();
+        match self.execute_all(statements)? {
+            Flow::Return(value) => Ok(value),
+            Flow::Next | Flow::Break(..) | Flow::Continue(_) => Ok(Value::Void),
+        }
+    }
+    fn parse_source(&mut self, source: &str) -> Result<Vec<AST>, EvalError> {
+        let tokens = lexer::lex(source).map_err(EvalError::Lex)?;
+        let mut parser = Parser::new(&tokens, PathBuf::from("<source>"), source);
+        parser.set_print_errors(false);
+        parser.run().map_err(EvalError::Parse)?;
+        for warning in parser.get_warnings() {
+            writeln!(self.error_output, "Warning: {}", warning)
+                .map_err(|error| EvalError::Runtime(RuntimeError::Output(error.to_string())))?;
+        }
+        parser
+            .fold_constants()
+            .map_err(|error| EvalError::Parse(vec![error]))?;
+        Ok(parser.get_asts().clone())
+    }
+    fn load(&mut self, ast: &AST) -> Result<(), RuntimeError> {
+        match ast {
+            AST::Statement(ASTstatement::Function {
+                attributes,
+                name,
+                args,
+                statements,
+                return_type,
+                ..
+            }) => {
+                let params = args.iter().filter_map(|arg| match arg {
+                    ASTtypecomp::Argument {
+                        identifier: ASTtypevalue::Identifier(param),
+                        ..
+                    } => Some(param),
+                    _ => None,
+                });
+                if let Some(defined) = std::iter::once(name)
+                    .chain(params)
+                    .find(|name| self.defines.contains_key(name.as_str()))
+                {
+                    return Err(RuntimeError::DefinedName(defined.clone()));
+                }
+                let mut statements = statements.clone();
+                let jump_tables = jump_table::build_all(&mut statements);
+                let function = Function {
+                    args: args.clone(),
+                    statements,
+                    return_type: *return_type,
+                    memoize: attributes.iter().any(|attribute| attribute == "memoize"),
+                    jump_tables,
+                };
+                match self.functions.insert(name.clone(), Rc::new(function)) {
+                    Some(_) => Err(RuntimeError::DuplicateFunction(name.clone())),
+                    None => Ok(()),
+                }
+            }
+            other => Err(RuntimeError::UnsupportedStatement(other.to_string())),
+        }
+    }
+    fn call(&mut self, name: &str, args: &[AST]) -> Result<Value, RuntimeError> {
+        let args = args
+            .iter()
+            .map(|arg| self.evaluate(arg))
+            .collect::<Result<Vec<Value>, RuntimeError>>()?;
+        self.call_function(name, args)
+    }
+    // `target.name(args)` is `name(target, args)`, the target is evaluated first
+    fn call_method(
+        &mut self,
+        target: &AST,
+        name: &str,
+        args: &[AST],
+    ) -> Result<Value, RuntimeError> {
+        let mut values = vec![self.evaluate(target)?];
+        for arg in args {
+            values.push(self.evaluate(arg)?);
+        }
+        self.call_function(name, values)
+    }
+    fn call_function(&mut self, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let function = match (self.functions.get(name), self.variable(name)) {
+            (Some(function), _) => Rc::clone(function),
+            // `f(args)` where `f` holds a function value
+            (None, Some(Value::Function(target))) => {
+                let target = target.clone();
+                return self.call_function(&target, args);
+            }
+            (None, Some(other)) => {
+                return Err(RuntimeError::NotCallable(
+                    name.to_string(),
+                    other.type_name().to_string(),
+                ))
+            }
+            (None, None) => return self.call_builtin(name, args),
+        };
+        let args = self.fill_defaults(&function.args, args)?;
+        // Arguments of different types are different keys, `fib(3)` is not `fib(3i32)`
+        let memo = function.memoize.then(|| (name.to_string(), args.clone()));
+        if let Some(value) = memo.as_ref().and_then(|memo| self.memos.get(memo)) {
+            return Ok(value.clone());
+        }
+        self.check_signatures(name, &function.args, &args)?;
+        let scope = bind_arguments(name, &function.args, args)?;
+        self.frames.push(vec![scope]);
+        self.calls.push(Rc::clone(&function));
+        self.trace_line(&format!("enter {}", name));
+        let flow = self.execute_all(&function.statements);
+        self.trace_line(&format!("leave {}", name));
+        self.calls.pop();
+        self.frames.pop();
+        let value = match flow? {
+            Flow::Return(value) => value,
+            // The parser only accepts `break` and `continue` inside loops
+            Flow::Next | Flow::Break(..) | Flow::Continue(_) => Value::Void,
+        };
+        let value = check_return(name, function.return_type, value)?;
+        if let Some(memo) = memo {
+            self.memos.insert(memo, value.clone());
+        }
+        Ok(value)
+    }
+    // A function passed to a `fn(...) -> T` parameter must declare the same signature
+    fn check_signatures(
+        &self,
+        name: &str,
+        params: &[ASTtypecomp],
+        args: &[Value],
+    ) -> Result<(), RuntimeError> {
+        for (position, (param, value)) in params.iter().zip(args).enumerate() {
+            let (
+                ASTtypecomp::Argument {
+                    signature: Some(expected),
+                    ..
+                },
+                Value::Function(target),
+            ) = (param, value)
+            else {
+                continue;
+            };
+            // Builtins do not declare a signature to compare against
+            let Some(function) = self.functions.get(target) else {
+                continue;
+            };
+            let found = ASTfunctiontype {
+                params: function
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        ASTtypecomp::Argument { type_name, .. } => Some(*type_name),
+                        _ => None,
+                    })
+                    .collect(),
+                return_type: function.return_type,
+            };
+            if found != *expected {
+                return Err(RuntimeError::MismatchedArgumentType(
+                    position + 1,
+                    name.to_string(),
+                    value::signature_label(expected),
+                    value::signature_label(&found),
+                ));
+            }
+        }
+        Ok(())
+    }
+    // Appends the default values of the omitted trailing parameters
+    fn fill_defaults(
+        &mut self,
+        params: &[ASTtypecomp],
+        mut args: Vec<Value>,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let count = args.len();
+        if count > params.len() {
+            return Err(RuntimeError::MismatchedArgumentCount(params.len(), count));
+        }
+        for param in &params[count..] {
+            match param {
+                ASTtypecomp::Argument {
+                    default: Some(default),
+                    ..
+                } => args.push(self.evaluate(default)?),
+                _ => return Err(RuntimeError::MismatchedArgumentCount(params.len(), count)),
+            }
+        }
+        Ok(args)
+    }
+    // Trace lines are indented by the depth of the call stack
+    fn trace_line(&mut self, line: &str) {
+        if self.trace {
+            let depth = self.frames.len().saturating_sub(1);
+            // Like `eprintln`, a trace that can not be written does not stop the program
+            let _ = writeln!(self.error_output, "[trace] {}{}", "  ".repeat(depth), line);
+        }
+    }
+
+    fn execute_block(&mut self, statements: &[AST]) -> Result<Flow, RuntimeError> {
+        self.scopes_mut().push(HashMap::new());
+        let flow = self.execute_all(statements);
+        self.scopes_mut().pop();
+        flow
+    }
+    fn execute_all(&mut self, statements: &[AST]) -> Result<Flow, RuntimeError> {
+        for statement in statements {
+            let flow = self.execute(statement)?;
+            if self.dump_scopes {
+                self.dump_scopes(statement);
+            }
+            match flow {
+                Flow::Next => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Next)
+    }
+    // One line per scope with its variables sorted by name, so the dump is the same every run
+    fn dump_scopes(&mut self, statement: &AST) {
+        let mut lines = vec![format!("[scopes] after {}", trace::describe(statement))];
+        for (depth, scope) in self.scopes().iter().enumerate() {
+            let mut variables = scope
+                .iter()
+                .map(|(name, value)| format!("{} = {}", name, value.literal()))
+                .collect::<Vec<_>>();
+            variables.sort();
+            lines.push(format!("[scopes]   {}: {}", depth, variables.join(", ")));
+        }
+        // Like traces, a dump that can not be written does not stop the program
+        let _ = writeln!(self.error_output, "{}", lines.join("\n"));
+    }
+    fn execute(&mut self, ast: &AST) -> Result<Flow, RuntimeError> {
+        if self.trace {
+            self.trace_line(&trace::describe(ast));
+        }
+        match ast {
+            AST::Statement(statement) => self.execute_statement(statement),
+            // Only a call used as a statement may return `Void`
+            AST::TypeValue(ASTtypevalue::FunctionCall { name, args }) => {
+                let value = self.call(name, args)?;
+                self.discard(name, value)
+            }
+            AST::TypeValue(ASTtypevalue::MethodCall { target, name, args }) => {
+                let value = self.call_method(target, name, args)?;
+                self.discard(name, value)
+            }
+            AST::TypeValue(ASTtypevalue::Match { value, arms }) => {
+                let arm = self.match_arm(value, arms)?;
+                self.execute(arm)
+            }
+            // The value of a `loop` statement is dropped, a `return` in it leaves the function
+            AST::TypeValue(ASTtypevalue::Loop { label, statements }) => {
+                Ok(self.execute_forever(label, statements)?.0)
+            }
+            other => {
+                self.evaluate(other)?;
+                Ok(Flow::Next)
+            }
+        }
+    }
+    fn execute_statement(&mut self, statement: &ASTstatement) -> Result<Flow, RuntimeError> {
+        match statement {
+            ASTstatement::Let {
+                name,
+                type_name,
+                value,
+            } => {
+                let value = match (value, type_name) {
+                    (Some(value), Some(type_name)) => self.evaluate(value)?.cast(*type_name)?,
+                    (Some(value), None) => self.evaluate(value)?,
+                    (None, type_name) => Value::Uninitialized(*type_name),
+                };
+                if name != "_" {
+                    self.declare(name, value)?;
+                }
+            }
+            ASTstatement::Declare { name, value } => {
+                let value = self.evaluate(value)?;
+                let scope = self.scopes_mut().last_mut();
+                if scope.is_some_and(|scope| scope.contains_key(name)) {
+                    return Err(RuntimeError::AlreadyDeclared(name.clone()));
+                }
+                self.declare(name, value)?;
+            }
+            ASTstatement::Destructure { names, value } => self.destructure(names, value)?,
+            ASTstatement::Assignment { left, op, right } => {
+                self.assign(left, op, right)?;
+            }
+            ASTstatement::If {
+                condition,
+                statements,
+                elif,
+                else_statements,
+            } => {
+                return self.execute_if(condition, statements, elif, else_statements);
+            }
+            ASTstatement::For { .. }
+            | ASTstatement::While { .. }
+            | ASTstatement::Break { .. }
+            | ASTstatement::Continue { .. } => return self.execute_loop(statement),
+            ASTstatement::Print { value, args } => {
+                let text = self.format(value, args)?;
+                self.write_output(&text)?;
+            }
+            ASTstatement::Println { value, args } => {
+                let text = self.format(value, args)? + "\n";
+                self.write_output(&text)?;
+            }
+            ASTstatement::Return { value } => return Ok(Flow::Return(self.evaluate(value)?)),
+            other => return Err(RuntimeError::UnsupportedStatement(other.to_string())),
+        }
+        Ok(Flow::Next)
+    }
+    fn discard(&self, name: &str, value: Value) -> Result<Flow, RuntimeError> {
+        match value {
+            Value::Void => Ok(Flow::Next),
+            value if self.strict_discard => Err(RuntimeError::DiscardedValue(
+                name.to_string(),
+                value.type_name().to_string(),
+            )),
+            _ => Ok(Flow::Next),
+        }
+    }
+    fn execute_loop(&mut self, statement: &ASTstatement) -> Result<Flow, RuntimeError> {
+        match statement {
+            ASTstatement::For {
+                label,
+                start,
+                end,
+                value,
+                statements,
+            } => self.execute_for(label, start, end, value, statements),
+            ASTstatement::While {
+                label,
+                condition,
+                statements,
+            } => self.execute_while(label, condition, statements),
+            ASTstatement::Break { label, value } => {
+                let value = value
+                    .as_deref()
+                    .map(|value| self.evaluate(value))
+                    .transpose()?;
+                Ok(Flow::Break(label.clone(), value))
+            }
+            ASTstatement::Continue { label } => Ok(Flow::Continue(label.clone())),
+            other => Err(RuntimeError::UnsupportedStatement(other.to_string())),
+        }
+    }
+    fn execute_if(
+        &mut self,
+        condition: &AST,
+        statements: &[AST],
+        elif: &Option<Box<AST>>,
+        else_statements: &Option<Vec<AST>>,
+    ) -> Result<Flow, RuntimeError> {
+        if let AST::TypeValue(ASTtypevalue::SomePattern { name, value }) = condition {
+            // The name is bound in a scope around the block, it is not visible in the `else`
+            match self.evaluate(value)? {
+                Value::None => {}
+                value => {
+                    self.scopes_mut().push(HashMap::new());
+                    let flow = self
+                        .declare(name, value)
+                        .and_then(|()| self.execute_block(statements));
+                    self.scopes_mut().pop();
+                    return flow;
+                }
+            }
+        } else if self.condition(condition)? {
+            return self.execute_block(statements);
+        }
+        match (elif, else_statements) {
+            (Some(elif), _) => self.execute(elif),
+            (None, Some(else_statements)) => self.execute_block(else_statements),
+            (None, None) => Ok(Flow::Next),
+        }
+    }
+    // Follows the JIT: the body runs while `start < end`, then `start` is increased by `step`
+    fn execute_for(
+        &mut self,
+        label: &Option<String>,
+        start: &ASTtypevalue,
+        end: &ASTtypevalue,
+        step: &ASTtypevalue,
+        statements: &[AST],
+    ) -> Result<Flow, RuntimeError> {
+        let name = match start {
+            ASTtypevalue::Identifier(name) => name,
+            other => return Err(RuntimeError::InvalidAssignment(other.to_string())),
+        };
+        let end = self.evaluate_value(end)?;
+        let step = self.evaluate_value(step)?;
+        // The loop variable is bound in a scope around the loop, starting from the value of the
+        // variable of the same name or from zero. It is no longer bound however the loop ends.
+        let start = self.variable(name).cloned().unwrap_or(Value::I64(0));
+        self.scopes_mut().push(HashMap::new());
+        let flow = self
+            .declare(name, start)
+            .and_then(|()| self.execute_range(label, name, &end, &step, statements));
+        self.scopes_mut().pop();
+        flow
+    }
+    fn execute_range(
+        &mut self,
+        label: &Option<String>,
+        name: &str,
+        end: &Value,
+        step: &Value,
+        statements: &[AST],
+    ) -> Result<Flow, RuntimeError> {
+        while self.lookup(name)?.binary(&ASTOperator::Less, end)? == Value::Bool(true) {
+            if let Some(flow) = after_body(self.execute_block(statements)?, label) {
+                return Ok(flow);
+            }
+            *self.slot(name)? = self.lookup(name)?.binary(&ASTOperator::Add, step)?;
+        }
+        Ok(Flow::Next)
+    }
+    fn execute_while(
+        &mut self,
+        label: &Option<String>,
+        condition: &AST,
+        statements: &[AST],
+    ) -> Result<Flow, RuntimeError> {
+        while self.condition(condition)? {
+            if let Some(flow) = after_body(self.execute_block(statements)?, label) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+    // Runs the body until a `break` leaves the loop, with the value of the `break`
+    // Any other way out of the loop is returned as the flow, with a `Void` value
+    fn execute_forever(
+        &mut self,
+        label: &Option<String>,
+        statements: &[AST],
+    ) -> Result<(Flow, Value), RuntimeError> {
+        let targets = |target: &Option<String>| target.is_none() || target == label;
+        loop {
+            match self.execute_block(statements)? {
+                Flow::Next => {}
+                Flow::Continue(target) if targets(&target) => {}
+                Flow::Break(target, value) if targets(&target) => {
+                    return Ok((Flow::Next, value.unwrap_or(Value::Void)))
+                }
+                flow => return Ok((flow, Value::Void)),
+            }
+        }
+    }
+    // Returns the value that was stored, the value of `b = 5` in `a = b = 5`
+    fn assign(&mut self, left: &AST, op: &ASTOperator, right: &AST) -> Result<Value, RuntimeError> {
+        let value = self.evaluate(right)?;
+        let (name, path) = self.place(left)?;
+        // Plain assignment may create a missing map key, compound ones need it to exist
+        let insert = *op == ASTOperator::Assign;
+        let strict_types = self.strict_types;
+        let mut slot = self.slot(&name)?;
+        if matches!(slot, Value::Uninitialized(_)) && (!insert || !path.is_empty()) {
+            return Err(RuntimeError::UninitializedVariable(name));
+        }
+        for key in &path {
+            slot = slot.index_mut(key, insert)?;
+        }
+        let value = match compound_operator(op) {
+            Some(op) if strict_types && slot.mixes_numbers(&value) => {
+                return Err(slot.invalid_operands(&op, &value))
+            }
+            Some(op) => slot.binary(&op, &value)?,
+            None if strict_types && path.is_empty() && slot.mixes_numbers(&value) => {
+                return Err(RuntimeError::AssignmentMismatch(
+                    name,
+                    slot.type_name().to_string(),
+                    value.type_name().to_string(),
+                ))
+            }
+            None => value,
+        };
+        // A variable keeps its type, the elements of arrays and maps are not typed
+        *slot = match path.is_empty() {
+            true => value.assign_to(&name, slot)?,
+            false => value,
+        };
+        Ok(slot.clone())
+    }
+    // Resolves the left side of an assignment to a variable and the keys indexing into it
+    fn place(&mut self, left: &AST) -> Result<(String, Vec<Value>), RuntimeError> {
+        match left {
+            AST::TypeValue(ASTtypevalue::Identifier(name)) => Ok((name.clone(), Vec::new())),
+            AST::TypeValue(ASTtypevalue::Index { target, index }) => {
+                let (name, mut path) = self.place(target)?;
+                path.push(self.evaluate(index)?);
+                Ok((name, path))
+            }
+            other => Err(RuntimeError::InvalidAssignment(other.to_string())),
+        }
+    }
+    // Replaces each `{}` or `{:spec}` of the format string with the next argument
+    fn format(&mut self, value: &AST, args: &[AST]) -> Result<String, RuntimeError> {
+        let template = self.evaluate(value)?.to_string();
+        // The parser only counts the placeholders of a literal, `"{}" + "{}"` is counted here
+        let placeholders = template.matches("{}").count() + template.matches("{:").count();
+        if placeholders != args.len() {
+            return Err(RuntimeError::MismatchedArgumentCount(placeholders, args.len()));
+        }
+        let mut args = args.iter();
+        let mut output = String::new();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find("{}").into_iter().chain(rest.find("{:")).min() {
+            output.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| RuntimeError::InvalidFormatSpec(rest[start + 2..].to_string()))?;
+            let Some(arg) = args.next() else {
+                break;
+            };
+            let value = self.evaluate(arg)?;
+            let spec = rest[start + 1..end].strip_prefix(':').unwrap_or_default();
+            output.push_str(&pad(&value, spec)?);
+            rest = &rest[end + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    // Builtins that need the interpreter, the others are in `builtins`
+    fn call_builtin(&mut self, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        match name {
+            "time_ms" => self.time_ms(args),
+            "map" => self.map(args),
+            "filter" => self.filter(args),
+            "reduce" => self.reduce(args),
+            "sort_by" => self.sort_by(args),
+            "exit" => self.exit(args),
+            _ => builtins::call(name, args),
+        }
+    }
+    // map(array, f), the results of calling `f` on each element
+    fn map(&mut self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let [array, function] = <[Value; 2]>::try_from(args)
+            .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+        let values = builtins::array_argument("map", array)?;
+        let function = builtins::function_argument("map", function)?;
+        values
+            .into_iter()
+            .map(|value| self.call_value(&function, vec![value]))
+            .collect::<Result<Vec<Value>, RuntimeError>>()
+            .map(Value::Array)
+    }
+    // filter(array, pred), the elements for which `pred` returns true, in order
+    fn filter(&mut self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let [array, predicate] = <[Value; 2]>::try_from(args)
+            .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+        let values = builtins::array_argument("filter", array)?;
+        let predicate = builtins::function_argument("filter", predicate)?;
+        let mut kept = Vec::new();
+        for value in values {
+            match self.call_value(&predicate, vec![value.clone()])? {
+                Value::Bool(true) => kept.push(value),
+                Value::Bool(false) => {}
+                other => {
+                    return Err(RuntimeError::InvalidArgument(
+                        "filter".to_string(),
+                        format!(
+                            "`{}` returned {}, expected Bool",
+                            predicate,
+                            other.type_name()
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(Value::Array(kept))
+    }
+    // reduce(array, f, init), folds the elements from the left with `f(accumulator, element)`
+    fn reduce(&mut self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let [array, function, init] = <[Value; 3]>::try_from(args)
+            .map_err(|args| RuntimeError::MismatchedArgumentCount(3, args.len()))?;
+        let values = builtins::array_argument("reduce", array)?;
+        let function = builtins::function_argument("reduce", function)?;
+        values.into_iter().try_fold(init, |accumulator, value| {
+            self.call_value(&function, vec![accumulator, value])
+        })
+    }
+    // sort_by(array, cmp), a sorted copy where `cmp(a, b)` returns -1, 0 or 1 like `a - b`
+    fn sort_by(&mut self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let [array, function] = <[Value; 2]>::try_from(args)
+            .map_err(|args| RuntimeError::MismatchedArgumentCount(2, args.len()))?;
+        let values = builtins::array_argument("sort_by", array)?;
+        let function = builtins::function_argument("sort_by", function)?;
+        let mut compare = |a: &Value, b: &Value| {
+            let result = self.call_value(&function, vec![a.clone(), b.clone()])?;
+            result.as_integer().map(|n| n.cmp(&0)).ok_or_else(|| {
+                RuntimeError::InvalidArgument(
+                    "sort_by".to_string(),
+                    format!("`{}` returned {}, expected an integer", function, result.type_name()),
+                )
+            })
+        };
+        builtins::sort_values(values, &mut compare).map(Value::Array)
+    }
+    // Calls a function for its value, a `Void` result is an error
+    fn call_value(&mut self, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        match self.call_function(name, args)? {
+            Value::Void => Err(RuntimeError::VoidValue()),
+            value => Ok(value),
+        }
+    }
+    fn time_ms(&self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        if !args.is_empty() {
+            return Err(RuntimeError::MismatchedArgumentCount(0, args.len()));
+        }
+        Ok(Value::I64((self.clock)()))
+    }
+    // exit(code), output written so far is flushed before the program unwinds
+    fn exit(&mut self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let [code] = <[Value; 1]>::try_from(args)
+            .map_err(|args| RuntimeError::MismatchedArgumentCount(1, args.len()))?;
+        let code = code
+            .as_integer()
+            .and_then(|code| i32::try_from(code).ok())
+            .ok_or_else(|| {
+                RuntimeError::InvalidArgument(
+                    "exit".to_string(),
+                    format!("expected an i32 exit code, found {}", code),
+                )
+            })?;
+        self.output
+            .flush()
+            .map_err(|e| RuntimeError::Output(e.to_string()))?;
+        Err(RuntimeError::Exit(code))
+    }
+    fn destructure(&mut self, names: &[String], value: &AST) -> Result<(), RuntimeError> {
+        let values = match self.evaluate(value)? {
+            Value::Tuple(values) => values,
+            other => {
+                return Err(RuntimeError::MismatchedTypes(
+                    "Tuple".to_string(),
+                    other.type_name().to_string(),
+                ))
+            }
+        };
+        if values.len() != names.len() {
+            return Err(RuntimeError::DestructureArity(names.len(), values.len()));
+        }
+        for (name, value) in names.iter().zip(values) {
+            if name != "_" {
+                self.declare(name, value)?;
+            }
+        }
+        Ok(())
+    }
+    fn write_output(&mut self, text: &str) -> Result<(), RuntimeError> {
+        self.output
+            .write_all(text.as_bytes())
+            .map_err(|e| RuntimeError::Output(e.to_string()))
+    }
+
+    fn evaluate(&mut self, ast: &AST) -> Result<Value, RuntimeError> {
+        match ast {
+            AST::TypeValue(value) => self.evaluate_value(value),
+            AST::Logic(ASTlogic::BinaryOperation { left, op, right }) => {
+                self.evaluate_binary(left, op, right)
+            }
+            AST::Statement(ASTstatement::Assignment { left, op, right }) => {
+                self.assign(left, op, right)
+            }
+            other => Err(RuntimeError::UnsupportedStatement(other.to_string())),
+        }
+    }
+    fn evaluate_value(&mut self, value: &ASTtypevalue) -> Result<Value, RuntimeError> {
+        match value {
+            ASTtypevalue::Identifier(name) => self.lookup(name),
+            ASTtypevalue::Array(values) => values
+                .iter()
+                .map(|value| self.evaluate_value(value))
+                .collect::<Result<Vec<Value>, RuntimeError>>()
+                .map(Value::Array),
+            ASTtypevalue::Map(entries) => self.evaluate_map(entries),
+            ASTtypevalue::Tuple(values) => values
+                .iter()
+                .map(|value| self.evaluate(value))
+                .collect::<Result<Vec<Value>, RuntimeError>>()
+                .map(Value::Tuple),
+            ASTtypevalue::Index { target, index } => {
+                let target = self.evaluate(target)?;
+                target.index(&self.evaluate(index)?)
+            }
+            ASTtypevalue::FunctionCall { name, args } => match self.call(name, args)? {
+                Value::Void => Err(RuntimeError::VoidValue()),
+                value => Ok(value),
+            },
+            ASTtypevalue::Field { target, name } => self.evaluate(target)?.field(name),
+            ASTtypevalue::MethodCall { target, name, args } => {
+                match self.call_method(target, name, args)? {
+                    Value::Void => Err(RuntimeError::VoidValue()),
+                    value => Ok(value),
+                }
+            }
+            ASTtypevalue::Match { value, arms } => {
+                let arm = self.match_arm(value, arms)?;
+                self.evaluate(arm)
+            }
+            ASTtypevalue::Loop { label, statements } => {
+                let (flow, value) = self.execute_forever(label, statements)?;
+                leave_expression(flow)?;
+                Ok(value)
+            }
+            ASTtypevalue::Block { statements, value } => {
+                self.scopes_mut().push(HashMap::new());
+                let value = self.block_value(statements, value.as_deref());
+                self.scopes_mut().pop();
+                match value? {
+                    Value::Void => Err(RuntimeError::VoidBlock()),
+                    value => Ok(value),
+                }
+            }
+            ASTtypevalue::Panic { message, line } => Err(RuntimeError::Panic(
+                self.evaluate(message)?.to_string(),
+                *line,
+            )),
+            ASTtypevalue::Debug { value, source } => {
+                let value = self.evaluate(value)?;
+                writeln!(self.error_output, "{} = {}", source, value)
+                    .map_err(|e| RuntimeError::Output(e.to_string()))?;
+                Ok(value)
+            }
+            literal => Ok(literal_value(literal)),
+        }
+    }
+    fn block_value(
+        &mut self,
+        statements: &[AST],
+        value: Option<&AST>,
+    ) -> Result<Value, RuntimeError> {
+        let flow = self.execute_all(statements)?;
+        leave_expression(flow)?;
+        match value {
+            Some(value) => self.evaluate(value),
+            None => Ok(Value::Void),
+        }
+    }
+    // The first arm whose pattern equals the value, patterns of another type never match
+    fn match_arm<'a>(
+        &mut self,
+        value: &AST,
+        arms: &'a [(AST, AST)],
+    ) -> Result<&'a AST, RuntimeError> {
+        let value = self.evaluate(value)?;
+        // Only arms in the statements of the running function have a table, the arms of
+        // default arguments and of REPL lines are always scanned
+        if let Some(n) = value.as_integer() {
+            let key = arms.as_ptr() as usize;
+            let table = self.calls.last().and_then(|function| function.jump_tables.get(&key));
+            if let Some(table) = table {
+                return match table.lookup(n).and_then(|position| arms.get(position)) {
+                    Some((_, arm)) => Ok(arm),
+                    None => Err(RuntimeError::NoMatchingArm(value.to_string())),
+                };
+            }
+        }
+        for (pattern, arm) in arms {
+            if *pattern == AST::TypeValue(ASTtypevalue::Discard) {
+                return Ok(arm);
+            }
+            let pattern = self.evaluate(pattern)?;
+            if value.binary(&ASTOperator::Equals, &pattern) == Ok(Value::Bool(true)) {
+                return Ok(arm);
+            }
+        }
+        Err(RuntimeError::NoMatchingArm(value.to_string()))
+    }
+    fn evaluate_map(&mut self, entries: &[(AST, AST)]) -> Result<Value, RuntimeError> {
+        let mut map = Value::Map(Vec::new());
+        for (key, value) in entries {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            *map.index_mut(&key, true)? = value;
+        }
+        Ok(map)
+    }
+    fn evaluate_binary(
+        &mut self,
+        left: &AST,
+        op: &ASTOperator,
+        right: &AST,
+    ) -> Result<Value, RuntimeError> {
+        match op {
+            // `&&` and `||` only evaluate the right side when needed
+            ASTOperator::And | ASTOperator::Or => {
+                let left = self.condition(left)?;
+                if left == (*op == ASTOperator::Or) {
+                    return Ok(Value::Bool(left));
+                }
+                Ok(Value::Bool(self.condition(right)?))
+            }
+            _ => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                if self.strict_types && left.mixes_numbers(&right) {
+                    return Err(left.invalid_operands(op, &right));
+                }
+                left.binary(op, &right)
+            }
+        }
+    }
+    fn condition(&mut self, ast: &AST) -> Result<bool, RuntimeError> {
+        match self.evaluate(ast)? {
+            Value::Bool(value) => Ok(value),
+            other => Err(RuntimeError::InvalidCondition(
+                other.type_name().to_string(),
+            )),
+        }
+    }
+
+    fn scopes_mut(&mut self) -> &mut Vec<HashMap<String, Value>> {
+        if self.frames.is_empty() {
+            self.frames.push(vec![HashMap::new()]);
+        }
+        let last = self.frames.len() - 1;
+        &mut self.frames[last]
+    }
+    fn declare(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
+        if self.defines.contains_key(name) {
+            return Err(RuntimeError::DefinedName(name.to_string()));
+        }
+        if let Some(scope) = self.scopes_mut().last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+        Ok(())
+    }
+    // Reading a variable copies its value, which is what gives values copy semantics
+    // Variables shadow the constants, which shadow the functions of the program
+    fn lookup(&self, name: &str) -> Result<Value, RuntimeError> {
+        match self
+            .variable(name)
+            .or_else(|| self.defines.get(name))
+            .cloned()
+            .or_else(|| builtins::constant(name))
+        {
+            Some(Value::Uninitialized(_)) => {
+                Err(RuntimeError::UninitializedVariable(name.to_string()))
+            }
+            Some(value) => Ok(value),
+            None if self.functions.contains_key(name) => Ok(Value::Function(name.to_string())),
+            None => Err(RuntimeError::UndefinedVariable(name.to_string())),
+        }
+    }
+    fn variable(&self, name: &str) -> Option<&Value> {
+        self.frames
+            .last()
+            .and_then(|scopes| scopes.iter().rev().find_map(|scope| scope.get(name)))
+    }
+    fn slot(&mut self, name: &str) -> Result<&mut Value, RuntimeError> {
+        self.scopes_mut()
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or_else(|| RuntimeError::UndefinedVariable(name.to_string()))
+    }
+}
+
+// Checks each argument against the declared parameter type before the body runs
+fn bind_arguments(
+    name: &str,
+    params: &[ASTtypecomp],
+    args: Vec<Value>,
+) -> Result<HashMap<String, Value>, RuntimeError> {
+    let mut scope = HashMap::new();
+    for (position, (param, value)) in params.iter().zip(args).enumerate() {
+        if let ASTtypecomp::Argument {
+            type_name,
+            identifier: ASTtypevalue::Identifier(ident),
+            ..
+        } = param
+        {
+            let value = value.cast(*type_name).map_err(|error| match error {
+                RuntimeError::MismatchedTypes(expected, found) => {
+                    RuntimeError::MismatchedArgumentType(
+                        position + 1,
+                        name.to_string(),
+                        expected,
+                        found,
+                    )
+                }
+                error => error,
+            })?;
+            scope.insert(ident.clone(), value);
+        }
+    }
+    Ok(scope)
+}
+// `return;` is only valid in `Void` functions and `return expr;` only in the others
+// Expressions can not return from the function or jump to a loop outside of them
+fn leave_expression(flow: Flow) -> Result<(), RuntimeError> {
+    match flow {
+        Flow::Next => Ok(()),
+        Flow::Return(_) => Err(RuntimeError::ReturnInBlock()),
+        Flow::Break(..) => Err(RuntimeError::LoopControlInBlock("break".to_string())),
+        Flow::Continue(_) => Err(RuntimeError::LoopControlInBlock("continue".to_string())),
+    }
+}
+// What a loop does after its body ran with `flow`, `None` to go on with the next iteration
+fn after_body(flow: Flow, label: &Option<String>) -> Option<Flow> {
+    let targets = |target: &Option<String>| target.is_none() || target == label;
+    match flow {
+        Flow::Next => None,
+        Flow::Continue(target) if targets(&target) => None,
+        Flow::Break(target, _) if targets(&target) => Some(Flow::Next),
+        flow => Some(flow),
+    }
+}
+fn check_return(name: &str, return_type: ASTtypename, value: Value) -> Result<Value, RuntimeError> {
+    match (return_type, value) {
+        (ASTtypename::TypeVoid, Value::Void) => Ok(Value::Void),
+        (ASTtypename::TypeVoid, _) => {
+            Err(RuntimeError::ReturnValueInVoidFunction(name.to_string()))
+        }
+        (return_type, Value::Void) => Err(RuntimeError::MissingReturnValue(
+            name.to_string(),
+            value::type_label(return_type).to_string(),
+        )),
+        (return_type, value) => value.cast(return_type),
+    }
+}
+// `{:>8}`, `{:<8}` and `{:^8}` pad the value with spaces to the width, without an alignment
+// numbers go to the right and everything else to the left
+// A trailing `,` as in `{:,}` or `{:>12,}` groups the digits of a number in thousands
+fn pad(value: &Value, spec: &str) -> Result<String, RuntimeError> {
+    let is_number = value.as_float().is_some();
+    let (width_spec, text) = match spec.strip_suffix(',') {
+        Some(_) if !is_number => {
+            return Err(RuntimeError::MismatchedTypes(
+                "number".to_string(),
+                value.type_name().to_string(),
+            ))
+        }
+        Some(width_spec) => (width_spec, group_thousands(&value.to_string())),
+        None => (spec, value.to_string()),
+    };
+    if width_spec.is_empty() {
+        return Ok(text);
+    }
+    let (align, width) = match width_spec.chars().next() {
+        Some(align @ ('<' | '>' | '^')) => (align, &width_spec[1..]),
+        _ if is_number => ('>', width_spec),
+        _ => ('<', width_spec),
+    };
+    let width: usize = width
+        .parse()
+        .map_err(|_| RuntimeError::InvalidFormatSpec(spec.to_string()))?;
+    let padding = width.saturating_sub(text.chars().count());
+    let (left, right) = match align {
+        '<' => (0, padding),
+        '>' => (padding, 0),
+        _ => (padding / 2, padding - padding / 2),
+    };
+    Ok(format!("{}{}{}", " ".repeat(left), text, " ".repeat(right)))
+}
+// `-1234567.5` => `-1,234,567.5`, only the digits before the point are grouped
+fn group_thousands(number: &str) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", number),
+    };
+    let (integer, fraction) = digits.split_at(digits.find('.').unwrap_or(digits.len()));
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+fn literal_value(value: &ASTtypevalue) -> Value {
+    match value {
+        ASTtypevalue::I8(n) => Value::I8(*n),
+        ASTtypevalue::I16(n) => Value::I16(*n),
+        ASTtypevalue::I32(n) => Value::I32(*n),
+        ASTtypevalue::I64(n) => Value::I64(*n),
+        ASTtypevalue::U8(n) => Value::U8(*n),
+        ASTtypevalue::U16(n) => Value::U16(*n),
+        ASTtypevalue::U32(n) => Value::U32(*n),
+        ASTtypevalue::U64(n) => Value::U64(*n),
+        ASTtypevalue::None => Value::None,
+        ASTtypevalue::Bool(b) => Value::Bool(*b),
+        ASTtypevalue::QuotedString(s) => Value::QuotedString(s.clone()),
+        ASTtypevalue::Char(c) => Value::Char(*c),
+        ASTtypevalue::Bytes(bytes) => Value::Bytes(bytes.clone()),
+        _ => Value::Void,
+    }
+}
+fn compound_operator(op: &ASTOperator) -> Option<ASTOperator> {
+    match op {
+        ASTOperator::AddAssign => Some(ASTOperator::Add),
+        ASTOperator::SubAssign => Some(ASTOperator::Subtract),
+        ASTOperator::MulAssign => Some(ASTOperator::Multiply),
+        ASTOperator::DivAssign => Some(ASTOperator::Divide),
+        ASTOperator::RemAssign => Some(ASTOperator::Remainder),
+        ASTOperator::BitAndAssign => Some(ASTOperator::BitAnd),
+        ASTOperator::BitOrAssign => Some(ASTOperator::BitOr),
+        ASTOperator::BitXorAssign => Some(ASTOperator::BitXor),
+        _ => None,
+    }
+}
+
+fn start_clock(start: Instant) -> impl Fn() -> i64 {
+    move || i64::try_from(start.elapsed().as_millis()).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lexer::lex_new_new::Lexer;
+    use std::cell::RefCell;
+
+    fn run(code: &str) -> Result<Value, RuntimeError> {
+        run_with(code, &mut Interpreter::new())
+    }
+    fn run_with(code: &str, interpreter: &mut Interpreter) -> Result<Value, RuntimeError> {
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        interpreter.run(parser.get_asts())
+    }
+    // Output buffer that can still be read after the interpreter took ownership of it
+    #[derive(Clone, Default)]
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    impl SharedOutput {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).expect("output should be UTF-8")
+        }
+    }
+
+    #[test]
+    fn test_dbg() {
+        let code = r#"fn main() -> i64 {
+            let a = dbg(2 + 3) * 2;
+            dbg(a);
+            return dbg(( a - 1 ) * 2) + 1;
+        }"#;
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_error_output(Box::new(errors.clone()));
+        assert_eq!(run_with(code, &mut interpreter), Ok(Value::I64(19)));
+        assert_eq!(errors.text(), "2 + 3 = 5\na = 10\n( a - 1 ) * 2 = 18\n");
+    }
+
+    #[test]
+    fn test_recursive_function() {
+        let code = r#"fn fib(i64:n) -> i64 {
+            if (n < 2) {
+                return n;
+            }
+            return fib(n - 1) + fib(n - 2);
+        }
+        fn main() -> i64 {
+            return fib(20);
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(6765)));
+    }
+    #[test]
+    fn test_integer_overflow() {
+        let code = r#"fn main() -> i8 {
+            let:i8 a = 100;
+            a += 100;
+            return a;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::IntegerOverflow("i8".to_string()))
+        );
+    }
+    #[test]
+    fn test_function_values() {
+        let code = r#"fn add(a: i64, b: i64) -> i64 {
+            return a + b;
+        }
+        fn double(n: i64) -> i64 {
+            return n * 2;
+        }
+        fn main() -> i64 {
+            let f = add;
+            let g = f;
+            let doubled = map(range(1, 4, 1), double);
+            return g(1, 2) + doubled[2];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(9)));
+        let code = r#"fn double(n: i64) -> i64 {
+            return n * 2;
+        }
+        fn main() -> Array {
+            return map(range(0, 3, 1), double);
+        }"#;
+        let expected = vec![Value::I64(0), Value::I64(2), Value::I64(4)];
+        assert_eq!(run(code), Ok(Value::Array(expected)));
+        let code = r#"fn main() -> i64 {
+            let f = 5;
+            return f(1);
+        }"#;
+        let error = RuntimeError::NotCallable("f".to_string(), "i64".to_string());
+        assert_eq!(error.to_string(), "`f` is i64, not a function");
+        assert_eq!(run(code), Err(error));
+        assert_eq!(Value::Function("add".to_string()).to_string(), "fn add");
+    }
+    #[test]
+    fn test_function_type_arguments() {
+        let functions = r#"fn apply(f: fn(i32) -> i32, x: i32) -> i32 {
+            return f(x);
+        }
+        fn double(n: i32) -> i32 {
+            return n * 2;
+        }
+        fn add(a: i32, b: i32) -> i32 {
+            return a + b;
+        }
+        fn wide(n: i64) -> i64 {
+            return n;
+        }"#;
+        let cases = [
+            ("apply(double, 5)", Ok(Value::I32(10))),
+            ("apply(add, 5)", Err(("fn(i32) -> i32", "fn(i32, i32) -> i32"))),
+            ("apply(wide, 5)", Err(("fn(i32) -> i32", "fn(i64) -> i64"))),
+            ("apply(5, 5)", Err(("Function", "i64"))),
+        ];
+        for (call, expected) in cases {
+            let code = format!("{}\nfn main() -> i32 {{\n    return {};\n}}", functions, call);
+            let expected = expected.map_err(|(expected, found)| {
+                let (apply, expected) = ("apply".to_string(), expected.to_string());
+                RuntimeError::MismatchedArgumentType(1, apply, expected, found.to_string())
+            });
+            assert_eq!(run(&code), expected, "{}", call);
+        }
+    }
+    #[test]
+    fn test_map_filter_reduce() {
+        let functions = r#"fn double(n: i64) -> i64 {
+            return n * 2;
+        }
+        fn is_even(n: i64) -> Bool {
+            return n % 2 == 0;
+        }
+        fn add(a: i64, b: i64) -> i64 {
+            return a + b;
+        }
+        fn main() -> "#;
+        let array = |values: &[i64]| Value::Array(values.iter().map(|n| Value::I64(*n)).collect());
+        let cases = [
+            (
+                "Array",
+                "return map(range(1, 5, 1), double);",
+                Ok(array(&[2, 4, 6, 8])),
+            ),
+            (
+                "Array",
+                "return filter(range(1, 7, 1), is_even);",
+                Ok(array(&[2, 4, 6])),
+            ),
+            (
+                "i64",
+                "return reduce(range(1, 5, 1), add, 0);",
+                Ok(Value::I64(10)),
+            ),
+            (
+                "Array",
+                "return filter(range(1, 4, 1), double);",
+                Err(RuntimeError::InvalidArgument(
+                    "filter".to_string(),
+                    "`double` returned i64, expected Bool".to_string(),
+                )),
+            ),
+            (
+                "Array",
+                "return map(range(1, 4, 1), 5);",
+                Err(RuntimeError::InvalidArgument(
+                    "map".to_string(),
+                    "expected Function, found i64".to_string(),
+                )),
+            ),
+            (
+                "i64",
+                "return reduce(5, add, 0);",
+                Err(RuntimeError::InvalidArgument(
+                    "reduce".to_string(),
+                    "expected Array, found i64".to_string(),
+                )),
+            ),
+        ];
+        for (return_type, body, expected) in cases {
+            let code = format!("{}{} {{\n{}\n}}", functions, return_type, body);
+            assert_eq!(run(&code), expected, "{}", body);
+        }
+    }
+    #[test]
+    fn test_sort() {
+        let functions = r#"fn scramble(n: i64) -> i64 {
+            return n * 5 % 8;
+        }
+        fn descending(a: i64, b: i64) -> i64 {
+            if (a > b) {
+                return 0 - 1;
+            }
+            if (a < b) {
+                return 1;
+            }
+            return 0;
+        }
+        fn main() -> Array {
+        let a = map(range(0, 8, 1), scramble);
+        "#;
+        let array = |values: &[i64]| Value::Array(values.iter().map(|n| Value::I64(*n)).collect());
+        let invalid = |name: &str, message: &str| {
+            Err(RuntimeError::InvalidArgument(name.to_string(), message.to_string()))
+        };
+        let cases = [
+            ("return a;", Ok(array(&[0, 5, 2, 7, 4, 1, 6, 3]))),
+            ("return sort(a);", Ok(array(&[0, 1, 2, 3, 4, 5, 6, 7]))),
+            ("return sort(range(0, 0, 1));", Ok(array(&[]))),
+            ("return sort_by(a, descending);", Ok(array(&[7, 6, 5, 4, 3, 2, 1, 0]))),
+            (
+                "return sort(keys({ 2: 1, \"one\": 2 }));",
+                invalid("sort", "can not order i64 and String"),
+            ),
+            ("return sort_by(a, 3);", invalid("sort_by", "expected Function, found i64")),
+            (
+                "return sort_by(a, scramble);",
+                Err(RuntimeError::MismatchedArgumentCount(1, 2)),
+            ),
+        ];
+        for (body, expected) in cases {
+            let code = format!("{}{}\n}}", functions, body);
+            assert_eq!(run(&code), expected, "{}", body);
+        }
+    }
+    #[test]
+    fn test_strict_types() {
+        // There are no float literals, the float constants stand in for them
+        let operands = |left: &str, op: &str, right: &str| {
+            RuntimeError::InvalidOperands(op.to_string(), left.to_string(), right.to_string())
+        };
+        let mismatch =
+            RuntimeError::AssignmentMismatch("x".to_string(), "f64".to_string(), "i64".to_string());
+        let cases = [
+            (
+                "return 5 + F64_MAX == F64_MAX;",
+                Err(operands("i64", "+", "f64")),
+            ),
+            (
+                "let x = F64_MIN; x += 5; return x < 0;",
+                Err(operands("f64", "+", "i64")),
+            ),
+            ("let x = F64_MIN; x = 5; return x > 0;", Err(mismatch)),
+            ("return 5 > F64_MIN;", Err(operands("i64", ">", "f64"))),
+            ("let:i32 a = 2; return a + 5 == 7;", Ok(Value::Bool(true))),
+        ];
+        for (body, strict) in cases {
+            let code = format!("fn main() -> Bool {{ {} }}", body);
+            let permissive = Interpreter::new().eval_source(&code);
+            assert_eq!(permissive.ok(), Some(Value::Bool(true)));
+            let mut interpreter = Interpreter::new();
+            interpreter.set_strict_types(true);
+            match (interpreter.eval_source(&code), strict) {
+                (Err(EvalError::Runtime(error)), Err(expected)) => assert_eq!(error, expected),
+                (result, expected) => assert_eq!(result.ok(), expected.ok(), "{}", code),
+            }
+        }
+    }
+    #[test]
+    fn test_strict_discard() {
+        let functions = r#"fn answer() -> i64 {
+            return 42;
+        }
+        fn double(n: i64) -> i64 {
+            return n * 2;
+        }
+        fn nothing() {
+        }
+        fn main() -> i64 {
+        "#;
+        let discarded = |name: &str, type_name: &str| {
+            Err(RuntimeError::DiscardedValue(name.to_string(), type_name.to_string()))
+        };
+        let cases = [
+            ("answer();", discarded("answer", "i64")),
+            ("let n = 5;\n    n.double();", discarded("double", "i64")),
+            ("range(0, 3, 1);", discarded("range", "Array")),
+            ("let _ = answer();", Ok(Value::I64(1))),
+            ("nothing();", Ok(Value::I64(1))),
+        ];
+        for (statement, strict) in cases {
+            let code = format!("{}{}\n    return 1;\n}}", functions, statement);
+            assert_eq!(run(&code), Ok(Value::I64(1)), "{}", statement);
+            let mut interpreter = Interpreter::new();
+            interpreter.set_strict_discard(true);
+            match (interpreter.eval_source(&code), strict) {
+                (Err(EvalError::Runtime(error)), Err(expected)) => assert_eq!(error, expected),
+                (result, expected) => assert_eq!(result.ok(), expected.ok(), "{}", statement),
+            }
+        }
+    }
+    #[test]
+    fn test_define() {
+        let mut interpreter = Interpreter::new();
+        interpreter.define("N", Value::I64(5));
+        interpreter.define("NAME", Value::QuotedString("nk".to_string()));
+        let code = r#"fn main() -> i64 {
+            if (NAME == "nk") {
+                return N * 2;
+            }
+            return 0;
+        }"#;
+        assert_eq!(run_with(code, &mut interpreter), Ok(Value::I64(10)));
+        let cases = [
+            "fn main() {\n    let N = 1;\n}",
+            "fn main() {\n    N := 1;\n}",
+            "fn N() {\n}\nfn main() {\n}",
+            "fn f(N: i64) {\n}\nfn main() {\n}",
+        ];
+        for code in cases {
+            let mut interpreter = Interpreter::new();
+            interpreter.define("N", Value::I64(5));
+            let error = run_with(code, &mut interpreter);
+            assert_eq!(
+                error,
+                Err(RuntimeError::DefinedName("N".to_string())),
+                "{}",
+                code
+            );
+        }
+    }
+    #[test]
+    fn test_eval_source() {
+        let mut interpreter = Interpreter::new();
+        let value = interpreter.eval_source("fn main() -> i32 { return 7; }");
+        assert_eq!(value.unwrap(), Value::I32(7));
+        let error = Interpreter::new()
+            .eval_source("fn main() -> i32 { return 1 / x; }")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Runtime Error: undefined variable `x`");
+        let error = Interpreter::new()
+            .eval_source("fn main() { let a = \"\\q\"; }")
+            .unwrap_err();
+        assert!(matches!(error, EvalError::Lex(errors) if errors.len() == 1));
+    }
+    #[test]
+    fn test_eval_source_twice() {
+        let mut interpreter = Interpreter::new();
+        let value = interpreter.eval_source("fn main() -> i32 { return 7; }");
+        assert_eq!(value.unwrap(), Value::I32(7));
+        // Functions of the first program are gone, including the first `main`
+        let code = "fn helper() -> i32 { return 8; }\nfn main() -> i32 { return helper(); }";
+        assert_eq!(interpreter.eval_source(code).unwrap(), Value::I32(8));
+        let error = interpreter.eval_source("fn main() -> i32 { return helper(); }");
+        let undefined = RuntimeError::UndefinedFunction("helper".to_string());
+        assert!(matches!(error, Err(EvalError::Runtime(error)) if error == undefined));
+        let code = "fn main() -> i32 { return 1; }\nfn main() -> i32 { return 2; }";
+        let duplicate = RuntimeError::DuplicateFunction("main".to_string());
+        let error = interpreter.eval_source(code);
+        assert!(matches!(error, Err(EvalError::Runtime(error)) if error == duplicate));
+    }
+    #[test]
+    fn test_eval_source_diagnostics() {
+        let code = "fn main() -> i64 {\n    return 1 / x;\n    println(\"done\");\n}";
+        let output = SharedOutput::default();
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_error_output(Box::new(errors.clone()));
+        assert!(interpreter.eval_source(code).is_err());
+        assert_eq!(
+            errors.text(),
+            "Warning: unreachable code after return at line 3\n\
+             Runtime Error: undefined variable `x`\n"
+        );
+        assert_eq!(output.text(), "");
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_error_output(Box::new(errors.clone()));
+        assert!(interpreter.eval_source("fn main() {\n    let a = ;\n}").is_err());
+        assert!(errors.text().starts_with("2:13: Error: "), "{}", errors.text());
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_error_output(Box::new(errors.clone()));
+        let error = interpreter.eval_source("fn main() {\n    let a = \"open;\n}");
+        assert!(matches!(error, Err(EvalError::Lex(_))));
+        assert!(errors.text().contains("Expected quote"), "{}", errors.text());
+    }
+    #[test]
+    fn test_eval_line() {
+        let output = SharedOutput::default();
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_error_output(Box::new(errors.clone()));
+        let lines = [
+            "let a = 4;",
+            "fn double(x: i64) -> i64 { return x * 2; }",
+            "println(\"{}\", double(a));",
+            "a = a + 1;",
+        ];
+        for line in lines {
+            assert!(matches!(interpreter.eval_line(line), Ok(Value::Void)), "{}", line);
+        }
+        assert_eq!(interpreter.eval_line("return double(a);").unwrap(), Value::I64(10));
+        // Typing a function again replaces it
+        interpreter.eval_line("fn double(x: i64) -> i64 { return x * 3; }").unwrap();
+        assert_eq!(interpreter.eval_line("return double(a);").unwrap(), Value::I64(15));
+        assert_eq!(output.text(), "8\n");
+        assert_eq!(errors.text(), "");
+    }
+    #[test]
+    fn test_eval_line_redefine_memoized() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_line("@memoize fn f(x: i64) -> i64 { return x + 1; }").unwrap();
+        assert_eq!(interpreter.eval_line("return f(1);").unwrap(), Value::I64(2));
+        interpreter.eval_line("@memoize fn f(x: i64) -> i64 { return x + 2; }").unwrap();
+        assert_eq!(interpreter.eval_line("return f(1);").unwrap(), Value::I64(3));
+    }
+    #[test]
+    fn test_eval_line_errors() {
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_error_output(Box::new(errors.clone()));
+        interpreter.eval_line("fn one() -> i64 { return 1; }").unwrap();
+        interpreter.eval_line("let zero = 0;").unwrap();
+        let cases = [
+            ("missing();", RuntimeError::UndefinedFunction("missing".to_string())),
+            ("let b = 1 / zero;", RuntimeError::DivisionByZero()),
+            ("one(2);", RuntimeError::MismatchedArgumentCount(0, 1)),
+            ("x = 1;", RuntimeError::UndefinedVariable("x".to_string())),
+        ];
+        for (line, expected) in cases {
+            let error = interpreter.eval_line(line);
+            let found = matches!(&error, Err(EvalError::Runtime(error)) if *error == expected);
+            assert!(found, "{}: {:?}", line, error);
+        }
+        assert_eq!(errors.text().lines().count(), 4, "{}", errors.text());
+        // The interpreter is still usable after the errors
+        assert_eq!(interpreter.eval_line("return one();").unwrap(), Value::I64(1));
+    }
+    #[test]
+    fn test_eval_line_syntax_errors() {
+        let errors = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_error_output(Box::new(errors.clone()));
+        assert!(matches!(interpreter.eval_line("let = ;"), Err(EvalError::Parse(_))));
+        assert!(matches!(interpreter.eval_line("let c = $;"), Err(EvalError::Lex(_))));
+        assert!(matches!(interpreter.eval_line("fn f( {"), Err(EvalError::Parse(_))));
+        assert_eq!(errors.text().lines().count(), 3, "{}", errors.text());
+    }
+    #[test]
+    fn test_empty_source() {
+        for source in ["", "  \n\t\n", "// nothing here\n"] {
+            let error = run_source(source).unwrap_err();
+            let no_main = matches!(error, EvalError::Runtime(RuntimeError::NoMainFunction()));
+            assert!(no_main, "{:?}: {}", source, error);
+        }
+    }
+    #[test]
+    fn test_exit() {
+        let code = r#"fn stop(code: i64) -> i64 {
+            exit(code);
+            return 0;
+        }
+        fn main() -> i64 {
+            println("before");
+            stop(2);
+            println("after");
+            return 1;
+        }"#;
+        let output = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(output.clone()));
+        let error = interpreter.eval_source(code).unwrap_err();
+        assert!(matches!(error, EvalError::Exit(2)));
+        assert_eq!(output.text(), "before\n");
+        let code = r#"fn main() {
+            exit(I64_MAX);
+        }"#;
+        assert!(matches!(
+            run(code),
+            Err(RuntimeError::InvalidArgument(name, _)) if name == "exit"
+        ));
+    }
+    #[test]
+    fn test_missing_main() {
+        let code = r#"fn helper() {
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::NoMainFunction()));
+    }
+    #[test]
+    fn test_duplicate_main() {
+        let code = r#"fn main() {
+        }
+        fn main() {
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::DuplicateFunction("main".to_string()))
+        );
+    }
+    #[test]
+    fn test_main_with_parameters() {
+        let code = r#"fn main(argc: i32) -> i32 {
+            return argc;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::MainWithParameters()));
+    }
+    #[test]
+    fn test_declare_infers_type() {
+        let code = r#"fn main() -> i64 {
+            a := 5;
+            a += 1;
+            return a;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(6)));
+        let code = r#"fn main() -> i64 {
+            a := 5u8;
+            a += 251;
+            return a;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::IntegerOverflow("u8".to_string()))
+        );
+        let code = r#"fn main() -> String {
+            s := "six";
+            if (s == "six") {
+                s := "inner";
+            }
+            return s + "!";
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("six!".to_string())));
+    }
+    #[test]
+    fn test_declare_twice_in_scope() {
+        let code = r#"fn main() -> i64 {
+            a := 5;
+            a := 6;
+            return a;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::AlreadyDeclared("a".to_string()))
+        );
+    }
+    #[test]
+    fn test_map_literal_lookup() {
+        let code = r#"fn main() -> i64 {
+            let m = { "a": 1, "b": 2 };
+            return m["b"];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(2)));
+    }
+    #[test]
+    fn test_map_keys() {
+        let code = r#"fn main() -> i64 {
+            let squares = {};
+            let i = 0;
+            while (i < 5) {
+                squares[i * i] = i;
+                i += 1;
+            }
+            let:u8 nine = 9;
+            return squares[16] * 10 + squares[nine];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(43)));
+        let code = r#"fn main() -> String {
+            let names = {};
+            names["a" + "b"] = "first";
+            names["ab"] = names["ab"] + "!";
+            return names["ab"];
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("first!".to_string())));
+        let code = r#"fn main() {
+            let m = {};
+            m[to_float(1)] = 1;
+        }"#;
+        let error = Err(RuntimeError::InvalidMapKey("f64".to_string()));
+        assert_eq!(run(code), error);
+    }
+    #[test]
+    fn test_value_equality() {
+        assert_eq!(Value::F64(f64::NAN), Value::F64(f64::NAN));
+        assert_ne!(Value::F64(0.0), Value::F64(-0.0));
+        assert_ne!(Value::I64(1), Value::I32(1));
+        let array = |values: &[i64]| Value::Array(values.iter().map(|n| Value::I64(*n)).collect());
+        let set: std::collections::HashSet<Value> =
+            [array(&[1, 2]), array(&[1, 2]), array(&[2, 1])]
+                .into_iter()
+                .collect();
+        assert_eq!(set.len(), 2);
+    }
+    #[test]
+    fn test_chained_field_access() {
+        let code = r#"fn main() -> i64 {
+            let a = { "b": { "c": 7 } };
+            return a.b.c;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(7)));
+        let code = r#"fn main() -> i64 {
+            let a = { "b": 1 };
+            return a.c;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::MissingKey("c".to_string())));
+        let code = r#"fn main() -> i64 {
+            let a = 1;
+            return a.b;
+        }"#;
+        let error = RuntimeError::NoField("i64".to_string(), "b".to_string());
+        assert_eq!(error.to_string(), "i64 has no field `b`");
+        assert_eq!(run(code), Err(error));
+    }
+    #[test]
+    fn test_chained_method_calls() {
+        let code = r#"fn inner(m: Map) -> Map {
+            return m["inner"];
+        }
+        fn plus(n: i64, m: i64) -> i64 {
+            return n + m;
+        }
+        fn main() -> i64 {
+            let a = { "b": { "inner": { "c": 40 } } };
+            return a.b.inner().c.plus(2);
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(42)));
+        let code = r#"fn main() -> String {
+            let s = "ab";
+            return s.repeat(3);
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("ababab".to_string())));
+    }
+    #[test]
+    fn test_map_missing_key() {
+        let code = r#"fn main() -> i64 {
+            let m = { "a": 1 };
+            return m["z"];
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::MissingKey("z".to_string())));
+    }
+    #[test]
+    fn test_map_get_missing_key_is_none() {
+        let code = r#"fn main() -> Bool {
+            let m = { "a": 1 };
+            return is_none(get(m, "z"));
+        }"#;
+        assert_eq!(run(code), Ok(Value::Bool(true)));
+        let code = r#"fn main() -> Bool {
+            let m = { "a": 1 };
+            return is_some(get(m, "a"));
+        }"#;
+        assert_eq!(run(code), Ok(Value::Bool(true)));
+        let code = r#"fn main() -> Bool {
+            let m = { "a": 1 };
+            return get(m, "z") == none;
+        }"#;
+        assert_eq!(run(code), Ok(Value::Bool(true)));
+    }
+    #[test]
+    fn test_none_in_arithmetic() {
+        let code = r#"fn main() -> i64 {
+            let m = { "a": 1 };
+            return get(m, "z") + 1;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::NoneValue("+".to_string())));
+        assert_eq!(
+            builtins::call("is_none", vec![Value::I64(0)]),
+            Ok(Value::Bool(false))
+        );
+        assert_eq!(
+            builtins::call("is_some", vec![]),
+            Err(RuntimeError::MismatchedArgumentCount(1, 0))
+        );
+    }
+    #[test]
+    fn test_let_copies_aggregates() {
+        let code = r#"fn main() -> i64 {
+            let a = range(0, 3, 1);
+            let b = a;
+            b[0] = 9;
+            let m = { "x": 1 };
+            let n = m;
+            n["x"] = 2;
+            return a[0] * 10 + m["x"];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(1)));
+    }
+    #[test]
+    fn test_arguments_and_returns_copy_aggregates() {
+        let code = r#"fn change(m: Map) -> Map {
+            m["x"] = 2;
+            return m;
+        }
+        fn main() -> i64 {
+            let m = { "x": 1 };
+            let n = change(m);
+            n["x"] += 10;
+            return m["x"] * 100 + n["x"];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(112)));
+    }
+    #[test]
+    fn test_map_keys_insertion_order() {
+        let code = r#"fn main() -> String {
+            let m = { "b": 1, "a": 2 };
+            m["c"] = 3;
+            m["b"] += 10;
+            let k = keys(m);
+            return k[0] + k[1] + k[2];
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("bac".to_string())));
+    }
+    #[test]
+    fn test_map_values_insertion_order() {
+        let code = r#"fn main() -> i64 {
+            let m = { "b": 1, "a": 2 };
+            m["c"] = 3;
+            m["b"] += 10;
+            let v = values(m);
+            return v[0] * 100 + v[1] * 10 + v[2];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(1123)));
+    }
+    #[test]
+    fn test_void_call_statement() {
+        let code = r#"fn nothing() {
+            return;
+        }
+        fn main() -> i64 {
+            nothing();
+            return 0;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(0)));
+    }
+    #[test]
+    fn test_void_call_in_let() {
+        let code = r#"fn nothing() {
+            return;
+        }
+        fn main() -> i64 {
+            let a = nothing();
+            return 0;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::VoidValue()));
+    }
+    #[test]
+    fn test_void_operand() {
+        assert_eq!(
+            Value::Void.binary(&ASTOperator::Add, &Value::I64(1)),
+            Err(RuntimeError::VoidValue())
+        );
+    }
+    #[test]
+    fn test_return_value() {
+        let code = r#"fn two() -> i32 {
+            return 2;
+        }
+        fn main() -> i32 {
+            return two();
+        }"#;
+        assert_eq!(run(code), Ok(Value::I32(2)));
+    }
+    #[test]
+    fn test_bare_return() {
+        let code = r#"fn main() {
+            return;
+        }"#;
+        assert_eq!(run(code), Ok(Value::Void));
+    }
+    #[test]
+    fn test_return_value_in_void_function() {
+        let code = r#"fn main() {
+            return 1;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::ReturnValueInVoidFunction("main".to_string()))
+        );
+    }
+    #[test]
+    fn test_missing_return_value() {
+        let code = r#"fn main() -> i64 {
+            return;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::MissingReturnValue(
+                "main".to_string(),
+                "i64".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_range_ascending() {
+        let code = r#"fn main() -> i64 {
+            let r = range(0, 5, 2);
+            return r[0] * 100 + r[1] * 10 + r[2];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(24)));
+        assert_eq!(
+            builtins::call("range", vec![Value::I64(0), Value::I64(5), Value::I64(2)]),
+            Ok(Value::Array(vec![
+                Value::I64(0),
+                Value::I64(2),
+                Value::I64(4)
+            ]))
+        );
+    }
+    #[test]
+    fn test_range_descending() {
+        assert_eq!(
+            builtins::call("range", vec![Value::I64(5), Value::I64(0), Value::I64(-2)]),
+            Ok(Value::Array(vec![
+                Value::I64(5),
+                Value::I64(3),
+                Value::I64(1)
+            ]))
+        );
+    }
+    #[test]
+    fn test_range_zero_step() {
+        assert_eq!(
+            builtins::call("range", vec![Value::I64(0), Value::I64(5), Value::I64(0)]),
+            Err(RuntimeError::InvalidArgument(
+                "range".to_string(),
+                "step must not be zero".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_format_specifiers() {
+        let code = r#"fn main() -> String {
+            let a = 2;
+            return format("%d + %d = %d", a, 3, a + 3);
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("2 + 3 = 5".to_string())));
+        let cases = [
+            ("%s!", Value::QuotedString("hi".to_string()), "hi!"),
+            ("%f", Value::F64(1.5), "1.500000"),
+            ("%f", Value::I32(2), "2.000000"),
+            ("100%% %d", Value::U8(7), "100% 7"),
+        ];
+        for (template, value, expected) in cases {
+            let args = vec![Value::QuotedString(template.to_string()), value];
+            assert_eq!(
+                builtins::call("format", args),
+                Ok(Value::QuotedString(expected.to_string()))
+            );
+        }
+    }
+    #[test]
+    fn test_repeat() {
+        let code = r#"fn main() -> String {
+            return repeat("ab", 3) + "|" + repeat("ab", 0) + "|";
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("ababab||".to_string())));
+        let code = r#"fn main() -> String {
+            return repeat("ab", 0 - 1);
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::InvalidArgument(
+                "repeat".to_string(),
+                "count must not be negative, found -1".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_type_assert() {
+        let code = r#"fn main() -> i32 {
+            let:i32 a = 5;
+            return type_assert(a, "i32") + 1;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I32(6)));
+        let code = r#"fn main() -> i64 {
+            return type_assert("5", "i64");
+        }"#;
+        let error = run(code).unwrap_err();
+        assert_eq!(
+            error,
+            RuntimeError::MismatchedTypes("i64".to_string(), "String".to_string())
+        );
+        assert_eq!(
+            error.to_string(),
+            "mismatched types: expected i64, found String"
+        );
+    }
+    #[test]
+    fn test_conversions() {
+        let cases = [
+            (r#"to_int("42")"#, "42"),
+            (r#"to_int(" -7 ")"#, "-7"),
+            (r#"to_int(to_float("3.9"))"#, "3"),
+            (r#"to_float("3.14")"#, "3.14"),
+            ("to_float(2)", "2.0"),
+            (r#"to_bool("true")"#, "true"),
+            ("to_bool(0)", "false"),
+        ];
+        for (call, expected) in cases {
+            let code = format!("fn main() {{\n    println(\"{{}}\", {});\n}}", call);
+            let output = SharedOutput::default();
+            let mut interpreter = Interpreter::new();
+            interpreter.set_output(Box::new(output.clone()));
+            run_with(&code, &mut interpreter).unwrap();
+            assert_eq!(output.text(), format!("{}\n", expected), "{}", call);
+        }
+        let cases = [
+            (
+                r#"to_int("4x2")"#,
+                "to_int",
+                r#"cannot convert "4x2" to an integer"#,
+            ),
+            (
+                r#"to_float("pi")"#,
+                "to_float",
+                r#"cannot convert "pi" to a float"#,
+            ),
+            (
+                r#"to_bool("yes")"#,
+                "to_bool",
+                r#"cannot convert "yes" to a Bool"#,
+            ),
+        ];
+        for (call, name, message) in cases {
+            let code = format!("fn main() {{\n    println(\"{{}}\", {});\n}}", call);
+            assert_eq!(
+                run(&code),
+                Err(RuntimeError::InvalidArgument(
+                    name.to_string(),
+                    message.to_string()
+                )),
+                "{}",
+                call
+            );
+        }
+    }
+    #[test]
+    fn test_format_width() {
+        let cases = [
+            (r#""[{:>6}]", "ab""#, "[    ab]"),
+            (r#""[{:<6}]", 42"#, "[42    ]"),
+            (r#""[{:^6}]", "abc""#, "[ abc  ]"),
+            (r#""[{:4}|{:4}]", 7, "x""#, "[   7|x   ]"),
+            (r#""[{:>2}]", "long""#, "[long]"),
+            (r#""{:,}", 1000000"#, "1,000,000"),
+            (r#""{:,}", 0 - 123456"#, "-123,456"),
+            (r#""{:,}", to_float("1234567.25")"#, "1,234,567.25"),
+            (r#""[{:>8,}]", 12345"#, "[  12,345]"),
+            (r#""{:,}", 999"#, "999"),
+        ];
+        for (args, expected) in cases {
+            let code = format!("fn main() {{\n    println({});\n}}", args);
+            let output = SharedOutput::default();
+            let mut interpreter = Interpreter::new();
+            interpreter.set_output(Box::new(output.clone()));
+            run_with(&code, &mut interpreter).unwrap();
+            assert_eq!(output.text(), format!("{}\n", expected), "{}", args);
+        }
+        let code = r#"fn main() {
+            println("{:>x}", 1);
+        }"#;
+        let error = run(code).unwrap_err();
+        assert_eq!(error, RuntimeError::InvalidFormatSpec(">x".to_string()));
+        // A template built at runtime is checked against the arguments when it is printed
+        let code = "fn main() {\n    println(\"{}\" + \"-{}\", 1);\n}";
+        assert_eq!(run(code), Err(RuntimeError::MismatchedArgumentCount(2, 1)));
+        assert_eq!(error.to_string(), "invalid format spec `{:>x}`");
+        let code = r#"fn main() {
+            println("{:,}", "text");
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::MismatchedTypes(
+                "number".to_string(),
+                "String".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_wrapping_arithmetic() {
+        let cases = [
+            ("wrapping_add(I32_MAX, 1)", Value::I32(i32::MIN)),
+            ("wrapping_sub(I8_MIN, 1)", Value::I8(i8::MAX)),
+            ("wrapping_mul(U64_MAX, U64_MAX)", Value::U64(1)),
+            ("wrapping_add(2, 3)", Value::I64(5)),
+        ];
+        for (call, expected) in cases {
+            let code = format!(
+                "fn main() -> {} {{\n    return {};\n}}",
+                expected.type_name(),
+                call
+            );
+            assert_eq!(run(&code), Ok(expected), "{}", call);
+        }
+        let code = r#"fn main() -> i32 {
+            return I32_MAX + 1;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::IntegerOverflow("i32".to_string()))
+        );
+    }
+    #[test]
+    fn test_tuples() {
+        let code = r#"fn divide(a: i64, b: i64) -> Tuple {
+            return (a / b, a % b);
+        }
+        fn main() -> i64 {
+            let (quotient, remainder) = divide(17, 5);
+            let pair = divide(9, 4);
+            return quotient * 100 + remainder * 10 + pair.0 + pair.1;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(323)));
+        let code = r#"fn main() -> i64 {
+            let (a, _, c) = (1, 2);
+            return a;
+        }"#;
+        let error = run(code).unwrap_err();
+        assert_eq!(error, RuntimeError::DestructureArity(3, 2));
+        assert_eq!(
+            error.to_string(),
+            "cannot destructure a tuple of 2 values into 3 names"
+        );
+        let pair = Value::Tuple(vec![Value::I64(1), Value::QuotedString("a".to_string())]);
+        assert_eq!(pair.to_string(), r#"(1, "a")"#);
+    }
+    #[test]
+    fn test_search() {
+        let cases = [
+            (r#"contains("nukleus", "kle")"#, "Bool", Value::Bool(true)),
+            (r#"contains("nukleus", "elk")"#, "Bool", Value::Bool(false)),
+            (r#"index_of("nukleus", "eus")"#, "i64", Value::I64(4)),
+            ("contains(range(0, 10, 2), 4)", "Bool", Value::Bool(true)),
+            ("index_of(range(0, 10, 2), 6)", "i64", Value::I64(3)),
+            ("index_of(range(0, 10, 2), 5)", "i64", Value::I64(-1)),
+            (r#"index_of("nukleus", "x")"#, "i64", Value::I64(-1)),
+        ];
+        for (call, return_type, expected) in cases {
+            let code = format!("fn main() -> {} {{\n    return {};\n}}", return_type, call);
+            assert_eq!(run(&code), Ok(expected), "{}", call);
+        }
+        let code = r#"fn main() -> i64 {
+            let values = values({"a": 1, "b": "1", "c": (1, 2), "d": to_float(2)});
+            if (contains(values, b"1")) {
+                return 0;
+            }
+            return index_of(values, "1") * 100 + index_of(values, (1, 2)) * 10
+                + index_of(values, 2);
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(123)));
+    }
+    #[test]
+    fn test_reverse() {
+        let cases = [
+            ("reverse(range(1, 5, 1))", "Array", "[4, 3, 2, 1]"),
+            (r#"reverse("nukleus")"#, "String", "suelkun"),
+            (r#"reverse("añb€")"#, "String", "€bña"),
+            (r#"reverse("")"#, "String", ""),
+            (r#"reverse(b"ab")"#, "Bytes", "b\"ba\""),
+        ];
+        for (call, return_type, expected) in cases {
+            let code = format!("fn main() -> {} {{\n    return {};\n}}", return_type, call);
+            assert_eq!(run(&code).map(|value| value.to_string()), Ok(expected.to_string()));
+        }
+        assert_eq!(
+            run("fn main() -> i64 {\n    return reverse(5);\n}"),
+            Err(RuntimeError::InvalidArgument(
+                "reverse".to_string(),
+                "expected Array or String, found i64".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_min_max_sum() {
+        let cases = [
+            ("sum(range(1, 5, 1))", Ok(Value::I64(10))),
+            ("sum(range(0, 0, 1))", Ok(Value::I64(0))),
+            ("max(range(3, 0 - 3, 0 - 2))", Ok(Value::I64(3))),
+            ("min(range(3, 0 - 3, 0 - 2))", Ok(Value::I64(-1))),
+            ("max(3, 7)", Ok(Value::I64(7))),
+            ("min(3, 7)", Ok(Value::I64(3))),
+            ("max(range(0, 0, 1))", Err("the Array is empty")),
+            (r#"sum(keys({"a": 1}))"#, Err("expected a number, found String")),
+            (r#"min(1, "a")"#, Err("expected a number, found String")),
+        ];
+        for (call, expected) in cases {
+            let code = format!("fn main() -> i64 {{\n    return {};\n}}", call);
+            let name = &call[..3];
+            let expected = expected.map_err(|message| {
+                RuntimeError::InvalidArgument(name.to_string(), message.to_string())
+            });
+            assert_eq!(run(&code), expected, "{}", call);
+        }
+        let code = "fn main() -> i64 {\n    return max(1, 2, 3);\n}";
+        assert_eq!(run(code), Err(RuntimeError::MismatchedArgumentCount(2, 3)));
+    }
+    #[test]
+    fn test_deep_equality() {
+        let functions = r#"fn row(n: i64) -> Array {
+            return range(0, n, 1);
+        }
+        fn grid(n: i64) -> Array {
+            return map(range(1, n, 1), row);
+        }"#;
+        let cases = [
+            ("grid(4) == grid(4)", Ok(true)),
+            ("grid(4) != grid(4)", Ok(false)),
+            ("grid(4) == grid(3)", Ok(false)),
+            ("map(range(1, 4, 1), row) == map(range(2, 5, 1), row)", Ok(false)),
+            ("(1, (2, \"a\")) == (1, (2, \"a\"))", Ok(true)),
+            (r#"{"x": 1, "y": grid(3)} == {"y": grid(3), "x": 1}"#, Ok(true)),
+            (r#"{"x": 1, "y": 2} == {"x": 1, "y": 3}"#, Ok(false)),
+            (r#"{"x": 1, "y": 2} == {"x": 1, "z": 2}"#, Ok(false)),
+            (r#"{"x": 1} == {"x": 1, "y": 2}"#, Ok(false)),
+            ("row(2) == 5", Err(("Array", "i64"))),
+            ("row(2) == (0, 1)", Err(("Array", "Tuple"))),
+            (r#"row(2) == keys({"a": 1, "b": 2})"#, Err(("i64", "String"))),
+            (r#"{"x": row(2)} != {"x": (0, 1)}"#, Err(("Array", "Tuple"))),
+        ];
+        for (condition, expected) in cases {
+            let main = format!("fn main() -> Bool {{\n    return {};\n}}", condition);
+            let code = format!("{}\n{}", functions, main);
+            let op = if condition.contains("!=") { "!=" } else { "==" };
+            let expected = expected.map(Value::Bool).map_err(|(a, b)| {
+                RuntimeError::InvalidOperands(op.to_string(), a.to_string(), b.to_string())
+            });
+            assert_eq!(run(&code), expected, "{}", condition);
+        }
+    }
+    #[test]
+    fn test_split_text() {
+        let cases = [
+            (r#"split_whitespace("  one two\t three \n")"#, r#"["one", "two", "three"]"#),
+            (r#"split_whitespace("   ")"#, "[]"),
+            (
+                r#"lines("first\nsecond line\r\n\nlast\n")"#,
+                r#"["first", "second line", "", "last"]"#,
+            ),
+            (r#"lines("no newline")"#, r#"["no newline"]"#),
+            (r#"lines("")"#, "[]"),
+        ];
+        for (call, expected) in cases {
+            let code = format!("fn main() -> Array {{\n    return {};\n}}", call);
+            let result = run(&code).map(|value| value.to_string());
+            assert_eq!(result, Ok(expected.to_string()), "{}", call);
+        }
+        assert_eq!(
+            run("fn main() -> Array {\n    return lines(5);\n}"),
+            Err(RuntimeError::InvalidArgument(
+                "lines".to_string(),
+                "expected String, found i64".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_format_mismatch() {
+        let cases = [
+            (
+                "%d %d",
+                vec![Value::I64(1)],
+                "missing argument for '%d', 1 given",
+            ),
+            (
+                "%d",
+                vec![Value::I64(1), Value::I64(2)],
+                "2 arguments given but the format string uses 1",
+            ),
+            (
+                "%d",
+                vec![Value::QuotedString("x".to_string())],
+                "'%d' cannot format String",
+            ),
+            ("%q", vec![Value::I64(1)], "unknown specifier '%q'"),
+        ];
+        for (template, mut args, message) in cases {
+            args.insert(0, Value::QuotedString(template.to_string()));
+            assert_eq!(
+                builtins::call("format", args),
+                Err(RuntimeError::InvalidArgument(
+                    "format".to_string(),
+                    message.to_string()
+                ))
+            );
+        }
+    }
+    #[test]
+    fn test_time_ms() {
+        let code = r#"fn main() -> i64 {
+            let start = time_ms();
+            return time_ms() - start;
+        }"#;
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_ok());
+        let ticks = Rc::new(std::cell::Cell::new(1000));
+        let clock = Rc::clone(&ticks);
+        let mut interpreter = Interpreter::new();
+        interpreter.set_clock(Box::new(move || {
+            clock.set(clock.get() + 250);
+            clock.get()
+        }));
+        assert_eq!(interpreter.run(parser.get_asts()), Ok(Value::I64(250)));
+        assert_eq!(ticks.get(), 1500);
+    }
+    #[test]
+    fn test_constants() {
+        let code = r#"fn main() -> String {
+            return format("%d %d %s", I32_MAX, U8_MIN, I64_MIN);
+        }"#;
+        assert_eq!(
+            run(code),
+            Ok(Value::QuotedString(
+                "2147483647 0 -9223372036854775808".to_string()
+            ))
+        );
+        let code = r#"fn main() -> i32 {
+            return I32_MAX + 1;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::IntegerOverflow("i32".to_string()))
+        );
+        let code = r#"fn main() -> i32 {
+            let I32_MAX = 5;
+            return I32_MAX;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I32(5)));
+        assert_eq!(builtins::constant("F64_MAX"), Some(Value::F64(f64::MAX)));
+    }
+    #[test]
+    fn test_chained_assignment() {
+        let code = r#"fn main() -> i64 {
+            let a = 1;
+            let b = 2;
+            let m = { "k": 0 };
+            a = b = 5;
+            m["k"] = a += b = 3;
+            return a * 100 + b * 10 + m["k"];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(838)));
+        let code = r#"fn main() -> i64 {
+            let a = 1;
+            a = c = 5;
+            return a;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::UndefinedVariable("c".to_string()))
+        );
+    }
+    #[test]
+    fn test_assignment_keeps_type() {
+        let code = r#"fn main() -> i64 {
+            let:i32 a = 1;
+            a = a + 2;
+            a = 40;
+            let:i32 b = 1;
+            b += 1;
+            let m = none;
+            m = 5;
+            return a + b + m;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(47)));
+        let code = r#"fn main() -> i64 {
+            let:i32 a = 1;
+            a = "hello";
+            return a;
+        }"#;
+        let error = run(code).unwrap_err();
+        assert_eq!(
+            error,
+            RuntimeError::AssignmentMismatch(
+                "a".to_string(),
+                "i32".to_string(),
+                "String".to_string()
+            )
+        );
+        assert_eq!(error.to_string(), "cannot assign String to `a` of type i32");
+        let code = r#"fn main() -> i64 {
+            let:u8 a = 1;
+            a = 300;
+            return a;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::IntegerOverflow("u8".to_string()))
+        );
+    }
+    #[test]
+    fn test_byte_strings() {
+        let code = r#"fn first(data: Bytes) -> u8 {
+            return data[0];
+        }
+        fn main() -> u8 {
+            let data = b"\x7fELF";
+            return first(data) + data[1] - b"A"[0];
+        }"#;
+        assert_eq!(run(code), Ok(Value::U8(0x7f + b'E' - b'A')));
+        assert_eq!(
+            Value::Bytes(vec![0x7f, b'E']).to_string(),
+            r#"b"\x7FE""#.to_string()
+        );
+        let cases = [
+            (r#"b"a" == b"a""#, Ok(true)),
+            (r#"b"a" != b"ab""#, Ok(true)),
+            (r#"b"a" < b"b""#, Ok(true)),
+            (r#"(1, b"\x00") == (1, b"\x00")"#, Ok(true)),
+            (r#"b"a" == "a""#, Err(("Bytes", "String"))),
+        ];
+        for (condition, expected) in cases {
+            let code = format!("fn main() -> Bool {{\n    return {};\n}}", condition);
+            let op = if condition.contains("==") { "==" } else { "<" };
+            let expected = expected.map(Value::Bool).map_err(|(a, b)| {
+                RuntimeError::InvalidOperands(op.to_string(), a.to_string(), b.to_string())
+            });
+            assert_eq!(run(&code), expected, "{}", condition);
+        }
+    }
+    #[test]
+    fn test_let_without_value() {
+        let code = r#"fn main() -> i32 {
+            let:i32 a;
+            let b;
+            if (1 < 2) {
+                a = 5;
+                b = "five";
+            }
+            return a;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I32(5)));
+        let cases = [
+            (
+                "let:i32 a; return a;",
+                RuntimeError::UninitializedVariable("a".to_string()),
+            ),
+            (
+                "let:i32 a; a += 1; return a;",
+                RuntimeError::UninitializedVariable("a".to_string()),
+            ),
+            (
+                "let:i32 a; a = \"x\"; return a;",
+                RuntimeError::MismatchedTypes("i32".to_string(), "String".to_string()),
+            ),
+        ];
+        for (body, expected) in cases {
+            let code = format!("fn main() -> i32 {{ {} }}", body);
+            assert_eq!(run(&code), Err(expected));
+        }
+        assert_eq!(
+            RuntimeError::UninitializedVariable("a".to_string()).to_string(),
+            "use of uninitialized variable 'a'"
+        );
+    }
+    #[test]
+    fn test_shadowing() {
+        let code = r#"fn main() -> i64 {
+            let a = 1;
+            let b = 0;
+            if (a == 1) {
+                let a = 5;
+                b = a;
+            }
+            return a * 10 + b;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(15)));
+    }
+    #[test]
+    fn test_scope_exit() {
+        let code = r#"fn main() -> i64 {
+            let a = 1;
+            if (a == 1) {
+                let c = 5;
+            }
+            return c;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::UndefinedVariable("c".to_string()))
+        );
+    }
+    #[test]
+    fn test_argument_types() {
+        let code = r#"fn add(a: i32, b: i32) -> i32 {
+            return a + b;
+        }
+        fn main() -> i32 {
+            return add(1, 2);
+        }"#;
+        assert_eq!(run(code), Ok(Value::I32(3)));
+    }
+    #[test]
+    fn test_mismatched_argument_type() {
+        let code = r#"fn add(a: i32, b: i32) -> i32 {
+            return a + b;
+        }
+        fn main() -> i32 {
+            return add(1, "two");
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::MismatchedArgumentType(
+                2,
+                "add".to_string(),
+                "i32".to_string(),
+                "String".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_default_parameter() {
+        let code = r#"fn greet(name: String = "world") -> String {
+            return "hello " + name;
+        }
+        fn main() -> String {
+            return greet() + ", " + greet("nukleus");
+        }"#;
+        assert_eq!(
+            run(code),
+            Ok(Value::QuotedString(
+                "hello world, hello nukleus".to_string()
+            ))
+        );
+    }
+    #[test]
+    fn test_missing_argument_without_default() {
+        let code = r#"fn add(a: i64, b: i64 = 1) -> i64 {
+            return a + b;
+        }
+        fn main() -> i64 {
+            return add();
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::MismatchedArgumentCount(2, 0)));
+    }
+    #[test]
+    fn test_match_literal_arm() {
+        let code = r#"fn describe(x: i64) -> String {
+            return match x {
+                1 => "one",
+                "foo" => "text",
+                _ => "other",
+            };
+        }
+        fn main() -> String {
+            return describe(1) + " " + describe(7);
+        }"#;
+        assert_eq!(run(code), Ok(Value::QuotedString("one other".to_string())));
+    }
+    #[test]
+    fn test_discard_binding() {
+        let code = r#"fn count(n: i64) -> i64 {
+            println("{}", n);
+            return n;
+        }
+        fn main() -> i64 {
+            let _ = count(1);
+            let:i64 _ = count(2);
+            let _a = 3;
+            return _a;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(3)));
+        let code = r#"fn main() -> i64 {
+            let _ = 1;
+            return _;
+        }"#;
+        let mut lexer = Lexer::new(PathBuf::from("test.nk"), code);
+        assert!(lexer.run().is_ok());
+        let mut parser = Parser::new(lexer.tokens(), PathBuf::from("test.nk"), code);
+        assert!(parser.run().is_err());
+    }
+    #[test]
+    fn test_panic() {
+        let code = r#"fn check(n: i64) -> i64 {
+            if (n > 1) {
+                panic("boom");
+            }
+            return n;
+        }
+        fn main() -> i64 {
+            check(1);
+            return check(2);
+        }"#;
+        let error = run(code).unwrap_err();
+        assert_eq!(error, RuntimeError::Panic("boom".to_string(), 3));
+        assert_eq!(error.to_string(), "panicked at line 3: boom");
+        let code = r#"fn main() -> i64 {
+            let a = 7;
+            return panic(a);
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::Panic("7".to_string(), 3)));
+    }
+    #[test]
+    fn test_block_expression() {
+        let code = r#"fn f() -> i64 {
+            return 4;
+        }
+        fn main() -> i64 {
+            let t = 10;
+            let x = { let t = f(); t + 1 };
+            let m = { "a": x };
+            return { x = x * t; x } + m["a"];
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(55)));
+    }
+    #[test]
+    fn test_void_block_expression() {
+        let code = r#"fn main() {
+            let x = { let t = 1; };
+            return x;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::VoidBlock()));
+        let code = r#"fn main() -> i64 {
+            return { let t = 1; };
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::VoidBlock()));
+        let code = r#"fn main() -> i64 {
+            return { let t = 1; } + 1;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::VoidBlock()));
+        let code = r#"fn main() -> i64 {
+            let x = { return 1; };
+            return x;
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::ReturnInBlock()));
+    }
+    #[test]
+    fn test_memoize() {
+        // Without the cache this makes about 2^90 calls
+        let code = r#"@memoize
+        fn fib(n: i64) -> i64 {
+            if (n < 2) {
+                return n;
+            }
+            return fib(n - 1) + fib(n - 2);
+        }
+        fn main() -> i64 {
+            return fib(90);
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(2880067194370816120)));
+        // The body only runs for arguments that were not seen before
+        let code = r#"@memoize
+        fn square(n: i64) -> i64 {
+            println("{}", n);
+            return n * n;
+        }
+        fn main() -> i64 {
+            return square(3) + square(4) + square(3);
+        }"#;
+        let output = SharedOutput::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(output.clone()));
+        assert_eq!(run_with(code, &mut interpreter), Ok(Value::I64(34)));
+        assert_eq!(output.text(), "3\n4\n");
+    }
+    #[test]
+    fn test_loop() {
+        let code = r#"fn main() -> i64 {
+            let count = 0;
+            loop {
+                count += 1;
+                if (count == 5) {
+                    break;
+                }
+            }
+            return count;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(5)));
+        let code = r#"fn main() -> i64 {
+            let n = 1;
+            let power = loop {
+                n = n * 2;
+                if (n > 100) {
+                    break n;
+                }
+            };
+            return power;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(128)));
+        let code = r#"fn find() -> i64 {
+            let i = 0;
+            'search: loop {
+                i += 1;
+                while (i < 3) {
+                    break 'search;
+                }
+            }
+            return i * 10;
+        }
+        fn main() -> i64 {
+            let i = 0;
+            loop {
+                i += 1;
+                if (i == 4) {
+                    return find() + i;
+                }
+            }
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(14)));
+    }
+    #[test]
+    fn test_if_let() {
+        let functions = r#"fn lookup(key: String) -> i64 {
+            let m = { "a": 1 };
+            if let Some(x) = get(m, key) {
+                return x + 10;
+            } else {
+                return 0;
+            }
+        }
+        fn main() -> i64 {
+        "#;
+        let cases = [
+            ("return lookup(\"a\");", Ok(Value::I64(11))),
+            ("return lookup(\"b\");", Ok(Value::I64(0))),
+            (
+                "let v = none;\n if let Some(x) = v { return 1; }\n return 2;",
+                Ok(Value::I64(2)),
+            ),
+            (
+                "if let Some(x) = to_int(\"5\") { let y = x; }\n return x;",
+                Err(RuntimeError::UndefinedVariable("x".to_string())),
+            ),
+        ];
+        for (body, expected) in cases {
+            let code = format!("{}{}\n}}", functions, body);
+            assert_eq!(run(&code), expected, "{}", body);
+        }
+    }
+    #[test]
+    fn test_for_loop_control() {
+        // Every value of the range is visited once, `continue` still steps to the next one
+        let code = r#"fn main() -> i64 {
+            let sum = 0;
+            let visited = 0;
+            for(i -> 10 :: 1) {
+                visited += 1;
+                if (i % 2 == 1) {
+                    continue;
+                }
+                sum += i;
+            }
+            return sum * 100 + visited;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(2010)));
+        let code = r#"fn main() -> i64 {
+            let found = 0;
+            for(i -> 10 :: 1) {
+                if (i * i > 20) {
+                    found = i;
+                    break;
+                }
+            }
+            return found;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(5)));
+        // The loop variable is gone after the loop, after a `break` as well
+        for exit in ["", "break;"] {
+            let code = format!(
+                "fn main() -> i64 {{ for(i -> 3 :: 1) {{ {} }} return i; }}",
+                exit
+            );
+            let undefined = RuntimeError::UndefinedVariable("i".to_string());
+            assert_eq!(run(&code), Err(undefined), "{}", exit);
+        }
+        // A variable of the same name gives the start and is left as it was
+        let code = r#"fn main() -> i64 {
+            let i = 7;
+            let sum = 0;
+            for(i -> 10 :: 1) {
+                sum += i;
+            }
+            return sum * 10 + i;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(247)));
+    }
+    #[test]
+    fn test_labeled_loops() {
+        // Stops both loops at the first pair that multiplies to 6, skipping rows with `i == 1`
+        let code = r#"fn main() -> i64 {
+            let i = 0;
+            let found = 0;
+            let visited = 0;
+            'rows: while (i < 5) {
+                i += 1;
+                let j = 0;
+                while (j < 5) {
+                    j += 1;
+                    if (i == 1) {
+                        continue 'rows;
+                    }
+                    visited += 1;
+                    if (i * j == 6) {
+                        found = i * 10 + j;
+                        break 'rows;
+                    }
+                }
+            }
+            return found * 100 + visited;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(2303)));
+        let code = r#"fn main() -> i64 {
+            let i = 0;
+            let j = 0;
+            let total = 0;
+            'outer: for(i -> 4 :: 1) {
+                j = 0;
+                while (j < 10) {
+                    j += 1;
+                    if (j > i) {
+                        continue 'outer;
+                    }
+                    total += j;
+                    if (total > 6) {
+                        break;
+                    }
+                }
+            }
+            return total;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(7)));
+    }
+    #[test]
+    fn test_match_jump_table() {
+        let mut arms: Vec<String> = (0..16).map(|n| format!("{} => {}", n, n * 10)).collect();
+        arms.insert(3, "3 => 333".to_string());
+        let code = |value: &str, wildcard: &str| {
+            format!(
+                "fn main() -> i64 {{ let:u8 x = {}; return match x {{ {}{} }}; }}",
+                value,
+                arms.join(", "),
+                wildcard
+            )
+        };
+        let cases = [
+            ("0", Ok(Value::I64(0))),
+            ("3", Ok(Value::I64(333))),
+            ("15", Ok(Value::I64(150))),
+            ("16", Ok(Value::I64(99))),
+            ("255", Ok(Value::I64(99))),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                run(&code(value, ", _ => 99, 16 => 0")),
+                expected,
+                "{}",
+                value
+            );
+        }
+        assert_eq!(
+            run(&code("16", "")),
+            Err(RuntimeError::NoMatchingArm("16".to_string()))
+        );
+    }
+    #[test]
+    fn test_match_jump_table_per_program() {
+        // Each program has its own tables, even when its arms reuse the memory of the last one
+        let mut interpreter = Interpreter::new();
+        for scale in 1..20 {
+            let arms: Vec<String> = (0..8).map(|n| format!("{} => {}", n, n * scale)).collect();
+            let code = format!(
+                "fn main() -> i64 {{ let x = 5; return match x {{ {}, _ => 0 }}; }}",
+                arms.join(", ")
+            );
+            let value = interpreter.eval_source(&code);
+            assert!(matches!(value, Ok(Value::I64(n)) if n == 5 * scale), "{}", scale);
+        }
+    }
+    #[test]
+    fn test_match_without_wildcard() {
+        let code = r#"fn main() -> i64 {
+            let x = 3;
+            return match x { 1 => 10, 2 => 20 };
+        }"#;
+        assert_eq!(run(code), Err(RuntimeError::NoMatchingArm("3".to_string())));
+    }
+    #[test]
+    fn test_integer_suffix() {
+        let code = r#"fn main() -> i64 {
+            let a = 200u8;
+            return a + 100;
+        }"#;
+        assert_eq!(
+            run(code),
+            Err(RuntimeError::IntegerOverflow("u8".to_string()))
+        );
+        let code = r#"fn main() -> i64 {
+            return 5i64;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(5)));
+    }
+    #[test]
+    fn test_float_promotion() {
+        let add = |a: Value, b: Value| a.binary(&ASTOperator::Add, &b);
+        assert_eq!(add(Value::I64(1), Value::F64(1.5)), Ok(Value::F64(2.5)));
+        assert_eq!(add(Value::F32(1.5), Value::I32(2)), Ok(Value::F32(3.5)));
+        assert_eq!(add(Value::F32(1.5), Value::F64(1.0)), Ok(Value::F64(2.5)));
+        assert_eq!(
+            Value::I64(3).binary(&ASTOperator::Less, &Value::F64(3.5)),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(Value::F64(2.5).to_string(), "2.5");
+    }
+    #[test]
+    fn test_power() {
+        let code = r#"fn main() -> i64 {
+            return 2 ** 10 + 2 * 3 ** 2 + 2 ** 3 ** 2;
+        }"#;
+        assert_eq!(run(code), Ok(Value::I64(1024 + 18 + 512)));
+        assert_eq!(
+            Value::I64(2).binary(&ASTOperator::Power, &Value::I64(-1)),
+            Err(RuntimeError::NegativeExponent(-1))
+        );
+        assert_eq!(
+            Value::F64(2.0).binary(&ASTOperator::Power, &Value::F64(0.5)),
+            Ok(Value::F64(2.0_f64.sqrt()))
+        );
+        assert_eq!(
+            Value::I8(2).binary(&ASTOperator::Power, &Value::I64(7)),
+            Err(RuntimeError::IntegerOverflow("i8".to_string()))
+        );
+    }
+    #[test]
+    fn test_integer_float_comparison() {
+        // 2^53 + 1 is not a f64, the float it rounds to must not compare equal
+        let big = || Value::I64(9_007_199_254_740_993);
+        let rounded = || Value::F64(9_007_199_254_740_992.0);
+        let cases = [
+            (Value::I64(1), ASTOperator::Equals, Value::F64(1.0), true),
+            (
+                Value::F64(1.0),
+                ASTOperator::NotEquals,
+                Value::I32(1),
+                false,
+            ),
+            (Value::I64(1), ASTOperator::Less, Value::F64(2.0), true),
+            (Value::F32(1.5), ASTOperator::Greater, Value::U8(1), true),
+            (Value::I64(-2), ASTOperator::Less, Value::F64(-1.5), true),
+            (Value::I64(1), ASTOperator::Equals, Value::F64(1.5), false),
+            (big(), ASTOperator::Equals, rounded(), false),
+            (big(), ASTOperator::Greater, rounded(), true),
+            (
+                Value::I64(1),
+                ASTOperator::Less,
+                Value::F64(f64::INFINITY),
+                true,
+            ),
+            (
+                Value::I64(1),
+                ASTOperator::Equals,
+                Value::F64(f64::NAN),
+                false,
+            ),
+            (
+                Value::I64(1),
+                ASTOperator::NotEquals,
+                Value::F64(f64::NAN),
+                true,
+            ),
+        ];
+        for (left, op, right, expected) in cases {
+            assert_eq!(left.binary(&op, &right), Ok(Value::Bool(expected)));
+        }
+    }
+    #[test]
+    fn test_disallowed_operands() {
+        let string = || Value::QuotedString("a".to_string());
+        let cases = [
+            (string(), ASTOperator::Add, Value::I64(1)),
+            (Value::F64(1.0), ASTOperator::Add, string()),
+            (string(), ASTOperator::Subtract, string()),
+            (Value::Bool(true), ASTOperator::Add, Value::Bool(false)),
+            (Value::Char('a'), ASTOperator::Add, Value::Char('b')),
+            (Value::F64(1.0), ASTOperator::BitAnd, Value::I64(1)),
+            (Value::Array(vec![]), ASTOperator::Add, Value::Array(vec![])),
+            (Value::I8(1), ASTOperator::Add, Value::U8(1)),
+        ];
+        for (left, op, right) in cases {
+            assert_eq!(
+                left.binary(&op, &right),
+                Err(RuntimeError::InvalidOperands(
+                    op.to_string(),
+                    left.type_name().to_string(),
+                    right.type_name().to_string(),
+                ))
+            );
+        }
+    }
+}
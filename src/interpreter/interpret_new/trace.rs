@@ -0,0 +1,61 @@
+use astgen::ast::{ASTstatement, ASTtypevalue};
+use astgen::AST;
+
+// One line summary of a statement for `--trace`, bodies of blocks are left out
+pub fn describe(ast: &AST) -> String {
+    match ast {
+        AST::Statement(statement) => describe_statement(statement),
+        AST::TypeValue(ASTtypevalue::FunctionCall { name, args }) => {
+            format!("{}({})", name, join(args))
+        }
+        AST::TypeValue(ASTtypevalue::Loop { .. }) => "loop".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn describe_statement(statement: &ASTstatement) -> String {
+    match statement {
+        ASTstatement::Let {
+            name,
+            type_name: Some(type_name),
+            value: Some(value),
+        } => format!("let:{} {} = {}", type_name, name, value),
+        ASTstatement::Let {
+            name,
+            value: Some(value),
+            ..
+        } => format!("let {} = {}", name, value),
+        ASTstatement::Let { name, .. } => format!("let {}", name),
+        ASTstatement::Declare { name, value } => format!("{} := {}", name, value),
+        ASTstatement::Destructure { names, value } => {
+            format!("let ({}) = {}", names.join(", "), value)
+        }
+        ASTstatement::Assignment { left, op, right } => format!("{} {} {}", left, op, right),
+        ASTstatement::If { condition, .. } => format!("if ({})", condition),
+        ASTstatement::For {
+            start, end, value, ..
+        } => format!("for({} -> {} :: {})", start, end, value),
+        ASTstatement::While { condition, .. } => format!("while ({})", condition),
+        ASTstatement::Print { value, args } => format!("print({})", join_format(value, args)),
+        ASTstatement::Println { value, args } => {
+            format!("println({})", join_format(value, args))
+        }
+        ASTstatement::Return { value } => match value.as_ref() {
+            AST::TypeValue(ASTtypevalue::TypeVoid) => "return".to_string(),
+            value => format!("return {}", value),
+        },
+        other => other.to_string(),
+    }
+}
+
+fn join(args: &[AST]) -> String {
+    args.iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+fn join_format(value: &AST, args: &[AST]) -> String {
+    let mut parts = vec![format!("\"{}\"", value)];
+    parts.extend(args.iter().map(|arg| arg.to_string()));
+    parts.join(", ")
+}
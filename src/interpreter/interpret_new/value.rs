@@ -0,0 +1,721 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use astgen::ast::{ASTOperator, ASTfunctiontype, ASTtypename};
+use lexer::neo_tokens::byte_string_literal;
+
+use super::error::RuntimeError;
+
+// Operands of a binary operator after promotion to a common type
+enum Operands<'a> {
+    Integer(Value, i128, i128),
+    Float(Value, f64, f64),
+    Text(&'a str, &'a str),
+}
+
+// Values have copy semantics, arrays and maps included: `let b = a;`, passing an
+// argument and returning a value all copy, so changing the copy never changes `a`.
+#[derive(Debug, Clone)]
+#[allow(missing_docs)]
+pub enum Value {
+    Void,
+    // An absent optional value, only `==` and `!=` accept it
+    None,
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    QuotedString(String),
+    Char(char),
+    Array(Vec<Value>),
+    // Entries are kept in insertion order so iteration is deterministic.
+    // Keys are integers, stored as `i64` when they fit, strings, bools or chars.
+    Map(Vec<(Value, Value)>),
+    // A `b"..."` literal, indexing it gives `u8` values
+    Bytes(Vec<u8>),
+    // A variable declared by `let:i32 a;` before its first assignment, with the declared type
+    Uninitialized(Option<ASTtypename>),
+    // A function of the program used as a value, by its name
+    Function(String),
+    // `(a, b)`, its values are the fields `0`, `1`, ...
+    Tuple(Vec<Value>),
+}
+impl Value {
+    // Returns the name of the type as it is written in Nukleus source.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Void => "Void",
+            Value::None => "None",
+            Value::I8(_) => "i8",
+            Value::I16(_) => "i16",
+            Value::I32(_) => "i32",
+            Value::I64(_) => "i64",
+            Value::U8(_) => "u8",
+            Value::U16(_) => "u16",
+            Value::U32(_) => "u32",
+            Value::U64(_) => "u64",
+            Value::F32(_) => "f32",
+            Value::F64(_) => "f64",
+            Value::Bool(_) => "Bool",
+            Value::QuotedString(_) => "String",
+            Value::Char(_) => "Char",
+            Value::Array(_) => "Array",
+            Value::Map(_) => "Map",
+            Value::Bytes(_) => "Bytes",
+            Value::Tuple(_) => "Tuple",
+            Value::Uninitialized(_) => "Uninitialized",
+            Value::Function(_) => "Function",
+        }
+    }
+    pub fn as_integer(&self) -> Option<i128> {
+        match *self {
+            Value::I8(n) => Some(n.into()),
+            Value::I16(n) => Some(n.into()),
+            Value::I32(n) => Some(n.into()),
+            Value::I64(n) => Some(n.into()),
+            Value::U8(n) => Some(n.into()),
+            Value::U16(n) => Some(n.into()),
+            Value::U32(n) => Some(n.into()),
+            Value::U64(n) => Some(n.into()),
+            _ => None,
+        }
+    }
+    // Integers are promoted to floats, anything else is not a number.
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            Value::F32(n) => Some(n.into()),
+            Value::F64(n) => Some(n),
+            _ => self.as_integer().map(|n| n as f64),
+        }
+    }
+    // Builds an integer of the same type as `self`, `None` when `n` does not fit.
+    pub fn with_integer(&self, n: i128) -> Option<Value> {
+        match self {
+            Value::I8(_) => i8::try_from(n).ok().map(Value::I8),
+            Value::I16(_) => i16::try_from(n).ok().map(Value::I16),
+            Value::I32(_) => i32::try_from(n).ok().map(Value::I32),
+            Value::I64(_) => i64::try_from(n).ok().map(Value::I64),
+            Value::U8(_) => u8::try_from(n).ok().map(Value::U8),
+            Value::U16(_) => u16::try_from(n).ok().map(Value::U16),
+            Value::U32(_) => u32::try_from(n).ok().map(Value::U32),
+            Value::U64(_) => u64::try_from(n).ok().map(Value::U64),
+            _ => None,
+        }
+    }
+    // Keeps the low bits of `n` that fit the integer type of the value, like two's complement
+    // hardware does on overflow.
+    pub fn wrap_integer(&self, n: i128) -> Option<Value> {
+        match self {
+            Value::I8(_) => Some(Value::I8(n as i8)),
+            Value::I16(_) => Some(Value::I16(n as i16)),
+            Value::I32(_) => Some(Value::I32(n as i32)),
+            Value::I64(_) => Some(Value::I64(n as i64)),
+            Value::U8(_) => Some(Value::U8(n as u8)),
+            Value::U16(_) => Some(Value::U16(n as u16)),
+            Value::U32(_) => Some(Value::U32(n as u32)),
+            Value::U64(_) => Some(Value::U64(n as u64)),
+            _ => None,
+        }
+    }
+    // `+`, `-` and `*` modulo the size of the integer type instead of an overflow error.
+    // The low bits of the i128 result are exact even when it wraps itself.
+    pub fn wrapping(&self, op: &ASTOperator, other: &Value) -> Option<Value> {
+        let (template, a, b) = self.unify_integers(other)?;
+        let n = match op {
+            ASTOperator::Add => a.wrapping_add(b),
+            ASTOperator::Subtract => a.wrapping_sub(b),
+            ASTOperator::Multiply => a.wrapping_mul(b),
+            _ => return None,
+        };
+        template.wrap_integer(n)
+    }
+
+    // Converts the value to the declared type of a `let`.
+    pub fn cast(self, type_name: ASTtypename) -> Result<Value, RuntimeError> {
+        let expected = type_label(type_name);
+        if let Some(template) = integer_template(type_name) {
+            let n = self.as_integer().ok_or_else(|| {
+                RuntimeError::MismatchedTypes(expected.to_string(), self.type_name().to_string())
+            })?;
+            return template
+                .with_integer(n)
+                .ok_or_else(|| RuntimeError::IntegerOverflow(expected.to_string()));
+        }
+        if let Some(template) = float_template(type_name) {
+            let n = self.as_float().ok_or_else(|| {
+                RuntimeError::MismatchedTypes(expected.to_string(), self.type_name().to_string())
+            })?;
+            return Ok(template.with_float(n));
+        }
+        if self.type_name() == expected {
+            Ok(self)
+        } else {
+            Err(RuntimeError::MismatchedTypes(
+                expected.to_string(),
+                self.type_name().to_string(),
+            ))
+        }
+    }
+
+    // Integers of the same type are used as is, an `i64` (the type of every integer literal)
+    // adapts to the type of the other operand when it fits.
+    fn unify_integers(&self, other: &Value) -> Option<(Value, i128, i128)> {
+        let a = self.as_integer()?;
+        let b = other.as_integer()?;
+        if self.type_name() == other.type_name() {
+            return Some((self.clone(), a, b));
+        }
+        match (self, other) {
+            (Value::I64(_), _) => other.with_integer(a).map(|_| (other.clone(), a, b)),
+            (_, Value::I64(_)) => self.with_integer(b).map(|_| (self.clone(), a, b)),
+            _ => None,
+        }
+    }
+
+    // The promotion table shared by every binary operator:
+    //   int    op int    -> int     (an i64 literal adapts to the other integer type)
+    //   int    op float  -> float   (either order, f32 unless an f64 is involved)
+    //   float  op float  -> float
+    //   String op String -> String  (only `+` and comparisons)
+    // Every other combination is an `InvalidOperands` error. Comparing an int with a float
+    // uses their exact values instead, see `integer_float_ordering`.
+    fn promote<'a>(&'a self, other: &'a Value) -> Option<Operands<'a>> {
+        match (self, other) {
+            (Value::QuotedString(a), Value::QuotedString(b)) => Some(Operands::Text(a, b)),
+            (Value::F32(_) | Value::F64(_), _) | (_, Value::F32(_) | Value::F64(_)) => {
+                let template = match (self, other) {
+                    (Value::F64(_), _) | (_, Value::F64(_)) => Value::F64(0.0),
+                    _ => Value::F32(0.0),
+                };
+                Some(Operands::Float(
+                    template,
+                    self.as_float()?,
+                    other.as_float()?,
+                ))
+            }
+            _ => self
+                .unify_integers(other)
+                .map(|(template, a, b)| Operands::Integer(template, a, b)),
+        }
+    }
+    // An integer and a float, the operands `promote` turns into two floats
+    pub fn mixes_numbers(&self, other: &Value) -> bool {
+        let is_float = |value: &Value| matches!(value, Value::F32(_) | Value::F64(_));
+        (is_float(self) && other.as_integer().is_some())
+            || (self.as_integer().is_some() && is_float(other))
+    }
+    fn with_float(&self, n: f64) -> Value {
+        match self {
+            Value::F32(_) => Value::F32(n as f32),
+            _ => Value::F64(n),
+        }
+    }
+
+    // Converts a value assigned to the variable `name` to the type of its `current` value.
+    // As in the promotion table an `i64` adapts to another integer type and integers and
+    // `f32` widen to a float, `Void` and `none` have no type so they take or replace anything.
+    pub fn assign_to(self, name: &str, current: &Value) -> Result<Value, RuntimeError> {
+        let mismatch = || {
+            RuntimeError::AssignmentMismatch(
+                name.to_string(),
+                current.type_name().to_string(),
+                self.type_name().to_string(),
+            )
+        };
+        match (current, &self) {
+            (Value::Uninitialized(Some(type_name)), _) => self.cast(*type_name),
+            (Value::Void | Value::None | Value::Uninitialized(None), _) | (_, Value::None) => {
+                Ok(self)
+            }
+            _ if current.type_name() == self.type_name() => Ok(self),
+            (_, Value::I64(n)) if current.as_integer().is_some() => current
+                .with_integer((*n).into())
+                .ok_or_else(|| RuntimeError::IntegerOverflow(current.type_name().to_string())),
+            (Value::F32(_) | Value::F64(_), Value::F64(_)) => Err(mismatch()),
+            (Value::F32(_) | Value::F64(_), _) => match self.as_float() {
+                Some(n) => Ok(current.with_float(n)),
+                None => Err(mismatch()),
+            },
+            _ => Err(mismatch()),
+        }
+    }
+
+    pub fn binary(&self, op: &ASTOperator, other: &Value) -> Result<Value, RuntimeError> {
+        if *self == Value::Void || *other == Value::Void {
+            return Err(RuntimeError::VoidValue());
+        }
+        if *self == Value::None || *other == Value::None {
+            return match op {
+                ASTOperator::Equals => Ok(Value::Bool(self == other)),
+                ASTOperator::NotEquals => Ok(Value::Bool(self != other)),
+                _ => Err(RuntimeError::NoneValue(op.to_string())),
+            };
+        }
+        match op {
+            ASTOperator::Add
+            | ASTOperator::Subtract
+            | ASTOperator::Multiply
+            | ASTOperator::Divide
+            | ASTOperator::Remainder
+            | ASTOperator::Power
+            | ASTOperator::BitAnd
+            | ASTOperator::BitOr
+            | ASTOperator::BitXor
+            | ASTOperator::BitShiftLeft
+            | ASTOperator::BitShiftRight => self.arithmetic(op, other),
+            ASTOperator::Equals
+            | ASTOperator::NotEquals
+            | ASTOperator::Less
+            | ASTOperator::LessEquals
+            | ASTOperator::Greater
+            | ASTOperator::GreaterEquals => self.compare(op, other),
+            _ => Err(self.invalid_operands(op, other)),
+        }
+    }
+    fn arithmetic(&self, op: &ASTOperator, other: &Value) -> Result<Value, RuntimeError> {
+        match self.promote(other) {
+            Some(Operands::Integer(template, a, b)) => {
+                self.integer_arithmetic(op, other, template, a, b)
+            }
+            Some(Operands::Float(template, a, b)) => {
+                self.float_arithmetic(op, other, template, a, b)
+            }
+            Some(Operands::Text(a, b)) if *op == ASTOperator::Add => {
+                Ok(Value::QuotedString(format!("{}{}", a, b)))
+            }
+            _ => Err(self.invalid_operands(op, other)),
+        }
+    }
+    fn integer_arithmetic(
+        &self,
+        op: &ASTOperator,
+        other: &Value,
+        template: Value,
+        a: i128,
+        b: i128,
+    ) -> Result<Value, RuntimeError> {
+        let result = match op {
+            ASTOperator::Add => a.checked_add(b),
+            ASTOperator::Subtract => a.checked_sub(b),
+            ASTOperator::Multiply => a.checked_mul(b),
+            ASTOperator::Divide | ASTOperator::Remainder if b == 0 => {
+                return Err(RuntimeError::DivisionByZero())
+            }
+            ASTOperator::Divide => a.checked_div(b),
+            ASTOperator::Remainder => a.checked_rem(b),
+            ASTOperator::Power if b < 0 => return Err(RuntimeError::NegativeExponent(b)),
+            ASTOperator::Power => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+            ASTOperator::BitAnd => Some(a & b),
+            ASTOperator::BitOr => Some(a | b),
+            ASTOperator::BitXor => Some(a ^ b),
+            ASTOperator::BitShiftLeft => u32::try_from(b).ok().and_then(|s| a.checked_shl(s)),
+            ASTOperator::BitShiftRight => u32::try_from(b).ok().and_then(|s| a.checked_shr(s)),
+            _ => return Err(self.invalid_operands(op, other)),
+        };
+        result
+            .and_then(|n| template.with_integer(n))
+            .ok_or_else(|| RuntimeError::IntegerOverflow(template.type_name().to_string()))
+    }
+    fn float_arithmetic(
+        &self,
+        op: &ASTOperator,
+        other: &Value,
+        template: Value,
+        a: f64,
+        b: f64,
+    ) -> Result<Value, RuntimeError> {
+        let result = match op {
+            ASTOperator::Add => a + b,
+            ASTOperator::Subtract => a - b,
+            ASTOperator::Multiply => a * b,
+            ASTOperator::Divide | ASTOperator::Remainder if b == 0.0 => {
+                return Err(RuntimeError::DivisionByZero())
+            }
+            ASTOperator::Divide => a / b,
+            ASTOperator::Remainder => a % b,
+            ASTOperator::Power => a.powf(b),
+            _ => return Err(self.invalid_operands(op, other)),
+        };
+        Ok(template.with_float(result))
+    }
+    fn compare(&self, op: &ASTOperator, other: &Value) -> Result<Value, RuntimeError> {
+        let equality = matches!(op, ASTOperator::Equals | ASTOperator::NotEquals);
+        if equality && (self.is_aggregate() || other.is_aggregate()) {
+            // The error names the innermost values that do not compare
+            let equal = self
+                .deep_equals(other)
+                .map_err(|(a, b)| a.invalid_operands(op, b))?;
+            return Ok(Value::Bool(equal == (*op == ASTOperator::Equals)));
+        }
+        let ordering = self
+            .ordering(other)
+            .ok_or_else(|| self.invalid_operands(op, other))?;
+        ordering_result(op, ordering).ok_or_else(|| self.invalid_operands(op, other))
+    }
+    fn is_aggregate(&self) -> bool {
+        matches!(self, Value::Array(_) | Value::Tuple(_) | Value::Map(_))
+    }
+    // `==` of the language on arrays and tuples element by element and on maps field by
+    // field in any order, an error with two values at the same place whose types do not
+    // compare. Different lengths or fields are unequal without looking at the values.
+    fn deep_equals<'a>(&'a self, other: &'a Value) -> Result<bool, (&'a Value, &'a Value)> {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                if a.len() != b.len() {
+                    return Ok(false);
+                }
+                let mut equal = true;
+                for (a, b) in a.iter().zip(b) {
+                    equal &= a.deep_equals(b)?;
+                }
+                Ok(equal)
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                let fields = a
+                    .iter()
+                    .map(|(key, a)| Some((a, &b.iter().find(|(other, _)| other == key)?.1)))
+                    .collect::<Option<Vec<_>>>();
+                let fields = match fields {
+                    Some(fields) if a.len() == b.len() => fields,
+                    _ => return Ok(false),
+                };
+                let mut equal = true;
+                for (a, b) in fields {
+                    equal &= a.deep_equals(b)?;
+                }
+                Ok(equal)
+            }
+            (Value::None, _) | (_, Value::None) => Ok(self == other),
+            _ if self.is_aggregate() || other.is_aggregate() => Err((self, other)),
+            _ => match self.ordering(other) {
+                Some(ordering) => Ok(ordering == Some(Ordering::Equal)),
+                None => Err((self, other)),
+            },
+        }
+    }
+    // The order of two values, `None` for types that do not compare and `Some(None)` for NaN
+    pub fn ordering(&self, other: &Value) -> Option<Option<Ordering>> {
+        if let Some(ordering) = self.integer_float_ordering(other) {
+            return Some(ordering);
+        }
+        match (self, other) {
+            (Value::Char(a), Value::Char(b)) => Some(Some(a.cmp(b))),
+            (Value::Bool(a), Value::Bool(b)) => Some(Some(a.cmp(b))),
+            (Value::Bytes(a), Value::Bytes(b)) => Some(Some(a.cmp(b))),
+            _ => match self.promote(other)? {
+                Operands::Integer(_, a, b) => Some(Some(a.cmp(&b))),
+                Operands::Float(_, a, b) => Some(a.partial_cmp(&b)),
+                Operands::Text(a, b) => Some(Some(a.cmp(b))),
+            },
+        }
+    }
+    // `1 == 1.0` holds and `1 < 2.0` orders as expected, but the integer is not rounded to
+    // a float first, so a large integer never equals a float that only rounds to it.
+    fn integer_float_ordering(&self, other: &Value) -> Option<Option<Ordering>> {
+        match (self, other) {
+            (Value::F32(_) | Value::F64(_), _) if other.as_integer().is_some() => other
+                .integer_float_ordering(self)
+                .map(|ordering| ordering.map(Ordering::reverse)),
+            (_, Value::F32(_) | Value::F64(_)) => {
+                Some(compare_integer_float(self.as_integer()?, other.as_float()?))
+            }
+            _ => None,
+        }
+    }
+    pub fn invalid_operands(&self, op: &ASTOperator, other: &Value) -> RuntimeError {
+        RuntimeError::InvalidOperands(
+            op.to_string(),
+            self.type_name().to_string(),
+            other.type_name().to_string(),
+        )
+    }
+
+    pub fn index(&self, key: &Value) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Array(values) => Ok(values[array_position(key, values.len())?].clone()),
+            Value::Bytes(bytes) => Ok(Value::U8(bytes[array_position(key, bytes.len())?])),
+            Value::Map(entries) => {
+                let key = map_key(key)?;
+                entries
+                    .iter()
+                    .find(|(entry, _)| *entry == key)
+                    .map(|(_, value)| value.clone())
+                    .ok_or_else(|| RuntimeError::MissingKey(key.to_string()))
+            }
+            _ => Err(RuntimeError::NotIndexable(self.type_name().to_string())),
+        }
+    }
+    // `value.name`, fields are the string keys of a map and the positions of a tuple
+    pub fn field(&self, name: &str) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Map(_) => self.index(&Value::QuotedString(name.to_string())),
+            Value::Tuple(values) => name
+                .parse::<usize>()
+                .ok()
+                .and_then(|position| values.get(position).cloned())
+                .ok_or_else(|| RuntimeError::NoField("Tuple".to_string(), name.to_string())),
+            _ => Err(RuntimeError::NoField(
+                self.type_name().to_string(),
+                name.to_string(),
+            )),
+        }
+    }
+    // Returns the slot behind `key`, missing map keys are inserted when `insert` is set.
+    pub fn index_mut(&mut self, key: &Value, insert: bool) -> Result<&mut Value, RuntimeError> {
+        match self {
+            Value::Array(values) => {
+                let position = array_position(key, values.len())?;
+                Ok(&mut values[position])
+            }
+            Value::Map(entries) => {
+                let key = map_key(key)?;
+                match entries.iter().position(|(entry, _)| *entry == key) {
+                    Some(position) => Ok(&mut entries[position].1),
+                    None if insert => {
+                        entries.push((key, Value::Void));
+                        let last = entries.len() - 1;
+                        Ok(&mut entries[last].1)
+                    }
+                    None => Err(RuntimeError::MissingKey(key.to_string())),
+                }
+            }
+            _ => Err(RuntimeError::NotIndexable(self.type_name().to_string())),
+        }
+    }
+
+    // Representation used inside of arrays and maps and in scope dumps, where strings are quoted.
+    pub fn literal(&self) -> String {
+        match self {
+            Value::QuotedString(s) => format!("\"{}\"", s),
+            Value::Char(c) => format!("'{}'", c),
+            _ => self.to_string(),
+        }
+    }
+}
+
+// Structural equality, for tests and caches: values of different types are never equal
+// and floats are equal when their bits are, so `NaN` equals itself and `Eq` holds.
+// The `==` of the language promotes numbers and is implemented by `binary`.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Void, Value::Void) | (Value::None, Value::None) => true,
+            (Value::I8(a), Value::I8(b)) => a == b,
+            (Value::I16(a), Value::I16(b)) => a == b,
+            (Value::I32(a), Value::I32(b)) => a == b,
+            (Value::I64(a), Value::I64(b)) => a == b,
+            (Value::U8(a), Value::U8(b)) => a == b,
+            (Value::U16(a), Value::U16(b)) => a == b,
+            (Value::U32(a), Value::U32(b)) => a == b,
+            (Value::U64(a), Value::U64(b)) => a == b,
+            (Value::F32(a), Value::F32(b)) => a.to_bits() == b.to_bits(),
+            (Value::F64(a), Value::F64(b)) => a.to_bits() == b.to_bits(),
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::QuotedString(a), Value::QuotedString(b))
+            | (Value::Function(a), Value::Function(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) | (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::Uninitialized(a), Value::Uninitialized(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+impl Eq for Value {}
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Void | Value::None => {}
+            Value::I8(n) => n.hash(state),
+            Value::I16(n) => n.hash(state),
+            Value::I32(n) => n.hash(state),
+            Value::I64(n) => n.hash(state),
+            Value::U8(n) => n.hash(state),
+            Value::U16(n) => n.hash(state),
+            Value::U32(n) => n.hash(state),
+            Value::U64(n) => n.hash(state),
+            Value::F32(n) => n.to_bits().hash(state),
+            Value::F64(n) => n.to_bits().hash(state),
+            Value::Bool(b) => b.hash(state),
+            Value::QuotedString(s) | Value::Function(s) => s.hash(state),
+            Value::Char(c) => c.hash(state),
+            Value::Array(values) | Value::Tuple(values) => values.hash(state),
+            Value::Map(entries) => entries.hash(state),
+            Value::Bytes(bytes) => bytes.hash(state),
+            Value::Uninitialized(type_name) => type_name.hash(state),
+        }
+    }
+}
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Void => write!(f, "Void"),
+            Value::None => write!(f, "none"),
+            Value::I8(n) => write!(f, "{}", n),
+            Value::I16(n) => write!(f, "{}", n),
+            Value::I32(n) => write!(f, "{}", n),
+            Value::I64(n) => write!(f, "{}", n),
+            Value::U8(n) => write!(f, "{}", n),
+            Value::U16(n) => write!(f, "{}", n),
+            Value::U32(n) => write!(f, "{}", n),
+            Value::U64(n) => write!(f, "{}", n),
+            Value::F32(n) => write!(f, "{:?}", n),
+            Value::F64(n) => write!(f, "{:?}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::QuotedString(s) => write!(f, "{}", s),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::Array(values) => write!(
+                f,
+                "[{}]",
+                values
+                    .iter()
+                    .map(|value| value.literal())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Map(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.literal(), value.literal()))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Bytes(bytes) => write!(f, "{}", byte_string_literal(bytes)),
+            Value::Tuple(values) => write!(
+                f,
+                "({})",
+                values
+                    .iter()
+                    .map(|value| value.literal())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Uninitialized(_) => write!(f, "uninitialized"),
+            Value::Function(name) => write!(f, "fn {}", name),
+        }
+    }
+}
+
+// Returns the name of the type as it is written in Nukleus source.
+pub fn type_label(type_name: ASTtypename) -> &'static str {
+    match type_name {
+        ASTtypename::TypeVoid => "Void",
+        ASTtypename::I8 => "i8",
+        ASTtypename::I16 => "i16",
+        ASTtypename::I32 => "i32",
+        ASTtypename::I64 => "i64",
+        ASTtypename::U8 => "u8",
+        ASTtypename::U16 => "u16",
+        ASTtypename::U32 => "u32",
+        ASTtypename::U64 => "u64",
+        ASTtypename::F32 => "f32",
+        ASTtypename::F64 => "f64",
+        ASTtypename::Bool => "Bool",
+        ASTtypename::QuotedString => "String",
+        ASTtypename::Char => "Char",
+        ASTtypename::Array => "Array",
+        ASTtypename::Map => "Map",
+        ASTtypename::Bytes => "Bytes",
+        ASTtypename::Tuple => "Tuple",
+        ASTtypename::Identifier => "Identifier",
+        ASTtypename::Argument => "Argument",
+        ASTtypename::FunctionCall => "FunctionCall",
+        ASTtypename::Function => "Function",
+    }
+}
+// Formats a function type as it is written in Nukleus source, `fn(i32) -> i32`
+pub fn signature_label(signature: &ASTfunctiontype) -> String {
+    let params = signature
+        .params
+        .iter()
+        .map(|param| type_label(*param))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match signature.return_type {
+        ASTtypename::TypeVoid => format!("fn({})", params),
+        return_type => format!("fn({}) -> {}", params, type_label(return_type)),
+    }
+}
+fn integer_template(type_name: ASTtypename) -> Option<Value> {
+    match type_name {
+        ASTtypename::I8 => Some(Value::I8(0)),
+        ASTtypename::I16 => Some(Value::I16(0)),
+        ASTtypename::I32 => Some(Value::I32(0)),
+        ASTtypename::I64 => Some(Value::I64(0)),
+        ASTtypename::U8 => Some(Value::U8(0)),
+        ASTtypename::U16 => Some(Value::U16(0)),
+        ASTtypename::U32 => Some(Value::U32(0)),
+        ASTtypename::U64 => Some(Value::U64(0)),
+        _ => None,
+    }
+}
+// `None` for a NaN, which is unordered
+fn compare_integer_float(a: i128, b: f64) -> Option<Ordering> {
+    if b.is_nan() {
+        return None;
+    }
+    // Floats beyond the range of i128 are beyond every integer
+    if b >= i128::MAX as f64 {
+        return Some(Ordering::Less);
+    }
+    if b < i128::MIN as f64 {
+        return Some(Ordering::Greater);
+    }
+    let whole = b.trunc();
+    Some(a.cmp(&(whole as i128)).then(0.0.partial_cmp(&(b - whole))?))
+}
+// A NaN operand is unordered, only `!=` holds for it
+fn ordering_result(op: &ASTOperator, ordering: Option<Ordering>) -> Option<Value> {
+    let Some(ordering) = ordering else {
+        return Some(Value::Bool(*op == ASTOperator::NotEquals));
+    };
+    let result = match op {
+        ASTOperator::Equals => ordering.is_eq(),
+        ASTOperator::NotEquals => ordering.is_ne(),
+        ASTOperator::Less => ordering.is_lt(),
+        ASTOperator::LessEquals => ordering.is_le(),
+        ASTOperator::Greater => ordering.is_gt(),
+        ASTOperator::GreaterEquals => ordering.is_ge(),
+        _ => return None,
+    };
+    Some(Value::Bool(result))
+}
+fn float_template(type_name: ASTtypename) -> Option<Value> {
+    match type_name {
+        ASTtypename::F32 => Some(Value::F32(0.0)),
+        ASTtypename::F64 => Some(Value::F64(0.0)),
+        _ => None,
+    }
+}
+fn array_position(key: &Value, len: usize) -> Result<usize, RuntimeError> {
+    let index = key.as_integer().ok_or_else(|| {
+        RuntimeError::InvalidIndex("Array".to_string(), key.type_name().to_string())
+    })?;
+    usize::try_from(index)
+        .ok()
+        .filter(|position| *position < len)
+        .ok_or(RuntimeError::IndexOutOfBounds(index, len))
+}
+// Floats are not keys, `NaN` is not equal to itself and `0.0` is equal to `-0.0`.
+// Integer keys of any type find the same entry, `m[1]` and `m[x]` for `let:u8 x = 1`.
+fn map_key(key: &Value) -> Result<Value, RuntimeError> {
+    match key {
+        Value::QuotedString(_) | Value::Bool(_) | Value::Char(_) => Ok(key.clone()),
+        _ => match key.as_integer() {
+            Some(n) => Ok(i64::try_from(n).map_or_else(|_| key.clone(), Value::I64)),
+            None => Err(RuntimeError::InvalidMapKey(key.type_name().to_string())),
+        },
+    }
+}
@@ -1,3 +1 @@
-mod interpret;
-
-pub use interpret::Interpreter;
+pub mod interpret_new;
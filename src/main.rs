@@ -3,6 +3,7 @@
 //pub mod compiler;
 
 pub mod cores;
+mod emit;
 mod errors;
 pub mod interpreter;
 
@@ -11,7 +12,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use codegen::cranelift_jit::save_executable;
 use codegen::cranelift_jit::JIT;
 // use codegen::JIT;
@@ -24,6 +25,64 @@ fn cli() -> Command {
         .author("Skuld Norniern. <skuldnorniern@gmail.com>")
         .about("Nukleus Language")
         .arg(Arg::new("input").default_value("repl"))
+        .arg(
+            Arg::new("interpret")
+                .long("interpret")
+                .help("Run the program with the tree walking interpreter instead of the JIT")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("trace")
+                .long("trace")
+                .help("Log each statement to stderr before the interpreter runs it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dump-scopes")
+                .long("dump-scopes")
+                .help("Print the variables in scope to stderr after each interpreted statement")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("define")
+                .long("define")
+                .value_name("NAME=VALUE")
+                .help("Give the interpreter an integer or string constant, may be repeated")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("optimize")
+                .long("optimize")
+                .help("Inline trivial functions and drop unused ones before interpreting")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .help("Print diagnostics without ANSI colors, also set by NO_COLOR")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("error-format")
+                .long("error-format")
+                .help("Write lexer and parser errors to stderr as human readable text or JSON")
+                .value_parser(["human", "json"])
+                .default_value("human"),
+        )
+        .arg(
+            Arg::new("max-errors")
+                .long("max-errors")
+                .value_name("N")
+                .help("Report the first N lexer or parser errors and only count the rest")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("20"),
+        )
+        .arg(
+            Arg::new("emit")
+                .long("emit")
+                .help("Print an intermediate representation instead of running the program")
+                .value_parser(["tokens-json"]),
+        )
 }
 
 fn read_file(filename: &str) -> Result<String, std::io::Error> {
@@ -58,10 +117,35 @@ fn read_file(filename: &str) -> Result<String, std::io::Error> {
     Ok(contents)
 }
 
-// fn run_interpreter_environment() {
-// let _interpreter = interpreter::Interpreter::new();
-//interpreter.run_repl();
-// }
+// Reads and runs one line at a time until the input ends, errors are reported and skipped
+fn run_repl() {
+    println!("Nukleus {}", env!("CARGO_PKG_VERSION"));
+    let mut interpreter = interpreter::interpret_new::Interpreter::new();
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        let _ = std::io::stdout().flush();
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Error reading input: {}", e);
+                break;
+            }
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match interpreter.eval_line(&line) {
+            Err(interpreter::interpret_new::EvalError::Exit(code)) => std::process::exit(code),
+            // `eval_line` already wrote the error
+            Ok(interpreter::interpret_new::Value::Void) | Err(_) => {}
+            Ok(value) => println!("{}", value),
+        }
+    }
+    println!();
+}
 
 fn main() {
     let matches = cli().get_matches();
@@ -74,9 +158,8 @@ fn main() {
         }
     };
 
-    let mut interpreter = interpreter::Interpreter::new();
     if input == "repl" {
-        interpreter.run_repl();
+        run_repl();
         return;
     }
 
@@ -88,74 +171,40 @@ fn main() {
         }
     };
 
-    let mut new_lexer = lexer::lex_new::Lexer::new(&contents);
-    // check the time between the two lexers
-    let start_time_new = std::time::Instant::now();
-    new_lexer.run();
-    let end_time_new = std::time::Instant::now();
-    let duration_new = end_time_new.duration_since(start_time_new);
-    let new_tokens = new_lexer.get_tokens();
-
-    let mut new_new_lexer =
-        lexer::lex_new_new::Lexer::new(Path::new(input).to_path_buf(), &contents);
-    let start_time_new_new = std::time::Instant::now();
-    let lex_result = new_new_lexer.run();
-    if let Err(e) = lex_result {
-        println!("new_new_lexer Error: {}", e);
+    if let Some(emit) = matches.get_one::<String>("emit") {
+        emit_output(emit, &contents);
         return;
     }
+
+    let json_errors = matches.get_one::<String>("error-format").map(String::as_str) == Some("json");
+    let max_errors = matches.get_one::<usize>("max-errors").copied().unwrap_or(20);
+    let start_time_new_new = std::time::Instant::now();
+    // With JSON the errors are only reported once they are all found
+    let lex_result = if json_errors {
+        lexer::lex(&contents)
+    } else {
+        lexer::lex_file(Path::new(input), &contents, max_errors)
+    };
+    let new_new_tokens = match lex_result {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            report_lex_errors(&errors, json_errors, max_errors);
+            return;
+        }
+    };
     let end_time_new_new = std::time::Instant::now();
     let duration_new_new = end_time_new_new.duration_since(start_time_new_new);
-    let new_new_tokens = new_new_lexer.get_tokens();
 
     // #[cfg(debug_assertions)]
     println!("Neo Lexer Time: {:?}", duration_new_new);
-
-    let mut trie_lexer = lexer::trie_lex::Lexer::new(Path::new(input).to_path_buf(), &contents);
-    let start_time_trie = std::time::Instant::now();
-    let trie_result = trie_lexer.run();
-    if let Err(e) = trie_result {
-        println!("Trie Error: {}", e);
-        return;
-    }
-    let end_time_trie = std::time::Instant::now();
-    let duration_trie = end_time_trie.duration_since(start_time_trie);
-
-    println!("Trie Lexer Time: {:?}", duration_trie);
-    //println!("New Tokens: {:?}", new_tokens);
-    // let start_time_old = std::time::Instant::now();
-    // let tokens = lexer(&contents);
-    // let end_time_old = std::time::Instant::now();
-    // let duration_old = end_time_old.duration_since(start_time_old);
-    #[cfg(debug_assertions)]
-    println!("New Lexer Time: {:?}", duration_new);
-    // println!("Old Lexer Time: {:?}", duration_old);
-    #[cfg(debug_assertions)]
-    {
-        for token in &new_tokens {
-            // println!("{}", token);
-        }
-    }
-    // calulate how times faster the new lexer is
-    // let speedup = duration_old.as_nanos() as f64 / duration_new.as_nanos() as f64;
-    // println!("Speedup: {}x", speedup);
-    // calculate how much characters the old lexer can lex per second
-    // let old_chars_per_second = contents.len() as f64 / duration_old.as_secs_f64();
-    // println!("Old Chars Per Second: {}", old_chars_per_second);
-    // calculate how much characters the new lexer can lex per second
-    let new_chars_per_second = contents.len() as f64 / duration_new.as_secs_f64();
-    let _new_chars_mb_per_second = new_chars_per_second * 4.0 / 1024.0 / 1024.0;
     let new_new_chars_per_second = contents.len() as f64 / duration_new_new.as_secs_f64();
     let _new_new_chars_mb_per_second = new_new_chars_per_second * 4.0 / 1024.0 / 1024.0;
     #[cfg(debug_assertions)]
     {
-        println!("New Chars Per Second: {}", new_chars_per_second);
-        println!("New Chars MB/s: {}", new_chars_mb_per_second);
         println!("New New Chars Per Second: {}", new_new_chars_per_second);
         println!("New New Chars MB/s: {}", new_new_chars_mb_per_second);
     }
     // println!("New New Lexer Contents: {:?}", new_new_tokens);
-    // println!("New Lexer Contents: {:?}", new_tokens);
 
     // println!("Tokens: {:?}", tokens);
     // let ast = core::parser_new::parse::Parser::new(tokens).parse();
@@ -166,14 +215,22 @@ fn main() {
     // let duration_parser_old = end_time_parser_old.duration_since(start_time_parser_old);
     // println!("Old Parser Time: {:?}", duration_parser_old);
     let mut mid_ir =
-        astgen::parser_new::Parser::new(new_new_tokens, Path::new(input).to_path_buf(), &contents);
+        astgen::parser_new::Parser::new(&new_new_tokens, Path::new(input).to_path_buf(), &contents);
 
+    if matches.get_flag("no-color") {
+        mid_ir.set_color(false);
+    }
+    mid_ir.set_print_errors(!json_errors);
+    mid_ir.set_max_errors(Some(max_errors));
     let start_time_parser_new = std::time::Instant::now();
-    let ast_result = mid_ir.run();
-    if let Err(e) = ast_result {
-        println!("Error: {}", e);
+    // Folding is part of interpreting, the JIT compiles the program as it was written
+    let fold = matches.get_flag("interpret");
+    if !parse_program(&mut mid_ir, json_errors, max_errors, fold) {
         return;
     }
+    if matches.get_flag("optimize") {
+        optimize_program(&mut mid_ir, matches.get_flag("interpret"));
+    }
     let end_time_parser_new = std::time::Instant::now();
     let ast_new = mid_ir.get_asts();
 
@@ -186,7 +243,7 @@ fn main() {
 
     // let old_tokens_per_second = tokens.len() as f64 / duration_parser_old.as_secs_f64();
     // println!("Old Tokens Per Second: {}", old_tokens_per_second);
-    let _new_tokens_per_second = new_tokens.len() as f64 / duration_parser_new.as_secs_f64();
+    let _new_tokens_per_second = new_new_tokens.len() as f64 / duration_parser_new.as_secs_f64();
     #[cfg(debug_assertions)]
     println!("New Tokens Per Second: {}", new_tokens_per_second);
 
@@ -223,6 +280,14 @@ fn main() {
 
     // println!("{:?}",ast_new);
     //
+    if matches.get_flag("interpret") {
+        let defines = matches.get_many::<String>("define").into_iter().flatten();
+        let mut interpreter = interpreter::interpret_new::Interpreter::new();
+        interpreter.set_trace(matches.get_flag("trace"));
+        interpreter.set_dump_scopes(matches.get_flag("dump-scopes"));
+        run_interpreter(interpreter, ast_new, defines);
+        return;
+    }
 
     // println!("{}", generate_ir(ast_new));
     // generate_ir(ast_new);
@@ -230,12 +295,13 @@ fn main() {
     let mut jit = JIT::default();
     #[cfg(debug_assertions)]
     println!("JIT: ");
-    let code_ptr_result = jit.compile(ast_new.clone(), input, false);
-    if let Err(e) = code_ptr_result {
-        println!("JIT Compile Error: {}", e);
-        return;
-    }
-    let raw_code_ptr = code_ptr_result.unwrap();
+    let raw_code_ptr = match jit.compile(ast_new.clone(), input, false) {
+        Ok(code_ptr) => code_ptr,
+        Err(e) => {
+            println!("JIT Compile Error: {}", e);
+            return;
+        }
+    };
     let end_time_jit = std::time::Instant::now();
     let duration_jit = end_time_jit.duration_since(start_time_jit);
     #[cfg(debug_assertions)]
@@ -259,13 +325,121 @@ fn main() {
         println!("Failed to save executable: {}", e);
     }
     drop(jit);
-    drop(new_new_lexer);
-    drop(new_lexer);
+    drop(new_new_tokens);
     drop(contents);
 
     // println!("Run TIme: {:?}", duration);
 }
 
+fn run_interpreter<'a>(
+    mut interpreter: interpreter::interpret_new::Interpreter,
+    program: &[astgen::AST],
+    defines: impl Iterator<Item = &'a String>,
+) {
+    for define in defines {
+        match parse_define(define) {
+            Some((name, value)) => interpreter.define(name, value),
+            None => {
+                println!("Error: `--define {}` is not NAME=VALUE", define);
+                return;
+            }
+        }
+    }
+    match interpreter.run(program) {
+        Ok(value) => println!("exit with value {}", value),
+        Err(interpreter::interpret_new::RuntimeError::Exit(code)) => std::process::exit(code),
+        Err(e) => println!("Runtime Error: {}", e),
+    }
+}
+
+// `NAME=VALUE`, the value is an `i64` when it is a number and a `String` otherwise
+fn parse_define(define: &str) -> Option<(&str, interpreter::interpret_new::Value)> {
+    let (name, value) = define.split_once('=')?;
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    if !(first.is_alphabetic() || first == '_') || !chars.all(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    let value = match value.parse::<i64>() {
+        Ok(number) => interpreter::interpret_new::Value::I64(number),
+        Err(_) => interpreter::interpret_new::Value::QuotedString(value.to_string()),
+    };
+    Some((name, value))
+}
+// Parses and folds the program, printing every error and warning that was found
+fn parse_program(
+    parser: &mut astgen::parser_new::Parser,
+    json_errors: bool,
+    max_errors: usize,
+    fold: bool,
+) -> bool {
+    // Without JSON the parser already wrote the errors, capped by `--max-errors`
+    if let Err(errors) = parser.run() {
+        if json_errors {
+            let entries = errors.iter().take(max_errors).map(emit::ErrorEntry::from);
+            report_json_errors(&entries.collect::<Vec<_>>());
+        }
+        return false;
+    }
+    for warning in parser.get_warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+    if !fold {
+        return true;
+    }
+    if let Err(e) = parser.fold_constants() {
+        if json_errors {
+            report_json_errors(&[emit::ErrorEntry::from(&e)]);
+        } else {
+            println!("Error: {}", e);
+        }
+        return false;
+    }
+    true
+}
+// The JIT can not compile the blocks the inliner produces, so only the interpreter uses it
+fn optimize_program(parser: &mut astgen::parser_new::Parser, interpret: bool) {
+    if interpret {
+        parser.optimize();
+    } else {
+        eprintln!("Warning: --optimize only applies with --interpret, the JIT ignores it");
+    }
+}
+// Without JSON the lexer already wrote the errors, capped by `--max-errors`
+fn report_lex_errors(errors: &[lexer::LexcialError], json_errors: bool, max_errors: usize) {
+    if json_errors {
+        let entries = errors.iter().take(max_errors).map(emit::ErrorEntry::from);
+        report_json_errors(&entries.collect::<Vec<_>>());
+    }
+}
+// `--error-format=json`, the errors go to stderr as one JSON array
+fn report_json_errors(errors: &[emit::ErrorEntry]) {
+    match emit::errors_json(errors) {
+        Ok(json) => eprintln!("{}", json),
+        Err(e) => eprintln!("Failed to serialize errors: {}", e),
+    }
+}
+
+fn emit_output(emit: &str, contents: &str) {
+    let tokens = match lexer::lex(contents) {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("{}", e);
+            }
+            return;
+        }
+    };
+    match emit {
+        "tokens-json" => match emit::tokens_json(&tokens) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to serialize tokens: {}", e),
+        },
+        _ => eprintln!("Unknown emit kind: {}", emit),
+    }
+}
+
 fn run(codeptr: *const u8) -> Result<isize, String> {
     unsafe { run_code(codeptr, ()) }
 }
@@ -0,0 +1,74 @@
+// Not every test binary uses every helper
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Numbers the sources of one test binary, the process id tells concurrent runs apart
+static NEXT_SOURCE: AtomicUsize = AtomicUsize::new(0);
+
+// A program written to a temp file of its own, removed again when dropped
+struct Source {
+    path: PathBuf,
+}
+
+impl Source {
+    fn new(code: &str) -> Self {
+        let name = format!(
+            "nukleus_test_{}_{}.nk",
+            std::process::id(),
+            NEXT_SOURCE.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, code).expect("source should be writable");
+        Source { path }
+    }
+    // Runs `nukleus` with the arguments and the path of the source last
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_nukleus"))
+            .args(args)
+            .arg(&self.path)
+            .output()
+            .expect("nukleus should run")
+    }
+}
+
+impl Drop for Source {
+    fn drop(&mut self) {
+        // A file left behind has a name no other run uses
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// Writes the program to a file of its own and runs `nukleus` on it
+pub fn run(args: &[&str], code: &str) -> Output {
+    Source::new(code).run(args)
+}
+
+// Runs `nukleus` with the arguments and the input on stdin, the REPL when there is no file
+pub fn run_with_input(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nukleus"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("nukleus should start");
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(input.as_bytes())
+        .expect("stdin should be writable");
+    child.wait_with_output().expect("nukleus should run")
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("stdout should be UTF-8")
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).expect("stderr should be UTF-8")
+}
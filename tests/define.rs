@@ -0,0 +1,24 @@
+mod common;
+
+#[test]
+fn define_injects_constants() {
+    let code = "fn main() {\n    println(\"{} {}\", N, NAME);\n}\n";
+    let output = common::run(&["--interpret", "--define", "N=5", "--define", "NAME=nk"], code);
+    assert!(output.status.success());
+
+    let stdout = common::stdout(&output);
+    assert!(stdout.contains("\n5 nk\n"), "{}", stdout);
+}
+
+#[test]
+fn define_conflicts_with_program_names() {
+    let code = "fn main() {\n    let N = 1;\n}\n";
+    let output = common::run(&["--interpret", "--define", "N=5"], code);
+
+    let stdout = common::stdout(&output);
+    assert!(
+        stdout.contains("Runtime Error: `N` is already defined with --define"),
+        "{}",
+        stdout
+    );
+}
@@ -0,0 +1,23 @@
+mod common;
+
+#[test]
+fn dump_scopes_after_let() {
+    let code = "fn main() {\n    let:i32 a = 5;\n    let b = \"x\";\n}\n";
+    let output = common::run(&["--interpret", "--dump-scopes"], code);
+    assert!(output.status.success());
+
+    let stderr = common::stderr(&output);
+    let lines: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("[scopes]"))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "[scopes] after let:I32 a = 5",
+            "[scopes]   0: a = 5",
+            "[scopes] after let b = x",
+            "[scopes]   0: a = 5, b = \"x\"",
+        ]
+    );
+}
@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn emit_tokens_json() {
+    let output = common::run(&["--emit=tokens-json"], "let:i32 a = 5;");
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let first = &json[0];
+    assert_eq!(first["kind"], "Statement");
+    assert_eq!(first["text"], "let");
+    assert_eq!(first["line"], 1);
+    assert_eq!(first["column"], 3);
+    assert_eq!(json.as_array().map(Vec::len), Some(7));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+// The interpreter lives in the binary crate, so it is included directly
+#[allow(dead_code)]
+#[path = "../src/interpreter/interpret_new/mod.rs"]
+mod interpret_new;
+
+use interpret_new::{run_source, EvalError, RuntimeError};
+
+#[test]
+fn empty_source_reports_missing_main() {
+    for source in ["", "  \n\n"] {
+        let error = run_source(source);
+        let no_main = matches!(error, Err(EvalError::Runtime(RuntimeError::NoMainFunction())));
+        assert!(no_main, "{:?}", source);
+    }
+}
+
+#[test]
+fn empty_program_reports_missing_main() {
+    for mode in [&["--interpret"][..], &[]] {
+        let output = common::run(mode, "  \n\n");
+        assert!(output.status.success(), "{:?}", mode);
+
+        let stdout = common::stdout(&output);
+        assert!(
+            stdout.contains("no `main` function found"),
+            "{:?}: {}",
+            mode,
+            stdout
+        );
+    }
+}
@@ -0,0 +1,24 @@
+mod common;
+
+#[test]
+fn error_format_json() {
+    let output = common::run(&["--error-format=json"], "fn main() {\n    let = 5;\n}\n");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let errors = json.as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["message"], "Unexpected token");
+    assert_eq!(errors[0]["line"], 2);
+    assert_eq!(errors[0]["column"], 9);
+    assert_eq!(errors[0]["suggestion"], "Unexpected token. Check syntax.");
+}
+
+#[test]
+fn error_format_json_lexer_error() {
+    let code = "fn main() {\n    let a = \"open;\n}\n";
+    let output = common::run(&["--error-format=json"], code);
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(json[0]["message"], "Expected quote");
+    assert!(json[0]["suggestion"].as_str().unwrap().contains("double quote"));
+}
@@ -0,0 +1,11 @@
+mod common;
+
+#[test]
+fn exit_sets_the_process_exit_code() {
+    let code = "fn main() {\n    println(\"{}\", 1);\n    exit(2);\n    println(\"{}\", 3);\n}\n";
+    let output = common::run(&["--interpret"], code);
+    assert_eq!(output.status.code(), Some(2));
+
+    let stdout = common::stdout(&output);
+    assert!(stdout.ends_with("1\n"), "{}", stdout);
+}
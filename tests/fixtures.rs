@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// The interpreter lives in the binary crate, so it is included directly
+#[allow(dead_code)]
+#[path = "../src/interpreter/interpret_new/mod.rs"]
+mod interpret_new;
+
+// Every `tests/fixtures/<name>.nk` is run and its output compared to `<name>.out`
+
+// Output buffer that can still be read after the interpreter took ownership of it
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Runs a fixture through the lexer, parser and interpreter, runtime errors are part of the output
+fn run_fixture(path: &Path, optimize: bool) -> String {
+    let code = fs::read_to_string(path).expect("fixture should be readable");
+    let mut lexer = lexer::lex_new_new::Lexer::new(path.to_path_buf(), &code);
+    lexer.run().expect("fixture should lex");
+    let mut parser = astgen::parser_new::Parser::new(lexer.tokens(), path.to_path_buf(), &code);
+    parser.run().expect("fixture should parse");
+    parser.fold_constants().expect("fixture should fold");
+    if optimize {
+        parser.optimize();
+    }
+
+    let output = SharedOutput::default();
+    let mut interpreter = interpret_new::Interpreter::new();
+    interpreter.set_output(Box::new(output.clone()));
+    if let Err(e) = interpreter.run(parser.get_asts()) {
+        output
+            .0
+            .borrow_mut()
+            .extend(format!("Runtime Error: {}\n", e).bytes());
+    }
+    let bytes = output.0.borrow().clone();
+    String::from_utf8(bytes).expect("output should be UTF-8")
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("tests/fixtures should exist")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "nk"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn fixtures_match_expected_output() {
+    let paths = fixtures();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let expected = fs::read_to_string(path.with_extension("out")).unwrap();
+        assert_eq!(run_fixture(&path, false), expected, "fixture {}", path.display());
+    }
+}
+
+// `--optimize` only applies to the interpreter, the JIT runs every program unoptimized
+#[test]
+fn optimized_fixtures_match_expected_output() {
+    for path in fixtures() {
+        let expected = fs::read_to_string(path.with_extension("out")).unwrap();
+        assert_eq!(run_fixture(&path, true), expected, "fixture {}", path.display());
+    }
+}
@@ -0,0 +1,14 @@
+mod common;
+
+// Literal comparisons must reach the JIT as booleans it can branch on
+#[test]
+fn jit_branches_on_literal_comparison() {
+    let code = "fn main() -> i64 {\n    let a = 3;\n    if (1 < 2) {\n        a = 7;\n    }\n    \
+                return a;\n}\n";
+    let output = common::run(&[], code);
+    assert!(output.status.success());
+
+    let stdout = common::stdout(&output);
+    assert!(stdout.contains("exit with code 7"), "{}", stdout);
+    assert!(!stdout.contains("Unsupported type"), "{}", stdout);
+}
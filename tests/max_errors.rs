@@ -0,0 +1,47 @@
+mod common;
+
+// Runs a `main` with one broken statement per line, `#` is replaced by the line's index.
+// Returns the lines of stderr after checking that nothing about the errors is on stdout.
+fn report(statement: &str, count: usize, max_errors: Option<usize>) -> Vec<String> {
+    let body: String = (0..count)
+        .map(|i| format!("    {}\n", statement.replace('#', &i.to_string())))
+        .collect();
+    let max_errors = max_errors.map(|max_errors| format!("--max-errors={}", max_errors));
+    let args: Vec<&str> = max_errors.iter().map(String::as_str).collect();
+    let output = common::run(&args, &format!("fn main() {{\n{}}}\n", body));
+    let stdout = common::stdout(&output);
+    assert!(!stdout.contains("Error") && !stdout.contains("more errors"), "{}", stdout);
+    common::stderr(&output).lines().map(str::to_string).collect()
+}
+// The number of error reports and the summary lines among them
+fn count(lines: &[String]) -> (usize, Vec<&str>) {
+    let reports = lines.iter().filter(|line| line.contains("Error at")).count();
+    let summaries = lines
+        .iter()
+        .filter(|line| line.starts_with("..."))
+        .map(String::as_str)
+        .collect();
+    (reports, summaries)
+}
+
+#[test]
+fn max_errors_caps_parser_errors() {
+    let lines = report("let = #;", 6, Some(2));
+    assert_eq!(count(&lines), (2, vec!["... and 4 more errors"]));
+}
+
+#[test]
+fn max_errors_caps_lexer_errors() {
+    let lines = report("let a# = $;", 5, Some(3));
+    assert_eq!(count(&lines), (3, vec!["... and 2 more errors"]));
+    let symbols = lines.iter().filter(|line| line.contains("Invalid symbol: $"));
+    assert_eq!(symbols.count(), 3);
+}
+
+#[test]
+fn max_errors_defaults_to_twenty() {
+    let lines = report("let = #;", 25, None);
+    assert_eq!(count(&lines), (20, vec!["... and 5 more errors"]));
+    let lines = report("let = #;", 3, None);
+    assert_eq!(count(&lines), (3, vec![]));
+}
@@ -0,0 +1,16 @@
+mod common;
+
+const PROGRAM: &str = "fn main() -> i64 {\n    return 4;\n}\n";
+
+#[test]
+fn optimize_without_interpret_warns() {
+    let output = common::run(&["--optimize"], PROGRAM);
+    assert!(output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("--optimize only applies with --interpret"), "{}", stderr);
+    assert!(common::stdout(&output).contains("exit with code 4"));
+
+    let output = common::run(&["--optimize", "--interpret"], PROGRAM);
+    assert_eq!(common::stderr(&output), "");
+    assert!(common::stdout(&output).contains("exit with value 4"));
+}
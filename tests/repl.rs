@@ -0,0 +1,48 @@
+mod common;
+
+// Runs bare `nukleus` with the lines on stdin, returning stdout and stderr
+fn repl(lines: &str) -> (String, String) {
+    let output = common::run_with_input(&[], lines);
+    assert!(output.status.success(), "{:?}", output);
+    (common::stdout(&output), common::stderr(&output))
+}
+
+#[test]
+fn repl_keeps_state_between_lines() {
+    let (stdout, stderr) = repl(
+        "let a = 20;\nfn add(x: i64, y: i64) -> i64 { return x + y; }\n\
+         println(\"{}\", add(a, 1));\nreturn add(a, 2);\n",
+    );
+    // A prompt per line and one left when the input ends
+    let expected = format!("Nukleus {}\n> > > 21\n> 22\n> \n", env!("CARGO_PKG_VERSION"));
+    assert_eq!(stdout, expected);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn repl_reports_errors_and_continues() {
+    let (stdout, stderr) = repl("missing();\nlet zero = 0;\nreturn 1 / zero;\nreturn 3;\n");
+    assert!(stdout.contains("3\n"), "{}", stdout);
+    assert_eq!(
+        stderr,
+        "Runtime Error: undefined function `missing`\nRuntime Error: division by zero\n"
+    );
+}
+
+#[test]
+fn repl_exits_at_end_of_input() {
+    let (stdout, stderr) = repl("");
+    assert!(stdout.starts_with("Nukleus "), "{}", stdout);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn repl_redefines_memoized_function() {
+    let (stdout, stderr) = repl(
+        "@memoize fn f(x: i64) -> i64 { return x + 1; }\nreturn f(1);\n\
+         @memoize fn f(x: i64) -> i64 { return x + 2; }\nreturn f(1);\n",
+    );
+    let expected = format!("Nukleus {}\n> > 2\n> > 3\n> \n", env!("CARGO_PKG_VERSION"));
+    assert_eq!(stdout, expected);
+    assert_eq!(stderr, "");
+}
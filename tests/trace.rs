@@ -0,0 +1,23 @@
+mod common;
+
+#[test]
+fn trace_statements_in_order() {
+    let code = "fn main() {\n    let a = 1;\n    println(\"{}\", a);\n}\n";
+    let output = common::run(&["--interpret", "--trace"], code);
+    assert!(output.status.success());
+
+    let stderr = common::stderr(&output);
+    let lines: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("[trace]"))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "[trace] enter main",
+            "[trace] let a = 1",
+            "[trace] println(\"{}\", a)",
+            "[trace] leave main",
+        ]
+    );
+}